@@ -0,0 +1,73 @@
+//! Hot paths of `Vec4<f32>` and `Mat4x4<f32>` arithmetic. Compare the runs
+//! with and without the `simd` feature:
+//!
+//! ```sh
+//! cargo bench --bench simd -- --save-baseline scalar
+//! cargo bench --bench simd --features simd -- --baseline scalar
+//! ```
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use va::graphics::buffer::buffer2d::Buffer2d;
+use va::graphics::coverage_accumulator::FillRule;
+use va::graphics::rasterizate::Rasterizate;
+use va::utils::math::matrix::matrix4x4::Mat4x4;
+use va::utils::math::vector::vector2::Vec2;
+use va::utils::math::vector::vector3::Vec3;
+use va::utils::math::vector::vector4::Vec4;
+
+fn vector(c: &mut Criterion) {
+    let values: Vec<Vec4<f32>> = (0..1024).map(|i| Vec4::new(i as f32, 1.0, -0.5, 0.25)).collect();
+
+    c.bench_function("vec4_mix", |b| b.iter(|| {
+        values.iter()
+            .fold(Vec4::from(0.0), |sum, &value| sum.mix(value, 0.5) * 0.5 + value / 3.0)
+    }));
+
+    let a = Mat4x4::perspective(1.0, 1.5, 0.1, 100.0);
+    let b = Mat4x4::look_at(Vec3::new(1.0, 2.0, 3.0), Vec3::ZERO, Vec3::Y);
+    c.bench_function("mat4x4_mul", |bencher| bencher.iter(|| black_box(a) * black_box(b)));
+    c.bench_function("mat4x4_mul_vec4", |bencher| bencher.iter(|| {
+        values.iter().fold(Vec4::from(0.0), |sum, &value| sum + a * value)
+    }));
+}
+
+fn buffer(c: &mut Criterion) {
+    let size = Vec2::new(256, 256);
+    let gradient = Buffer2d::from_iter(size, (0..size.x * size.y).map(|i| {
+        Vec4::new((i % 256) as f32 / 255.0, (i / 256) as f32 / 255.0, 0.5, 1.0)
+    }));
+
+    c.bench_function("to_r8g8b8a8", |b| b.iter(|| gradient.to_r8g8b8a8()));
+
+    let star: Vec<Vec2<f32>> = (0..10)
+        .map(|i| {
+            let angle = i as f32 * std::f32::consts::PI / 5.0;
+            let radius = if i % 2 == 0 { 120.0 } else { 50.0 };
+            Vec2::new(128.0 + radius * angle.cos(), 128.0 + radius * angle.sin())
+        })
+        .collect();
+
+    c.bench_function("rasterizate_fill_smooth_polygon", |b| b.iter(|| {
+        let mut buffer = Buffer2d::new(size, Vec4::from(0.0));
+        buffer.fill_smooth_polygon(&star, FillRule::NonZero, Vec4::new(1.0, 0.5, 0.0, 1.0));
+        buffer
+    }));
+
+    c.bench_function("rasterizate_fill_triangle", |b| b.iter(|| {
+        let mut buffer = Buffer2d::new(size, Vec4::from(0.0));
+        buffer.fill_triangle(
+            [Vec2::new(10.0, 10.0), Vec2::new(250.0, 40.0), Vec2::new(60.0, 240.0)],
+            [Vec4::new(1.0, 0.0, 0.0, 1.0), Vec4::new(0.0, 1.0, 0.0, 1.0), Vec4::new(0.0, 0.0, 1.0, 0.5)],
+        );
+        buffer
+    }));
+
+    c.bench_function("rasterizate_draw_smooth_circle", |b| b.iter(|| {
+        let mut buffer = Buffer2d::new(size, Vec4::from(0.0));
+        buffer.draw_smooth_circle(Vec2::new(128.0, 128.0), 100.0, Vec4::new(0.2, 0.4, 0.8, 1.0));
+        buffer
+    }));
+}
+
+criterion_group!(benches, vector, buffer);
+criterion_main!(benches);
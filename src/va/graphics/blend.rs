@@ -0,0 +1,336 @@
+use crate::utils::cast::Cast;
+use crate::utils::math::vector::vector2::Vec2;
+use crate::utils::math::vector::vector4::Vec4;
+
+use super::buffer::buffer2d::{Buffer2dRead, Buffer2dWrite};
+use super::color_space::ColorSpace;
+use super::pixel_format::PixelFormat;
+
+/// How a source color is combined with a destination color.
+///
+/// Colors are in straight (not premultiplied) alpha, blending is done with
+/// premultiplied colors.
+// https://www.w3.org/TR/compositing-1/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    Clear,
+    Src,
+    Dst,
+    #[default]
+    SrcOver,
+    DstOver,
+    SrcIn,
+    DstIn,
+    SrcOut,
+    DstOut,
+    SrcAtop,
+    DstAtop,
+    Xor,
+
+    /// Sum of the colors clamped to 1
+    Plus,
+
+    // Separable blend modes, composited as `SrcOver`
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+}
+
+impl BlendMode {
+    pub fn blend(self, src: Vec4<f32>, dst: Vec4<f32>) -> Vec4<f32> {
+        self.blend_with_coverage(src, dst, 1.0)
+    }
+
+    /// `coverage` is the part of the pixel covered by the source, the result
+    /// is interpolated between `dst` and the full coverage blend.
+    pub fn blend_with_coverage(self, src: Vec4<f32>, dst: Vec4<f32>, coverage: f32) -> Vec4<f32> {
+        let coverage = coverage.clamp(0.0, 1.0);
+        if coverage <= 0.0 {
+            return dst;
+        }
+
+        let s = premultiply(src);
+        let d = premultiply(dst);
+        let result = d.mix(self.blend_premultiplied(s, d), coverage);
+
+        unpremultiply(result)
+    }
+
+    /// Blends colors encoded in `color_space`, the blending itself is done in
+    /// linear space
+    pub fn blend_in(self, color_space: ColorSpace, src: Vec4<f32>, dst: Vec4<f32>, coverage: f32) -> Vec4<f32> {
+        let result = self.blend_with_coverage(color_space.to_linear(src), color_space.to_linear(dst), coverage);
+        color_space.from_linear(result)
+    }
+
+    fn blend_premultiplied(self, s: Vec4<f32>, d: Vec4<f32>) -> Vec4<f32> {
+        let (sa, da) = (s.w, d.w);
+
+        match self {
+            BlendMode::Clear => Vec4::from(0.0),
+            BlendMode::Src => s,
+            BlendMode::Dst => d,
+            BlendMode::SrcOver => s + d * (1.0 - sa),
+            BlendMode::DstOver => d + s * (1.0 - da),
+            BlendMode::SrcIn => s * da,
+            BlendMode::DstIn => d * sa,
+            BlendMode::SrcOut => s * (1.0 - da),
+            BlendMode::DstOut => d * (1.0 - sa),
+            BlendMode::SrcAtop => with_alpha(s * da + d * (1.0 - sa), da),
+            BlendMode::DstAtop => with_alpha(d * sa + s * (1.0 - da), sa),
+            BlendMode::Xor => s * (1.0 - da) + d * (1.0 - sa),
+            BlendMode::Plus => (s + d).into_iter().map(|val| val.min(1.0)).collect(),
+            BlendMode::Multiply
+            | BlendMode::Screen
+            | BlendMode::Overlay
+            | BlendMode::Darken
+            | BlendMode::Lighten => {
+                // https://www.w3.org/TR/compositing-1/#blending
+                let mut result = Vec4::new(0.0, 0.0, 0.0, sa + da - sa * da);
+                for i in 0..3 {
+                    let cs = if sa > 0.0 { s[i] / sa } else { 0.0 };
+                    let cd = if da > 0.0 { d[i] / da } else { 0.0 };
+
+                    result[i] = (1.0 - da) * s[i] + (1.0 - sa) * d[i] + sa * da * self.blend_channel(cs, cd);
+                }
+
+                result
+            }
+        }
+    }
+
+    /// Blend function of straight colors of a separable blend mode
+    fn blend_channel(self, cs: f32, cd: f32) -> f32 {
+        let screen = |cs: f32, cd: f32| cs + cd - cs * cd;
+
+        match self {
+            BlendMode::Multiply => cs * cd,
+            BlendMode::Screen => screen(cs, cd),
+
+            // Hard light with swapped colors
+            BlendMode::Overlay => {
+                if cd <= 0.5 {
+                    2.0 * cs * cd
+                }
+                else {
+                    screen(cs, 2.0 * cd - 1.0)
+                }
+            }
+            BlendMode::Darken => cs.min(cd),
+            BlendMode::Lighten => cs.max(cd),
+            _ => cs,
+        }
+    }
+}
+
+fn with_alpha(color: Vec4<f32>, alpha: f32) -> Vec4<f32> {
+    Vec4::new(color.x, color.y, color.z, alpha)
+}
+
+pub(crate) fn premultiply(color: Vec4<f32>) -> Vec4<f32> {
+    Vec4::new(color.x * color.w, color.y * color.w, color.z * color.w, color.w)
+}
+
+pub(crate) fn unpremultiply(color: Vec4<f32>) -> Vec4<f32> {
+    if color.w < 1.0e-6 {
+        return Vec4::from(0.0); // Fully transparent -- R,G,B not important
+    }
+
+    Vec4::new(color.x / color.w, color.y / color.w, color.z / color.w, color.w)
+}
+
+/// Blends `src` onto `dst`, `offset` is the position of the top-left corner
+/// of `src` in `dst`. Pixels of `dst` outside of `src` are not changed.
+///
+/// Colors are blended in linear space, the result is in the color space of
+/// `dst`.
+pub fn composite<D, S>(dst: &mut D, src: &S, offset: Vec2<i32>, mode: BlendMode)
+    where D: Buffer2dWrite<Vec4<f32>>,
+          S: Buffer2dRead<Vec4<f32>>,
+{
+    let dst_size: Vec2<i64> = dst.size().cast();
+    let src_size: Vec2<i64> = src.size().cast();
+    let offset: Vec2<i64> = offset.cast();
+
+    let src_color_space = src.color_space();
+    let dst_color_space = dst.color_space();
+
+    let x0 = offset.x.max(0);
+    let y0 = offset.y.max(0);
+    let x1 = (offset.x + src_size.x).min(dst_size.x);
+    let y1 = (offset.y + src_size.y).min(dst_size.y);
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let dst_position: Vec2<usize> = Vec2::new(x, y).cast();
+            let src_position: Vec2<usize> = Vec2::new(x - offset.x, y - offset.y).cast();
+
+            let value = mode.blend_in(
+                dst_color_space,
+                src_color_space.convert(src.value(src_position), dst_color_space),
+                dst.value(dst_position),
+                1.0,
+            );
+
+            // Safety: the position is clipped by both buffers
+            unsafe { dst.set_value(dst_position, value) };
+        }
+    }
+}
+
+/// Buffer adapter blending every written value with the current one, so all
+/// rasterization methods can draw with `mode`.
+pub struct Buffer2dBlend<'a, B> {
+    buffer: &'a mut B,
+    mode: BlendMode,
+}
+
+impl<'a, B> Buffer2dBlend<'a, B> {
+    pub fn new(buffer: &'a mut B, mode: BlendMode) -> Self {
+        Self {
+            buffer,
+            mode,
+        }
+    }
+
+    pub fn mode(&self) -> BlendMode {
+        self.mode
+    }
+
+    /// # Safety
+    ///
+    /// `position` must be inside of the buffer
+    pub(crate) unsafe fn blend_value<P>(&mut self, position: Vec2<usize>, value: Vec4<f32>, coverage: f32)
+        where B: Buffer2dWrite<P>,
+              P: PixelFormat,
+    {
+        let dst = self.buffer.value(position).to_rgba();
+        let color_space = self.buffer.color_space();
+        self.buffer.set_value(position, P::from_rgba(self.mode.blend_in(color_space, value, dst, coverage)));
+    }
+}
+
+impl<'a, B, P> Buffer2dRead<P> for Buffer2dBlend<'a, B>
+    where B: Buffer2dWrite<P>,
+          P: PixelFormat,
+{
+    fn width(&self) -> usize {
+        self.buffer.width()
+    }
+
+    fn height(&self) -> usize {
+        self.buffer.height()
+    }
+
+    fn size(&self) -> Vec2<usize> {
+        self.buffer.size()
+    }
+
+    fn value(&self, position: Vec2<usize>) -> P {
+        self.buffer.value(position)
+    }
+
+    fn color_space(&self) -> ColorSpace {
+        self.buffer.color_space()
+    }
+}
+
+impl<'a, B, P> Buffer2dWrite<P> for Buffer2dBlend<'a, B>
+    where B: Buffer2dWrite<P>,
+          P: PixelFormat,
+{
+    unsafe fn set_value(&mut self, position: Vec2<usize>, value: P) {
+        self.blend_value::<P>(position, value.to_rgba(), 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::buffer::buffer2d::Buffer2d;
+    use crate::utils::math::is_equal::IsCopyTypeEqual;
+
+    use super::*;
+
+    const EPSILON: f64 = 0.001;
+
+    fn is_equal(a: Vec4<f32>, b: Vec4<f32>) -> bool {
+        (0..4).all(|i| f64::from(a[i]).is_equal(f64::from(b[i]), EPSILON))
+    }
+
+    #[test]
+    fn blend_porter_duff() {
+        let src = Vec4::new(1.0, 0.0, 0.0, 0.5);
+        let dst = Vec4::new(0.0, 0.0, 1.0, 1.0);
+
+        assert!(is_equal(BlendMode::Clear.blend(src, dst), Vec4::from(0.0)));
+        assert!(is_equal(BlendMode::Src.blend(src, dst), src));
+        assert!(is_equal(BlendMode::Dst.blend(src, dst), dst));
+        assert!(is_equal(BlendMode::SrcOver.blend(src, dst), Vec4::new(0.5, 0.0, 0.5, 1.0)));
+        assert!(is_equal(BlendMode::DstOver.blend(src, dst), dst));
+        assert!(is_equal(BlendMode::SrcIn.blend(src, dst), src));
+        assert!(is_equal(BlendMode::DstIn.blend(src, dst), Vec4::new(0.0, 0.0, 1.0, 0.5)));
+        assert!(is_equal(BlendMode::SrcOut.blend(src, dst), Vec4::from(0.0)));
+        assert!(is_equal(BlendMode::DstOut.blend(src, dst), Vec4::new(0.0, 0.0, 1.0, 0.5)));
+        assert!(is_equal(BlendMode::SrcAtop.blend(src, dst), Vec4::new(0.5, 0.0, 0.5, 1.0)));
+        assert!(is_equal(BlendMode::DstAtop.blend(src, dst), Vec4::new(0.0, 0.0, 1.0, 0.5)));
+        assert!(is_equal(BlendMode::Xor.blend(src, dst), Vec4::new(0.0, 0.0, 1.0, 0.5)));
+        assert!(is_equal(BlendMode::Plus.blend(src, dst), Vec4::new(0.5, 0.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn blend_separable() {
+        let src = Vec4::new(0.5, 0.25, 1.0, 1.0);
+        let dst = Vec4::new(0.5, 1.0, 0.25, 1.0);
+
+        assert!(is_equal(BlendMode::Multiply.blend(src, dst), Vec4::new(0.25, 0.25, 0.25, 1.0)));
+        assert!(is_equal(BlendMode::Screen.blend(src, dst), Vec4::new(0.75, 1.0, 1.0, 1.0)));
+        assert!(is_equal(BlendMode::Overlay.blend(src, dst), Vec4::new(0.5, 1.0, 0.5, 1.0)));
+        assert!(is_equal(BlendMode::Darken.blend(src, dst), Vec4::new(0.5, 0.25, 0.25, 1.0)));
+        assert!(is_equal(BlendMode::Lighten.blend(src, dst), Vec4::new(0.5, 1.0, 1.0, 1.0)));
+
+        // Transparent destination keeps the source
+        let transparent = Vec4::from(0.0);
+        assert!(is_equal(BlendMode::Multiply.blend(src, transparent), src));
+    }
+
+    #[test]
+    fn blend_coverage() {
+        let src = Vec4::new(1.0, 1.0, 1.0, 1.0);
+        let dst = Vec4::new(0.0, 0.0, 0.0, 1.0);
+
+        assert!(is_equal(BlendMode::SrcOver.blend_with_coverage(src, dst, 0.25), Vec4::new(0.25, 0.25, 0.25, 1.0)));
+        assert!(is_equal(BlendMode::Clear.blend_with_coverage(src, dst, 0.5), Vec4::new(0.0, 0.0, 0.0, 0.5)));
+        assert!(is_equal(BlendMode::Src.blend_with_coverage(src, dst, 0.0), dst));
+    }
+
+    #[test]
+    fn composite_clipped() {
+        let mut dst = Buffer2d::new(Vec2::new(3, 3), Vec4::new(0.0, 0.0, 0.0, 1.0));
+        let src = Buffer2d::new(Vec2::new(2, 2), Vec4::new(1.0, 1.0, 1.0, 0.5));
+
+        composite(&mut dst, &src, Vec2::new(2, -1), BlendMode::SrcOver);
+
+        for y in 0..3 {
+            for x in 0..3 {
+                let expected = if x == 2 && y == 0 { 0.5 } else { 0.0 };
+                assert!(is_equal(dst.value(Vec2::new(x, y)), Vec4::new(expected, expected, expected, 1.0)));
+            }
+        }
+    }
+
+    #[test]
+    fn composite_srgb() {
+        let mut dst = Buffer2d::new(Vec2::new(1, 1), Vec4::new(0.0, 0.0, 0.0, 1.0)).with_color_space(ColorSpace::Srgb);
+        let src = Buffer2d::new(Vec2::new(1, 1), Vec4::new(1.0, 1.0, 1.0, 0.5)).with_color_space(ColorSpace::Srgb);
+
+        composite(&mut dst, &src, Vec2::ZERO, BlendMode::SrcOver);
+        assert!(is_equal(dst.value(Vec2::ZERO), Vec4::new(0.735, 0.735, 0.735, 1.0)));
+
+        // Linear source is encoded into the destination color space
+        let src = Buffer2d::new(Vec2::new(1, 1), Vec4::new(0.214, 0.214, 0.214, 1.0));
+        composite(&mut dst, &src, Vec2::ZERO, BlendMode::Src);
+        assert!(is_equal(dst.value(Vec2::ZERO), Vec4::new(0.5, 0.5, 0.5, 1.0)));
+    }
+}
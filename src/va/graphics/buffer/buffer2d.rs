@@ -3,13 +3,14 @@ use std::path::Path;
 use std::slice::{Iter, IterMut};
 use std::vec::IntoIter;
 
-use anyhow::ensure;
-use png::ColorType;
 
-use crate::graphics::image::save_image;
+use crate::graphics::blend::{BlendMode, Buffer2dBlend};
+use crate::graphics::color_space::ColorSpace;
+use crate::graphics::image::codec::ImageCodecs;
+use crate::graphics::pixel_format::PixelFormat;
 use crate::utils::cast::Cast;
 use crate::utils::math::geometry::rect::Rect;
-use crate::utils::number::{Number, Float};
+use crate::utils::math::simd::FloatLanes;
 use crate::utils::math::vector::vector2::Vec2;
 use crate::utils::math::vector::vector4::Vec4;
 
@@ -18,6 +19,11 @@ pub trait Buffer2dRead<T = Self> {
     fn height(&self) -> usize;
     fn size(&self) -> Vec2<usize>;
     fn value(&self, position: Vec2<usize>) -> T;
+
+    /// Encoding of color values, blending happens in linear space
+    fn color_space(&self) -> ColorSpace {
+        ColorSpace::Linear
+    }
 }
 
 pub trait Buffer2dWrite<T = Self>: Buffer2dRead<T> {
@@ -30,6 +36,7 @@ pub struct Buffer2d<T>
 {
     size: Vec2<usize>,
     buffer: Vec<T>,
+    color_space: Option<ColorSpace>,
 }
 
 impl<T> Buffer2d<T> 
@@ -39,6 +46,7 @@ impl<T> Buffer2d<T>
         Self {
             size,
             buffer: vec![value; size.x * size.y],
+            color_space: None,
         }
     }
 
@@ -52,9 +60,31 @@ impl<T> Buffer2d<T>
         Self {
             size,
             buffer,
+            color_space: None,
         }
     }
 
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = Some(color_space);
+        self
+    }
+
+    /// Only changes the tag, values are not converted
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.color_space = Some(color_space);
+    }
+
+    /// `None` for buffers never tagged with a color space. They are blended
+    /// as linear values, but encoded like before color spaces were tracked.
+    pub fn color_space_tag(&self) -> Option<ColorSpace> {
+        self.color_space
+    }
+
+    pub(crate) fn with_color_space_tag(mut self, color_space: Option<ColorSpace>) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
     pub fn iter(&self) -> Iter<T> {
         self.buffer.iter()
     }
@@ -122,6 +152,7 @@ impl<T> Clone for Buffer2d<T>
         Self { 
             size: self.size, 
             buffer: self.buffer.clone(), 
+            color_space: self.color_space,
         }
     }
 }
@@ -155,6 +186,10 @@ impl<T> Buffer2dRead<T> for Buffer2d<T>
     fn value(&self, position: Vec2<usize>) -> T {
         self.buffer[self.size.x * position.y + position.x].clone()
     }
+
+    fn color_space(&self) -> ColorSpace {
+        self.color_space.unwrap_or_default()
+    }
 }
 
 impl<T> Buffer2dWrite<T> for Buffer2d<T>
@@ -166,21 +201,27 @@ impl<T> Buffer2dWrite<T> for Buffer2d<T>
 }
 
 impl<T> Buffer2d<Vec4<T>> 
-    where T: Number + Float,
-          <T as std::ops::Mul>::Output: Float + Cast<u8>,
+    where T: FloatLanes,
 {
     pub fn to_r8g8b8a8(&self) -> Vec<u8> {
         let mut output = Vec::with_capacity(self.buffer.len() * 4);
-        for pixel in &self.buffer {
-            for i in 0..4 {
-                output.push((pixel[i].clamp(Float::new(0.0), Float::new(1.0)) * Float::new(255.0)).round().cast());
-            }
+        for &pixel in &self.buffer {
+            output.extend_from_slice(&T::to_unorm8(pixel.into()));
         }
 
         output
     }
 }
 
+impl<P> Buffer2d<P>
+    where P: PixelFormat,
+{
+    /// Rasterization methods of the returned adapter blend with `mode`
+    pub fn blended(&mut self, mode: BlendMode) -> Buffer2dBlend<'_, Self> {
+        Buffer2dBlend::new(self, mode)
+    }
+}
+
 pub struct Buffer2dSlice<'a, T> 
     where T: Clone,
 {
@@ -214,6 +255,10 @@ impl<'a, T> Buffer2dRead<T> for Buffer2dSlice<'a, T>
     fn value(&self, position: Vec2<usize>) -> T {
         self.buffer.value(self.rect.p1 + position)
     }
+
+    fn color_space(&self) -> ColorSpace {
+        self.buffer.color_space()
+    }
 }
 
 pub struct Buffer2dMutSlice<'a, T> 
@@ -257,6 +302,10 @@ impl<'a, T> Buffer2dRead<T> for Buffer2dMutSlice<'a, T>
     fn value(&self, position: Vec2<usize>) -> T {
         self.buffer.value(self.rect.p1 + position)
     }
+
+    fn color_space(&self) -> ColorSpace {
+        self.buffer.color_space()
+    }
 }
 
 impl<'a, T> Buffer2dWrite<T> for Buffer2dMutSlice<'a, T>
@@ -267,19 +316,10 @@ impl<'a, T> Buffer2dWrite<T> for Buffer2dMutSlice<'a, T>
     }
 }
 
-pub fn save_buffer<T, U>(path: T, buffer: &Buffer2d<Vec4<U>>) -> anyhow::Result<()>
-        where T: AsRef<Path>,
-              U: Number + Float,
-              <U as std::ops::Mul>::Output: Float + Cast<u8>,
+/// The encoder is picked by the file extension, see [`ImageCodecs`]
+pub fn save_buffer<T, P>(path: T, buffer: &Buffer2d<P>) -> anyhow::Result<()>
+    where T: AsRef<Path>,
+          P: PixelFormat,
 {
-    ensure!(buffer.width() <= u32::MAX as usize && buffer.height() <= u32::MAX as usize, "Invalid buffer size for saving in a file");
-    let data = buffer.to_r8g8b8a8();
-    
-    save_image(
-        path, 
-        &data, 
-        ColorType::Rgba, 
-        buffer.width() as u32, 
-        buffer.height() as u32,
-    )
-}
\ No newline at end of file
+    ImageCodecs::new().save(path, &buffer.convert())
+}
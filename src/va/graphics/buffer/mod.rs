@@ -1 +1,2 @@
 pub mod buffer2d;
+pub mod transform;
@@ -0,0 +1,445 @@
+use std::f64::consts::PI;
+
+use crate::graphics::blend::{premultiply, unpremultiply};
+use crate::graphics::color_space::ColorSpace;
+use crate::utils::math::geometry::rect::Rect;
+use crate::utils::math::matrix::matrix3x3::Mat3x3;
+use crate::utils::math::vector::vector2::Vec2;
+use crate::utils::math::vector::vector3::Vec3;
+use crate::utils::math::vector::vector4::Vec4;
+
+use super::buffer2d::{Buffer2d, Buffer2dRead};
+
+/// Reconstruction filter used by [`Buffer2d::resize`] and
+/// [`Buffer2d::transform`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResampleFilter {
+    Nearest,
+    #[default]
+    Bilinear,
+
+    /// Catmull-Rom spline
+    Bicubic,
+
+    /// Lanczos with 3 lobes, the sharpest one
+    Lanczos3,
+}
+
+impl ResampleFilter {
+    /// Distance from the center where the kernel is not zero
+    fn support(self) -> f64 {
+        match self {
+            ResampleFilter::Nearest => 0.5,
+            ResampleFilter::Bilinear => 1.0,
+            ResampleFilter::Bicubic => 2.0,
+            ResampleFilter::Lanczos3 => 3.0,
+        }
+    }
+
+    fn kernel(self, x: f64) -> f64 {
+        let x = x.abs();
+
+        match self {
+            ResampleFilter::Nearest => if x < 0.5 { 1.0 } else { 0.0 },
+            ResampleFilter::Bilinear => (1.0 - x).max(0.0),
+            ResampleFilter::Bicubic => {
+                // https://en.wikipedia.org/wiki/Bicubic_interpolation#Bicubic_convolution_algorithm
+                const A: f64 = -0.5;
+
+                if x < 1.0 {
+                    ((A + 2.0) * x - (A + 3.0)) * x * x + 1.0
+                }
+                else if x < 2.0 {
+                    ((A * x - 5.0 * A) * x + 8.0 * A) * x - 4.0 * A
+                }
+                else {
+                    0.0
+                }
+            }
+            ResampleFilter::Lanczos3 => {
+                // https://en.wikipedia.org/wiki/Lanczos_resampling
+                if x < 3.0 {
+                    sinc(x) * sinc(x / 3.0)
+                }
+                else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1.0e-8 {
+        1.0
+    }
+    else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+impl<T> Buffer2d<T>
+    where T: Clone,
+{
+    /// Returns the part of the buffer inside of `rect`, the rect is clipped by
+    /// the buffer
+    pub fn crop(&self, rect: Rect<usize>) -> Self {
+        let p1 = Vec2::new(rect.p1.x.min(self.width()), rect.p1.y.min(self.height()));
+        let p2 = Vec2::new(rect.p2.x.clamp(p1.x, self.width()), rect.p2.y.clamp(p1.y, self.height()));
+
+        self.map_positions(p2 - p1, |x, y| Vec2::new(p1.x + x, p1.y + y))
+    }
+
+    /// Mirrors the buffer left to right
+    pub fn flip_horizontal(&self) -> Self {
+        let width = self.width();
+        self.map_positions(self.size(), |x, y| Vec2::new(width - 1 - x, y))
+    }
+
+    /// Mirrors the buffer top to bottom
+    pub fn flip_vertical(&self) -> Self {
+        let height = self.height();
+        self.map_positions(self.size(), |x, y| Vec2::new(x, height - 1 - y))
+    }
+
+    /// Rotates clockwise (Y axis is directed down)
+    pub fn rotate_90(&self) -> Self {
+        let height = self.height();
+        let size = Vec2::new(self.height(), self.width());
+
+        self.map_positions(size, |x, y| Vec2::new(y, height - 1 - x))
+    }
+
+    pub fn rotate_180(&self) -> Self {
+        let (width, height) = (self.width(), self.height());
+        self.map_positions(self.size(), |x, y| Vec2::new(width - 1 - x, height - 1 - y))
+    }
+
+    /// Rotates counterclockwise (Y axis is directed down)
+    pub fn rotate_270(&self) -> Self {
+        let width = self.width();
+        let size = Vec2::new(self.height(), self.width());
+
+        self.map_positions(size, |x, y| Vec2::new(width - 1 - y, x))
+    }
+
+    /// Creates a buffer of `size`, `source` returns the position in `self`
+    /// for every position of the new buffer
+    fn map_positions<F>(&self, size: Vec2<usize>, source: F) -> Self
+        where F: Fn(usize, usize) -> Vec2<usize>,
+    {
+        let values = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| (x, y)))
+            .map(|(x, y)| self.value(source(x, y)));
+
+        Buffer2d::from_iter(size, values).with_color_space_tag(self.color_space_tag())
+    }
+}
+
+impl Buffer2d<Vec4<f32>> {
+    /// Scales the buffer to `size`. Filters except nearest are widened while
+    /// downscaling, so every source pixel contributes to the result.
+    ///
+    /// Colors are interpolated in linear space with premultiplied alpha, so
+    /// transparent pixels don't darken edges.
+    pub fn resize(&self, size: Vec2<usize>, filter: ResampleFilter) -> Self {
+        let color_space = self.color_space();
+        if color_space != ColorSpace::Linear {
+            return self.to_color_space(ColorSpace::Linear)
+                .resize(size, filter)
+                .to_color_space(color_space);
+        }
+
+        if size.x == 0 || size.y == 0 || self.width() == 0 || self.height() == 0 {
+            return Buffer2d::new(size, Vec4::from(0.0)).with_color_space_tag(self.color_space_tag());
+        }
+
+        let premultiplied: Vec<Vec4<f32>> = self.iter().map(|&value| premultiply(value)).collect();
+
+        // Horizontal pass, then vertical one
+        let weights = resample_weights(self.width(), size.x, filter);
+        let mut rows = Vec::with_capacity(size.x * self.height());
+        for y in 0..self.height() {
+            let row = &premultiplied[y * self.width()..(y + 1) * self.width()];
+            rows.extend(weights.iter().map(|(start, weights)| weighted_sum(weights, |i| row[start + i])));
+        }
+
+        let weights = resample_weights(self.height(), size.y, filter);
+        let mut values = Vec::with_capacity(size.x * size.y);
+        for (start, weights) in &weights {
+            for x in 0..size.x {
+                values.push(finish(weighted_sum(weights, |i| rows[(start + i) * size.x + x])));
+            }
+        }
+
+        Buffer2d::from_iter(size, values.into_iter()).with_color_space_tag(self.color_space_tag())
+    }
+
+    /// Maps the buffer by the affine or projective `transform` from
+    /// positions of this buffer to positions of a new buffer of `size`.
+    /// Pixels without source are `background`.
+    ///
+    /// The filter is sampled without widening, so big downscales alias.
+    pub fn transform(&self, transform: &Mat3x3<f64>, size: Vec2<usize>, filter: ResampleFilter, background: Vec4<f32>)
+        -> Self
+    {
+        let color_space = self.color_space();
+        if color_space != ColorSpace::Linear {
+            return self.to_color_space(ColorSpace::Linear)
+                .transform(transform, size, filter, color_space.to_linear(background))
+                .to_color_space(color_space);
+        }
+
+        let inverse = match transform.inverse() {
+            Some(val) => val,
+            None => return Buffer2d::new(size, background).with_color_space_tag(self.color_space_tag()),
+        };
+
+        let background = premultiply(background);
+        let support = filter.support();
+
+        let mut values = Vec::with_capacity(size.x * size.y);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                // Pixel centers
+                let position = inverse * Vec3::new(x as f64 + 0.5, y as f64 + 0.5, 1.0);
+                if position.z.abs() < f64::EPSILON {
+                    values.push(unpremultiply(background));
+                    continue;
+                }
+
+                let center = Vec2::new(position.x / position.z - 0.5, position.y / position.z - 0.5);
+
+                let x0 = (center.x - support).ceil() as i64;
+                let y0 = (center.y - support).ceil() as i64;
+                let x1 = (center.x + support).floor() as i64;
+                let y1 = (center.y + support).floor() as i64;
+
+                let mut sum = Vec4::from(0.0);
+                let mut weight_sum = 0.0;
+                for sy in y0..=y1 {
+                    let wy = filter.kernel(sy as f64 - center.y);
+                    if wy == 0.0 {
+                        continue;
+                    }
+
+                    for sx in x0..=x1 {
+                        let weight = wy * filter.kernel(sx as f64 - center.x);
+                        if weight == 0.0 {
+                            continue;
+                        }
+
+                        // Pixels outside of the source are the background, so
+                        // edges are antialiased
+                        let is_inside = sx >= 0 && sy >= 0 && (sx as usize) < self.width() && (sy as usize) < self.height();
+                        let value = if is_inside {
+                            premultiply(self.value(Vec2::new(sx as usize, sy as usize)))
+                        }
+                        else {
+                            background
+                        };
+
+                        sum += value * weight as f32;
+                        weight_sum += weight;
+                    }
+                }
+
+                values.push(if weight_sum > 0.0 {
+                    finish(sum * (1.0 / weight_sum) as f32)
+                }
+                else {
+                    unpremultiply(background)
+                });
+            }
+        }
+
+        Buffer2d::from_iter(size, values.into_iter()).with_color_space_tag(self.color_space_tag())
+    }
+
+    /// Rotates clockwise (Y axis is directed down) by `angle` radians around
+    /// the center. The result is extended to contain the whole image.
+    pub fn rotate(&self, angle: f64, filter: ResampleFilter, background: Vec4<f32>) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let (width, height) = (self.width() as f64, self.height() as f64);
+
+        let new_width = (width * cos.abs() + height * sin.abs()).round();
+        let new_height = (width * sin.abs() + height * cos.abs()).round();
+
+        let transform = Mat3x3::with_translate(Vec2::new(new_width * 0.5, new_height * 0.5))
+            * Mat3x3::with_rotation(angle)
+            * Mat3x3::with_translate(Vec2::new(-width * 0.5, -height * 0.5));
+
+        self.transform(&transform, Vec2::new(new_width as usize, new_height as usize), filter, background)
+    }
+}
+
+/// For every destination pixel returns the first source pixel and weights of
+/// the source pixels. Out of range pixels are clamped to the edges.
+fn resample_weights(src_len: usize, dst_len: usize, filter: ResampleFilter) -> Vec<(usize, Vec<f32>)> {
+    let scale = src_len as f64 / dst_len as f64;
+
+    // Downscaling widens the filter
+    let filter_scale = scale.max(1.0);
+    let support = filter.support() * filter_scale;
+
+    (0..dst_len)
+        .map(|i| {
+            let center = (i as f64 + 0.5) * scale - 0.5;
+            if filter == ResampleFilter::Nearest {
+                let index = (center.round().max(0.0) as usize).min(src_len - 1);
+                return (index, vec![1.0]);
+            }
+
+            let first = (center - support).ceil() as i64;
+            let last = (center + support).floor() as i64;
+
+            let start = first.clamp(0, src_len as i64 - 1) as usize;
+            let end = last.clamp(0, src_len as i64 - 1) as usize;
+            let mut weights = vec![0.0; end - start + 1];
+
+            for j in first..=last {
+                let weight = filter.kernel((j as f64 - center) / filter_scale);
+                let index = j.clamp(0, src_len as i64 - 1) as usize;
+                weights[index - start] += weight;
+            }
+
+            let sum: f64 = weights.iter().sum();
+            let weights = if sum.abs() > f64::EPSILON {
+                weights.into_iter().map(|weight| (weight / sum) as f32).collect()
+            }
+            else {
+                // The kernel is between pixels
+                let mut weights = vec![0.0; end - start + 1];
+                let index = (center.round().max(0.0) as usize).clamp(start, end);
+                weights[index - start] = 1.0;
+                weights
+            };
+
+            (start, weights)
+        })
+        .collect()
+}
+
+fn weighted_sum<F>(weights: &[f32], value: F) -> Vec4<f32>
+    where F: Fn(usize) -> Vec4<f32>,
+{
+    weights.iter()
+        .enumerate()
+        .fold(Vec4::from(0.0), |sum, (i, &weight)| sum + value(i) * weight)
+}
+
+/// Clamps overshoots of sharp filters and removes premultiplication
+fn finish(value: Vec4<f32>) -> Vec4<f32> {
+    let alpha = value.w.clamp(0.0, 1.0);
+    let color = Vec4::new(value.x.max(0.0), value.y.max(0.0), value.z.max(0.0), alpha);
+
+    unpremultiply(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::buffer::buffer2d::Buffer2dWrite;
+    use crate::utils::math::is_equal::IsCopyTypeEqual;
+
+    use super::*;
+
+    const EPSILON: f64 = 0.001;
+
+    fn gradient(size: Vec2<usize>) -> Buffer2d<Vec4<f32>> {
+        let values = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| (x, y)))
+            .map(|(x, y)| Vec4::new(x as f32, y as f32, 0.0, 1.0));
+
+        Buffer2d::from_iter(size, values)
+    }
+
+    #[test]
+    fn buffer2d_rotate_flip_crop() {
+        let buffer = Buffer2d::from_iter(Vec2::new(3, 2), 0..6);
+
+        // 0 1 2
+        // 3 4 5
+        assert_eq!(buffer.rotate_90().buffer(), &vec![3, 0, 4, 1, 5, 2]);
+        assert_eq!(buffer.rotate_180().buffer(), &vec![5, 4, 3, 2, 1, 0]);
+        assert_eq!(buffer.rotate_270().buffer(), &vec![2, 5, 1, 4, 0, 3]);
+        assert_eq!(buffer.rotate_90().size(), Vec2::new(2, 3));
+
+        assert_eq!(buffer.flip_horizontal().buffer(), &vec![2, 1, 0, 5, 4, 3]);
+        assert_eq!(buffer.flip_vertical().buffer(), &vec![3, 4, 5, 0, 1, 2]);
+
+        let cropped = buffer.crop(Rect::new(Vec2::new(1, 1), Vec2::new(5, 5)));
+        assert_eq!(cropped.size(), Vec2::new(2, 1));
+        assert_eq!(cropped.buffer(), &vec![4, 5]);
+    }
+
+    #[test]
+    fn buffer2d_resize_constant() {
+        let color = Vec4::new(0.2, 0.4, 0.6, 0.8);
+        let buffer = Buffer2d::new(Vec2::new(7, 5), color);
+
+        for filter in [ResampleFilter::Nearest, ResampleFilter::Bilinear, ResampleFilter::Bicubic, ResampleFilter::Lanczos3] {
+            for size in [Vec2::new(3, 2), Vec2::new(16, 9)] {
+                let resized = buffer.resize(size, filter);
+                assert_eq!(resized.size(), size);
+                assert!(resized.iter().all(|&value| value.is_equal(color, EPSILON)));
+            }
+        }
+    }
+
+    #[test]
+    fn buffer2d_resize() {
+        let buffer = gradient(Vec2::new(4, 2));
+
+        let nearest = buffer.resize(Vec2::new(8, 4), ResampleFilter::Nearest);
+        assert!(nearest.value(Vec2::new(2, 0)).is_equal(Vec4::new(1.0, 0.0, 0.0, 1.0), EPSILON));
+        assert!(nearest.value(Vec2::new(3, 3)).is_equal(Vec4::new(1.0, 1.0, 0.0, 1.0), EPSILON));
+
+        // The widened filter is centered between source pixels
+        let bilinear = gradient(Vec2::new(8, 2)).resize(Vec2::new(4, 1), ResampleFilter::Bilinear);
+        assert!(bilinear.value(Vec2::new(1, 0)).is_equal(Vec4::new(2.5, 0.5, 0.0, 1.0), EPSILON));
+        assert!(bilinear.value(Vec2::new(2, 0)).is_equal(Vec4::new(4.5, 0.5, 0.0, 1.0), EPSILON));
+    }
+
+    #[test]
+    fn buffer2d_resize_srgb() {
+        let values = [Vec4::new(0.0, 0.0, 0.0, 1.0), Vec4::from(1.0)];
+        let buffer = Buffer2d::from_iter(Vec2::new(2, 1), values.into_iter()).with_color_space(ColorSpace::Srgb);
+
+        let resized = buffer.resize(Vec2::new(1, 1), ResampleFilter::Bilinear);
+        assert_eq!(resized.color_space(), ColorSpace::Srgb);
+        assert!(resized.value(Vec2::ZERO).is_equal(Vec4::new(0.735, 0.735, 0.735, 1.0), EPSILON));
+    }
+
+    #[test]
+    fn buffer2d_resize_transparent_edges() {
+        let mut buffer = Buffer2d::new(Vec2::new(2, 1), Vec4::from(0.0));
+        unsafe { buffer.set_value(Vec2::new(0, 0), Vec4::new(1.0, 1.0, 1.0, 1.0)) };
+
+        let resized = buffer.resize(Vec2::new(1, 1), ResampleFilter::Bilinear);
+        assert!(resized.value(Vec2::ZERO).is_equal(Vec4::new(1.0, 1.0, 1.0, 0.5), EPSILON));
+    }
+
+    #[test]
+    fn buffer2d_transform() {
+        let buffer = gradient(Vec2::new(4, 4));
+        let background = Vec4::new(0.0, 0.0, 1.0, 1.0);
+
+        let translate = Mat3x3::with_translate(Vec2::new(1.0, 2.0));
+        let moved = buffer.transform(&translate, Vec2::new(4, 4), ResampleFilter::Bilinear, background);
+        assert!(moved.value(Vec2::new(1, 2)).is_equal(Vec4::new(0.0, 0.0, 0.0, 1.0), EPSILON));
+        assert!(moved.value(Vec2::new(3, 3)).is_equal(Vec4::new(2.0, 1.0, 0.0, 1.0), EPSILON));
+        assert!(moved.value(Vec2::new(0, 0)).is_equal(background, EPSILON));
+
+        // Quarter turns match the exact rotations
+        let rotated = buffer.rotate(PI * 0.5, ResampleFilter::Nearest, background);
+        assert_eq!(rotated.size(), buffer.size());
+        for (a, b) in rotated.iter().zip(buffer.rotate_90().iter()) {
+            assert!(a.is_equal(*b, EPSILON));
+        }
+
+        // Singular transforms have no inverse, only the background is left
+        let squashed = Mat3x3::with_scale(Vec2::new(1.0, 0.0));
+        let squashed = buffer.transform(&squashed, Vec2::new(4, 4), ResampleFilter::Bilinear, background);
+        assert!(squashed.iter().all(|value| value.is_equal(background, EPSILON)));
+    }
+}
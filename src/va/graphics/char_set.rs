@@ -0,0 +1,34 @@
+use std::ops::RangeInclusive;
+
+/// Characters which are rasterized into a font atlas.
+#[derive(Clone, Debug, Default)]
+pub enum CharSet {
+    /// Printable ASCII characters
+    #[default]
+    Ascii,
+
+    /// Printable ASCII and Latin-1 Supplement characters
+    Latin1,
+
+    Ranges(Vec<RangeInclusive<char>>),
+    Chars(String),
+}
+
+impl CharSet {
+    const ASCII: RangeInclusive<char> = ' '..='~';
+    const LATIN1_SUPPLEMENT: RangeInclusive<char> = '\u{A0}'..='\u{FF}';
+
+    /// Returns sorted unique characters.
+    pub fn chars(&self) -> Vec<char> {
+        let mut chars: Vec<char> = match self {
+            CharSet::Ascii => Self::ASCII.collect(),
+            CharSet::Latin1 => Self::ASCII.chain(Self::LATIN1_SUPPLEMENT).collect(),
+            CharSet::Ranges(ranges) => ranges.iter().cloned().flatten().collect(),
+            CharSet::Chars(string) => string.chars().collect(),
+        };
+
+        chars.sort_unstable();
+        chars.dedup();
+        chars
+    }
+}
@@ -0,0 +1,107 @@
+use crate::utils::math::vector::vector4::Vec4;
+
+/// Encoding of color values, alpha is always linear
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    #[default]
+    Linear,
+
+    /// sRGB transfer function, the usual encoding of 8-bit images
+    Srgb,
+}
+
+impl ColorSpace {
+    pub fn to_linear(self, color: Vec4<f32>) -> Vec4<f32> {
+        match self {
+            ColorSpace::Linear => color,
+            ColorSpace::Srgb => map_rgb(color, srgb_to_linear),
+        }
+    }
+
+    pub fn from_linear(self, color: Vec4<f32>) -> Vec4<f32> {
+        match self {
+            ColorSpace::Linear => color,
+            ColorSpace::Srgb => map_rgb(color, linear_to_srgb),
+        }
+    }
+
+    pub fn convert(self, color: Vec4<f32>, color_space: ColorSpace) -> Vec4<f32> {
+        if self == color_space {
+            return color;
+        }
+
+        color_space.from_linear(self.to_linear(color))
+    }
+
+    /// Interpolates colors of this color space in linear space
+    pub fn mix(self, a: Vec4<f32>, b: Vec4<f32>, k: f32) -> Vec4<f32> {
+        self.from_linear(self.to_linear(a).mix(self.to_linear(b), k))
+    }
+}
+
+fn map_rgb<F>(color: Vec4<f32>, f: F) -> Vec4<f32>
+    where F: Fn(f32) -> f32,
+{
+    Vec4::new(f(color.x), f(color.y), f(color.z), color.w)
+}
+
+// https://en.wikipedia.org/wiki/SRGB#Transfer_function_(%22gamma%22)
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    }
+    else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    }
+    else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::math::is_equal::IsCopyTypeEqual;
+
+    use super::*;
+
+    const EPSILON: f64 = 0.001;
+
+    #[test]
+    fn srgb_transfer() {
+        assert!(f64::from(srgb_to_linear(0.5)).is_equal(0.214, EPSILON));
+        assert!(f64::from(linear_to_srgb(0.214)).is_equal(0.5, EPSILON));
+        assert!(f64::from(srgb_to_linear(0.02)).is_equal(0.02 / 12.92, EPSILON));
+
+        for i in 0..=255 {
+            let value = i as f32 / 255.0;
+            assert!(f64::from(linear_to_srgb(srgb_to_linear(value))).is_equal(value.into(), EPSILON));
+        }
+
+        assert_eq!(srgb_to_linear(0.0), 0.0);
+        assert!(f64::from(srgb_to_linear(1.0)).is_equal(1.0, EPSILON));
+    }
+
+    #[test]
+    fn color_space_mix() {
+        let black = Vec4::new(0.0, 0.0, 0.0, 1.0);
+        let white = Vec4::new(1.0, 1.0, 1.0, 0.0);
+
+        let linear = ColorSpace::Linear.mix(black, white, 0.5);
+        assert!(f64::from(linear.x).is_equal(0.5, EPSILON));
+
+        // Half of the light is brighter than the middle of sRGB values
+        let srgb = ColorSpace::Srgb.mix(black, white, 0.5);
+        assert!(f64::from(srgb.x).is_equal(0.735, EPSILON));
+        assert!(f64::from(srgb.w).is_equal(0.5, EPSILON));
+
+        let color = ColorSpace::Linear.convert(Vec4::new(0.214, 0.0, 1.0, 0.25), ColorSpace::Srgb);
+        assert!(f64::from(color.x).is_equal(0.5, EPSILON));
+        assert!(f64::from(color.w).is_equal(0.25, EPSILON));
+    }
+}
@@ -0,0 +1,313 @@
+use crate::utils::math::vector::vector2::Vec2;
+
+use super::buffer::buffer2d::Buffer2d;
+
+/// Rule deciding which areas of overlapping contours are inside of a shape.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FillRule {
+    /// Inside if the winding number is not zero (TrueType, CFF, SVG default).
+    #[default]
+    NonZero,
+
+    /// Inside if the winding number is odd.
+    EvenOdd,
+}
+
+impl FillRule {
+    /// Converts an accumulated winding value into pixel coverage.
+    pub fn coverage(self, winding: f32) -> f32 {
+        match self {
+            FillRule::NonZero => winding.abs().min(1.0),
+            FillRule::EvenOdd => {
+                let value = winding.abs() % 2.0;
+                if value > 1.0 {
+                    2.0 - value
+                }
+                else {
+                    value
+                }
+            }
+        }
+    }
+
+    pub fn is_inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// Signed-area coverage accumulator.
+///
+/// Every line adds the signed area it covers to the cells of its row, so after
+/// a prefix sum over the row each cell contains the winding-weighted coverage
+/// of the pixel.
+// https://medium.com/@raphlinus/inside-the-fastest-font-renderer-in-the-world-75ae5270c445
+pub struct CoverageAccumulator {
+    size: Vec2<usize>,
+    stride: usize,
+    accumulation: Vec<f32>,
+}
+
+impl CoverageAccumulator {
+    pub fn new(size: Vec2<usize>) -> Self {
+        // Two extra cells per row: lines clamped to the right border write
+        // into column `size.x` and its neighbour
+        let stride = size.x + 2;
+
+        Self {
+            size,
+            stride,
+            accumulation: vec![0.0; stride * size.y],
+        }
+    }
+
+    pub fn size(&self) -> Vec2<usize> {
+        self.size
+    }
+
+    /// Adds a closed polygon, the last point is connected with the first one.
+    pub fn add_outline(&mut self, points: &[Vec2<f32>]) {
+        if points.len() < 2 {
+            return;
+        }
+
+        for i in 1..points.len() {
+            self.add_line(points[i - 1], points[i]);
+        }
+
+        self.add_line(*points.last().unwrap(), points[0]);
+    }
+
+    pub fn add_line(&mut self, p0: Vec2<f32>, p1: Vec2<f32>) {
+        let width = self.size.x as f32;
+
+        // Split the line by the left and right borders, so all parts outside
+        // of the buffer become vertical lines on the border
+        let mut t_values = [0.0, 1.0, 1.0, 1.0];
+        let mut count = 1;
+
+        if p0.x != p1.x {
+            for border in [0.0, width] {
+                let t = (border - p0.x) / (p1.x - p0.x);
+                if 0.0 < t && t < 1.0 {
+                    t_values[count] = t;
+                    count += 1;
+                }
+            }
+        }
+
+        t_values[count] = 1.0;
+        t_values[..=count].sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+
+        for i in 1..=count {
+            let begin = p0 + (p1 - p0) * t_values[i - 1];
+            let end = p0 + (p1 - p0) * t_values[i];
+
+            self.add_clamped_line(
+                Vec2::new(begin.x.clamp(0.0, width), begin.y),
+                Vec2::new(end.x.clamp(0.0, width), end.y),
+            );
+        }
+    }
+
+    fn add_clamped_line(&mut self, p0: Vec2<f32>, p1: Vec2<f32>) {
+        if p0.y == p1.y || !p0.y.is_finite() || !p1.y.is_finite() {
+            return;
+        }
+
+        // Lines going down add coverage, lines going up remove it
+        let (direction, p0, p1) = if p0.y < p1.y {
+            (1.0, p0, p1)
+        }
+        else {
+            (-1.0, p1, p0)
+        };
+
+        let height = self.size.y as f32;
+        if p1.y <= 0.0 || p0.y >= height {
+            return;
+        }
+
+        let dxdy = (p1.x - p0.x) / (p1.y - p0.y);
+
+        let y_begin = p0.y.max(0.0);
+        let y_end = p1.y.min(height);
+
+        let mut x = p0.x + (y_begin - p0.y) * dxdy;
+
+        for y in y_begin as usize..y_end.ceil() as usize {
+            let row = y * self.stride;
+            let y = y as f32;
+
+            let dy = (y + 1.0).min(y_end) - y.max(y_begin);
+            let x_next = x + dxdy * dy;
+            let d = dy * direction;
+
+            let (x0, x1) = if x < x_next {
+                (x, x_next)
+            }
+            else {
+                (x_next, x)
+            };
+
+            let x0_floor = x0.floor();
+            let x0i = x0_floor as usize;
+            let x1_ceil = x1.ceil();
+            let x1i = x1_ceil as usize;
+
+            if x1i <= x0i + 1 {
+                // The line is inside of one cell
+                let x_middle = 0.5 * (x + x_next) - x0_floor;
+                self.accumulation[row + x0i] += d - d * x_middle;
+                self.accumulation[row + x0i + 1] += d * x_middle;
+            }
+            else {
+                let s = (x1 - x0).recip();
+                let x0_fract = x0 - x0_floor;
+                let a0 = 0.5 * s * (1.0 - x0_fract).powi(2);
+                let x1_fract = x1 - x1_ceil + 1.0;
+                let a_middle = 0.5 * s * x1_fract.powi(2);
+
+                self.accumulation[row + x0i] += d * a0;
+
+                if x1i == x0i + 2 {
+                    self.accumulation[row + x0i + 1] += d * (1.0 - a0 - a_middle);
+                }
+                else {
+                    let a1 = s * (1.5 - x0_fract);
+                    self.accumulation[row + x0i + 1] += d * (a1 - a0);
+
+                    for xi in x0i + 2..x1i - 1 {
+                        self.accumulation[row + xi] += d * s;
+                    }
+
+                    let a2 = a1 + (x1i - x0i - 3) as f32 * s;
+                    self.accumulation[row + x1i - 1] += d * (1.0 - a2 - a_middle);
+                }
+
+                self.accumulation[row + x1i] += d * a_middle;
+            }
+
+            x = x_next;
+        }
+    }
+
+    /// Returns pixel coverage in the range `[0.0; 1.0]`.
+    pub fn build(self, fill_rule: FillRule) -> Buffer2d<f32> {
+        let Self { size, stride, accumulation } = self;
+
+        let mut coverage = Vec::with_capacity(size.x * size.y);
+
+        for row in accumulation.chunks_exact(stride) {
+            let mut sum = 0.0;
+
+            for &value in &row[..size.x] {
+                sum += value;
+                coverage.push(fill_rule.coverage(sum));
+            }
+        }
+
+        Buffer2d::from_iter(size, coverage.into_iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::buffer::buffer2d::Buffer2dRead;
+    use crate::utils::math::is_equal::IsCopyTypeEqual;
+
+    use super::*;
+
+    const EPSILON: f64 = 0.001;
+
+    fn rect(p1: Vec2<f32>, p2: Vec2<f32>) -> [Vec2<f32>; 4] {
+        [p1, Vec2::new(p2.x, p1.y), p2, Vec2::new(p1.x, p2.y)]
+    }
+
+    #[test]
+    fn coverage_accumulator_rect() {
+        let mut accumulator = CoverageAccumulator::new(Vec2::new(4, 4));
+        accumulator.add_outline(&rect(Vec2::new(1.0, 1.0), Vec2::new(3.0, 3.0)));
+
+        let coverage = accumulator.build(FillRule::NonZero);
+        for y in 0..4 {
+            for x in 0..4 {
+                let expected = if (1..3).contains(&x) && (1..3).contains(&y) {
+                    1.0
+                }
+                else {
+                    0.0
+                };
+
+                assert!(f64::from(coverage.value(Vec2::new(x, y))).is_equal(expected, EPSILON));
+            }
+        }
+    }
+
+    #[test]
+    fn coverage_accumulator_partial_pixels() {
+        let mut accumulator = CoverageAccumulator::new(Vec2::new(2, 2));
+        accumulator.add_outline(&rect(Vec2::new(0.5, 0.0), Vec2::new(2.0, 0.5)));
+
+        let coverage = accumulator.build(FillRule::NonZero);
+        assert!(f64::from(coverage.value(Vec2::new(0, 0))).is_equal(0.25, EPSILON));
+        assert!(f64::from(coverage.value(Vec2::new(1, 0))).is_equal(0.5, EPSILON));
+        assert!(f64::from(coverage.value(Vec2::new(0, 1))).is_equal(0.0, EPSILON));
+
+        // Triangle covering the lower-left half of the buffer
+        let mut accumulator = CoverageAccumulator::new(Vec2::new(2, 2));
+        accumulator.add_outline(&[Vec2::new(0.0, 0.0), Vec2::new(2.0, 2.0), Vec2::new(0.0, 2.0)]);
+
+        let coverage = accumulator.build(FillRule::NonZero);
+        assert!(f64::from(coverage.value(Vec2::new(0, 0))).is_equal(0.5, EPSILON));
+        assert!(f64::from(coverage.value(Vec2::new(1, 0))).is_equal(0.0, EPSILON));
+        assert!(f64::from(coverage.value(Vec2::new(0, 1))).is_equal(1.0, EPSILON));
+        assert!(f64::from(coverage.value(Vec2::new(1, 1))).is_equal(0.5, EPSILON));
+    }
+
+    #[test]
+    fn coverage_accumulator_clipping() {
+        let mut accumulator = CoverageAccumulator::new(Vec2::new(2, 2));
+        accumulator.add_outline(&rect(Vec2::new(-5.0, -5.0), Vec2::new(1.0, 10.0)));
+
+        let coverage = accumulator.build(FillRule::NonZero);
+        assert!(f64::from(coverage.value(Vec2::new(0, 0))).is_equal(1.0, EPSILON));
+        assert!(f64::from(coverage.value(Vec2::new(0, 1))).is_equal(1.0, EPSILON));
+        assert!(f64::from(coverage.value(Vec2::new(1, 1))).is_equal(0.0, EPSILON));
+    }
+
+    #[test]
+    fn coverage_accumulator_fill_rules() {
+        // Two nested squares with the same direction
+        let mut accumulator = CoverageAccumulator::new(Vec2::new(4, 1));
+        accumulator.add_outline(&rect(Vec2::new(0.0, 0.0), Vec2::new(4.0, 1.0)));
+        accumulator.add_outline(&rect(Vec2::new(1.0, 0.0), Vec2::new(3.0, 1.0)));
+
+        let non_zero = accumulator.build(FillRule::NonZero);
+        assert!(non_zero.iter().all(|&value| f64::from(value).is_equal(1.0, EPSILON)));
+
+        let mut accumulator = CoverageAccumulator::new(Vec2::new(4, 1));
+        accumulator.add_outline(&rect(Vec2::new(0.0, 0.0), Vec2::new(4.0, 1.0)));
+        accumulator.add_outline(&rect(Vec2::new(1.0, 0.0), Vec2::new(3.0, 1.0)));
+
+        let even_odd = accumulator.build(FillRule::EvenOdd);
+        let expected = [1.0, 0.0, 0.0, 1.0];
+        for (&value, expected) in even_odd.iter().zip(expected) {
+            assert!(f64::from(value).is_equal(expected, EPSILON));
+        }
+
+        // Opposite direction of the inner square makes a hole for both rules
+        let mut accumulator = CoverageAccumulator::new(Vec2::new(4, 1));
+        let mut inner = rect(Vec2::new(1.0, 0.0), Vec2::new(3.0, 1.0));
+        inner.reverse();
+        accumulator.add_outline(&rect(Vec2::new(0.0, 0.0), Vec2::new(4.0, 1.0)));
+        accumulator.add_outline(&inner);
+
+        let non_zero = accumulator.build(FillRule::NonZero);
+        for (&value, expected) in non_zero.iter().zip(expected) {
+            assert!(f64::from(value).is_equal(expected, EPSILON));
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use crate::utils::math::vector::vector2::Vec2;
+use crate::utils::math::vector::vector3::Vec3;
+use crate::utils::math::vector::vector4::Vec4;
+
+use super::coverage_accumulator::FillRule;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DistanceFieldKind {
+    /// Single-channel signed distance field, stored in all channels
+    #[default]
+    Sdf,
+
+    /// Multi-channel signed distance field in RGB, the single-channel field
+    /// is stored in alpha
+    Msdf,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DistanceFieldOptions {
+    pub kind: DistanceFieldKind,
+
+    /// Distance in pixels mapped to the `[0.0; 1.0]` value range, the outline
+    /// is at 0.5
+    pub range: f32,
+
+    /// Empty pixels around every glyph, should be at least `range / 2`
+    pub padding: usize,
+}
+
+impl Default for DistanceFieldOptions {
+    fn default() -> Self {
+        Self {
+            kind: DistanceFieldKind::default(),
+            range: 4.0,
+            padding: 2,
+        }
+    }
+}
+
+// Channel masks of edge colors
+const RED: u8 = 1;
+const GREEN: u8 = 2;
+const BLUE: u8 = 4;
+const WHITE: u8 = RED | GREEN | BLUE;
+const CYAN: u8 = GREEN | BLUE;
+const MAGENTA: u8 = RED | BLUE;
+const YELLOW: u8 = RED | GREEN;
+
+/// Sine of the minimal angle between segments which makes a corner
+const CORNER_THRESHOLD: f32 = 0.14;
+
+/// Run of outline segments between two corners
+struct Edge {
+    points: Vec<Vec2<f32>>,
+    color: u8,
+}
+
+/// Nearest point of an edge
+#[derive(Clone, Copy)]
+struct EdgeDistance {
+    distance: f32,
+    orthogonality: f32,
+
+    /// Signed perpendicular distance, extended past the edge ends
+    pseudo_distance: f32,
+}
+
+impl EdgeDistance {
+    const NONE: EdgeDistance = EdgeDistance {
+        distance: f32::INFINITY,
+        orthogonality: 0.0,
+        pseudo_distance: f32::NEG_INFINITY,
+    };
+
+    fn is_closer(&self, other: &EdgeDistance) -> bool {
+        const EPSILON: f32 = 1e-4;
+
+        if (self.distance - other.distance).abs() < EPSILON {
+            self.orthogonality > other.orthogonality
+        }
+        else {
+            self.distance < other.distance
+        }
+    }
+}
+
+/// Signed distance field of flattened closed outlines, distances are
+/// positive inside of the shape.
+// https://github.com/Chlumsky/msdfgen
+pub struct DistanceField {
+    outlines: Vec<Vec<Vec2<f32>>>,
+    edges: Vec<Edge>,
+    fill_rule: FillRule,
+
+    /// 1.0 if the inner side is to the left of outline segments, -1.0 otherwise
+    orientation: f32,
+}
+
+impl DistanceField {
+    pub fn new<T>(outlines: &[T], fill_rule: FillRule) -> Self
+        where T: AsRef<[Vec2<f32>]>,
+    {
+        let outlines: Vec<Vec<Vec2<f32>>> = outlines.iter()
+            .map(|outline| DistanceField::remove_duplicates(outline.as_ref()))
+            .filter(|outline| outline.len() >= 2)
+            .collect();
+
+        let area: f32 = outlines.iter().map(|outline| DistanceField::signed_area(outline)).sum();
+        let edges = outlines.iter()
+            .flat_map(|outline| DistanceField::color_edges(outline))
+            .collect();
+
+        Self {
+            outlines,
+            edges,
+            fill_rule,
+            orientation: if area < 0.0 { -1.0 } else { 1.0 },
+        }
+    }
+
+    pub fn signed_distance(&self, point: Vec2<f32>) -> f32 {
+        let mut distance = f32::INFINITY;
+        for outline in &self.outlines {
+            for (p0, p1) in DistanceField::segments(outline) {
+                distance = distance.min(segment_distance(point, p0, p1).0);
+            }
+        }
+
+        if self.is_inside(point) {
+            distance
+        }
+        else {
+            -distance
+        }
+    }
+
+    /// Signed pseudo-distances to the nearest edges of every channel, the
+    /// median of channels gives sharp corners.
+    pub fn multi_signed_distance(&self, point: Vec2<f32>) -> Vec3<f32> {
+        let mut nearest = [EdgeDistance::NONE; 3];
+
+        for edge in &self.edges {
+            let edge_distance = self.edge_distance(edge, point);
+
+            for (i, channel) in [RED, GREEN, BLUE].into_iter().enumerate() {
+                if edge.color & channel != 0 && edge_distance.is_closer(&nearest[i]) {
+                    nearest[i] = edge_distance;
+                }
+            }
+        }
+
+        Vec3::new(nearest[0].pseudo_distance, nearest[1].pseudo_distance, nearest[2].pseudo_distance)
+    }
+
+    /// Returns the encoded field value at the pixel center.
+    pub fn pixel(&self, position: Vec2<usize>, options: &DistanceFieldOptions) -> Vec4<f32> {
+        let point = Vec2::new(position.x as f32 + 0.5, position.y as f32 + 0.5);
+        let encode = |distance: f32| (0.5 + distance / options.range).clamp(0.0, 1.0);
+
+        let distance = encode(self.signed_distance(point));
+        match options.kind {
+            DistanceFieldKind::Sdf => Vec4::from(distance),
+            DistanceFieldKind::Msdf => {
+                let multi_distance = self.multi_signed_distance(point);
+                Vec4::new(
+                    encode(multi_distance.x),
+                    encode(multi_distance.y),
+                    encode(multi_distance.z),
+                    distance,
+                )
+            }
+        }
+    }
+
+    fn is_inside(&self, point: Vec2<f32>) -> bool {
+        let mut winding = 0;
+        for outline in &self.outlines {
+            for (p0, p1) in DistanceField::segments(outline) {
+                if (p0.y <= point.y) == (p1.y <= point.y) {
+                    continue;
+                }
+
+                let x = p0.x + (point.y - p0.y) / (p1.y - p0.y) * (p1.x - p0.x);
+                if x > point.x {
+                    winding += if p1.y > p0.y { 1 } else { -1 };
+                }
+            }
+        }
+
+        self.fill_rule.coverage(winding as f32) > 0.5
+    }
+
+    fn edge_distance(&self, edge: &Edge, point: Vec2<f32>) -> EdgeDistance {
+        let last = edge.points.len() - 2;
+
+        let mut nearest = EdgeDistance::NONE;
+        let mut nearest_segment = (0, 0.0);
+
+        for i in 0..=last {
+            let (p0, p1) = (edge.points[i], edge.points[i + 1]);
+            let (distance, t) = segment_distance(point, p0, p1);
+
+            let direction = p1 - p0;
+            let cross = cross(direction, point - p0);
+            let orthogonality = if distance > 0.0 {
+                cross.abs() / (direction.lenght() * distance)
+            }
+            else {
+                1.0
+            };
+
+            let segment = EdgeDistance {
+                distance,
+                orthogonality,
+                pseudo_distance: distance * cross.signum() * self.orientation,
+            };
+
+            if segment.is_closer(&nearest) {
+                nearest = segment;
+                nearest_segment = (i, t);
+            }
+        }
+
+        // Beyond the edge ends the distance to the extended end segment is
+        // used, so channels of neighbour edges meet at sharp corners
+        let (i, t) = nearest_segment;
+        if (i == 0 && t <= 0.0) || (i == last && t >= 1.0) {
+            let (p0, p1) = (edge.points[i], edge.points[i + 1]);
+            let direction = (p1 - p0).normalize();
+            let origin = if t <= 0.0 { p0 } else { p1 };
+            let along = (point - origin).dot(direction);
+
+            if (t <= 0.0 && along < 0.0) || (t >= 1.0 && along > 0.0) {
+                let pseudo_distance = cross(direction, point - origin) * self.orientation;
+                if pseudo_distance.abs() <= nearest.distance {
+                    nearest.pseudo_distance = pseudo_distance;
+                }
+            }
+        }
+
+        nearest
+    }
+
+    fn segments(outline: &[Vec2<f32>]) -> impl Iterator<Item = (Vec2<f32>, Vec2<f32>)> + '_ {
+        let last = *outline.last().unwrap();
+        outline.iter()
+            .scan(last, |prev, &point| {
+                let segment = (*prev, point);
+                *prev = point;
+                Some(segment)
+            })
+    }
+
+    fn remove_duplicates(outline: &[Vec2<f32>]) -> Vec<Vec2<f32>> {
+        let mut points: Vec<Vec2<f32>> = Vec::with_capacity(outline.len());
+        for &point in outline {
+            if points.last() != Some(&point) {
+                points.push(point);
+            }
+        }
+
+        while points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+
+        points
+    }
+
+    fn signed_area(outline: &[Vec2<f32>]) -> f32 {
+        DistanceField::segments(outline)
+            .map(|(p0, p1)| cross(p0, p1))
+            .sum::<f32>() * 0.5
+    }
+
+    /// Splits the outline into edges at corners and colors them, so edges
+    /// meeting at a corner share only one channel.
+    fn color_edges(outline: &[Vec2<f32>]) -> Vec<Edge> {
+        let count = outline.len();
+        let is_corner = |i: usize| {
+            let prev = (outline[i] - outline[(i + count - 1) % count]).normalize();
+            let next = (outline[(i + 1) % count] - outline[i]).normalize();
+            prev.dot(next) <= 0.0 || cross(prev, next).abs() > CORNER_THRESHOLD
+        };
+
+        let corners: Vec<usize> = (0..count).filter(|&i| is_corner(i)).collect();
+
+        // Closed polyline starting at `begin` with `len` segments
+        let polyline = |begin: usize, len: usize| -> Vec<Vec2<f32>> {
+            (0..=len).map(|j| outline[(begin + j) % count]).collect()
+        };
+
+        match corners.len() {
+            0 => vec![Edge { points: polyline(0, count), color: WHITE }],
+            1 => {
+                // Teardrop, the edge is split into three parts
+                let begin = corners[0];
+                let splits = [0, count / 3, count * 2 / 3, count];
+                let colors = [MAGENTA, WHITE, YELLOW];
+
+                (0..3)
+                    .filter(|&i| splits[i + 1] > splits[i])
+                    .map(|i| Edge {
+                        points: polyline(begin + splits[i], splits[i + 1] - splits[i]),
+                        color: colors[i],
+                    })
+                    .collect()
+            }
+            _ => {
+                let mut edges = Vec::with_capacity(corners.len());
+                let mut color = CYAN;
+
+                for (i, &begin) in corners.iter().enumerate() {
+                    let end = corners[(i + 1) % corners.len()];
+                    let len = (end + count - begin - 1) % count + 1;
+
+                    // The last edge meets the first cyan one and the previous
+                    // yellow one
+                    if i + 1 == corners.len() && i > 0 && color == CYAN {
+                        color = MAGENTA;
+                    }
+
+                    edges.push(Edge { points: polyline(begin, len), color });
+                    color = switch_color(color);
+                }
+
+                edges
+            }
+        }
+    }
+}
+
+/// CYAN -> MAGENTA -> YELLOW -> CYAN
+fn switch_color(color: u8) -> u8 {
+    let shifted = color << 1;
+    (shifted | shifted >> 3) & WHITE
+}
+
+fn cross(a: Vec2<f32>, b: Vec2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+/// Returns the distance to the segment and the parameter of the nearest point
+/// on the segment line (not clamped)
+fn segment_distance(point: Vec2<f32>, p0: Vec2<f32>, p1: Vec2<f32>) -> (f32, f32) {
+    let direction = p1 - p0;
+    let length_squared = direction.dot(direction);
+    if length_squared == 0.0 {
+        return ((point - p0).lenght(), 0.0);
+    }
+
+    let t = (point - p0).dot(direction) / length_squared;
+    let nearest = p0 + direction * t.clamp(0.0, 1.0);
+
+    ((point - nearest).lenght(), t)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::math::is_equal::IsCopyTypeEqual;
+
+    use super::*;
+
+    const EPSILON: f64 = 0.001;
+
+    fn square() -> Vec<Vec2<f32>> {
+        vec![Vec2::new(0.0, 0.0), Vec2::new(4.0, 0.0), Vec2::new(4.0, 4.0), Vec2::new(0.0, 4.0)]
+    }
+
+    fn median(value: Vec3<f32>) -> f32 {
+        value.x.min(value.y).max(value.x.max(value.y).min(value.z))
+    }
+
+    #[test]
+    fn distance_field_signed_distance() {
+        let field = DistanceField::new(&[square()], FillRule::NonZero);
+
+        assert!(f64::from(field.signed_distance(Vec2::new(2.0, 2.0))).is_equal(2.0, EPSILON));
+        assert!(f64::from(field.signed_distance(Vec2::new(1.0, 2.0))).is_equal(1.0, EPSILON));
+        assert!(f64::from(field.signed_distance(Vec2::new(-1.0, 2.0))).is_equal(-1.0, EPSILON));
+        assert!(f64::from(field.signed_distance(Vec2::new(7.0, 8.0))).is_equal(-5.0, EPSILON));
+
+        // Orientation of the outline doesn't matter
+        let mut reversed = square();
+        reversed.reverse();
+        let field = DistanceField::new(&[reversed], FillRule::NonZero);
+        assert!(f64::from(field.signed_distance(Vec2::new(1.0, 2.0))).is_equal(1.0, EPSILON));
+    }
+
+    #[test]
+    fn distance_field_multi_signed_distance() {
+        let field = DistanceField::new(&[square()], FillRule::NonZero);
+
+        for point in [Vec2::new(1.0, 2.0), Vec2::new(2.0, 3.5), Vec2::new(-1.0, 2.0), Vec2::new(2.0, 6.0)] {
+            let expected = field.signed_distance(point);
+            let distance = median(field.multi_signed_distance(point));
+            assert!(f64::from(distance).is_equal(expected.into(), EPSILON));
+        }
+
+        // The corner stays sharp: outside of the corner along the diagonal
+        // the median is the perpendicular distance, not the euclidean one
+        let distance = median(field.multi_signed_distance(Vec2::new(5.0, 5.0)));
+        assert!(f64::from(distance).is_equal(-1.0, EPSILON));
+    }
+
+    #[test]
+    fn distance_field_edge_colors() {
+        let edges = DistanceField::color_edges(&square());
+        assert_eq!(edges.len(), 4);
+
+        for i in 0..edges.len() {
+            let next = &edges[(i + 1) % edges.len()];
+            assert_ne!(edges[i].color, next.color);
+            assert_eq!((edges[i].color & next.color).count_ones(), 1);
+        }
+    }
+}
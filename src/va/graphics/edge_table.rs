@@ -0,0 +1,158 @@
+use std::cmp::Ordering;
+
+use crate::utils::math::vector::vector2::Vec2;
+
+use super::coverage_accumulator::FillRule;
+
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    y_min: f32,
+    y_max: f32,
+
+    /// X at `y_min`
+    x: f32,
+    dx_dy: f32,
+
+    /// 1 for downward edges, -1 for upward ones
+    winding: i32,
+}
+
+impl Edge {
+    fn x_at(&self, y: f32) -> f32 {
+        self.x + (y - self.y_min) * self.dx_dy
+    }
+}
+
+/// Edges of polygons sorted by their top, scanned with an active edge table.
+///
+/// Every edge covers the half-open range `[y_min, y_max)`, so scanlines
+/// through shared vertices count them once.
+// https://www.cs.rit.edu/~icss571/filling/how_to.html
+pub struct EdgeTable {
+    edges: Vec<Edge>,
+}
+
+impl EdgeTable {
+    /// Polygons are closed implicitly, horizontal edges are skipped
+    pub fn new<T>(polygons: &[T]) -> Self
+        where T: AsRef<[Vec2<f32>]>,
+    {
+        let mut edges = Vec::new();
+
+        for polygon in polygons {
+            let points = polygon.as_ref();
+            if points.len() < 3 {
+                continue;
+            }
+
+            for (i, &p0) in points.iter().enumerate() {
+                let p1 = points[(i + 1) % points.len()];
+                if p0.y == p1.y || !(p0.x.is_finite() && p0.y.is_finite() && p1.x.is_finite() && p1.y.is_finite()) {
+                    continue;
+                }
+
+                let (top, bottom, winding) = if p0.y < p1.y { (p0, p1, 1) } else { (p1, p0, -1) };
+                edges.push(Edge {
+                    y_min: top.y,
+                    y_max: bottom.y,
+                    x: top.x,
+                    dx_dy: (bottom.x - top.x) / (bottom.y - top.y),
+                    winding,
+                });
+            }
+        }
+
+        edges.sort_by(|a, b| a.y_min.partial_cmp(&b.y_min).unwrap_or(Ordering::Equal));
+
+        Self {
+            edges,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    /// Top and bottom of all edges
+    pub fn y_range(&self) -> Option<(f32, f32)> {
+        let y_min = self.edges.first()?.y_min;
+        let y_max = self.edges.iter().map(|edge| edge.y_max).fold(f32::MIN, f32::max);
+
+        Some((y_min, y_max))
+    }
+
+    /// Calls `f(y, x0, x1)` for every span inside of the polygons on the
+    /// scanlines `ys`, which must be increasing.
+    pub fn scan<I, F>(&self, ys: I, fill_rule: FillRule, mut f: F)
+        where I: IntoIterator<Item = f32>,
+              F: FnMut(f32, f32, f32),
+    {
+        let mut next = 0;
+        let mut active: Vec<usize> = Vec::new();
+        let mut crossings: Vec<(f32, i32)> = Vec::new();
+
+        for y in ys {
+            while next < self.edges.len() && self.edges[next].y_min <= y {
+                active.push(next);
+                next += 1;
+            }
+
+            active.retain(|&i| self.edges[i].y_max > y);
+            if active.is_empty() {
+                if next == self.edges.len() {
+                    break;
+                }
+
+                continue;
+            }
+
+            crossings.clear();
+            crossings.extend(active.iter().map(|&i| (self.edges[i].x_at(y), self.edges[i].winding)));
+            crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+            // Adjacent inside intervals are merged into one span
+            let mut winding = 0;
+            let mut start = 0.0;
+            for &(x, edge_winding) in &crossings {
+                let was_inside = fill_rule.is_inside(winding);
+                winding += edge_winding;
+
+                match (was_inside, fill_rule.is_inside(winding)) {
+                    (false, true) => start = x,
+                    (true, false) if start < x => f(y, start, x),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(table: &EdgeTable, y: f32, fill_rule: FillRule) -> Vec<(f32, f32)> {
+        let mut spans = Vec::new();
+        table.scan([y], fill_rule, |_, x0, x1| spans.push((x0, x1)));
+        spans
+    }
+
+    #[test]
+    fn edge_table_scan() {
+        // Square with a square hole of the same direction
+        let outer = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(10.0, 10.0), Vec2::new(0.0, 10.0)];
+        let inner = [Vec2::new(2.0, 2.0), Vec2::new(6.0, 2.0), Vec2::new(6.0, 6.0), Vec2::new(2.0, 6.0)];
+        let table = EdgeTable::new(&[&outer[..], &inner[..]]);
+
+        assert_eq!(table.y_range(), Some((0.0, 10.0)));
+        assert_eq!(spans(&table, 1.0, FillRule::EvenOdd), vec![(0.0, 10.0)]);
+        assert_eq!(spans(&table, 4.0, FillRule::EvenOdd), vec![(0.0, 2.0), (6.0, 10.0)]);
+        assert_eq!(spans(&table, 4.0, FillRule::NonZero), vec![(0.0, 10.0)]);
+        assert!(spans(&table, 10.0, FillRule::NonZero).is_empty());
+
+        // Triangle, the shared vertex is counted once
+        let table = EdgeTable::new(&[[Vec2::new(0.0, 0.0), Vec2::new(4.0, 4.0), Vec2::new(0.0, 8.0)]]);
+        assert_eq!(spans(&table, 4.0, FillRule::NonZero), vec![(0.0, 4.0)]);
+        assert_eq!(spans(&table, 2.0, FillRule::NonZero), vec![(0.0, 2.0)]);
+    }
+}
@@ -1,28 +1,37 @@
-use std::cmp::max;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::sync::Arc;
 
 use anyhow::{bail, Context};
+use log::warn;
 use ttf_parser::{Face, GlyphId};
 use vulkano::command_buffer::{CommandBufferExecFuture, PrimaryAutoCommandBuffer};
 use vulkano::device::Queue;
-use vulkano::format::Format;
 use vulkano::image::{ImmutableImage, ImageDimensions, MipmapsCount};
 use vulkano::sync::NowFuture;
 
-use crate::graphics::buffer::buffer2d::Buffer2dRead;
+use crate::graphics::buffer::buffer2d::{Buffer2dRead, Buffer2dWrite};
 use crate::utils::cast::Cast;
 use crate::utils::math::geometry::rect::Rect;
 use crate::utils::math::vector::vector2::Vec2;
-use crate::utils::math::vector::vector3::Vec3;
 use crate::utils::math::vector::vector4::Vec4;
 use crate::utils::math::matrix::matrix3x3::Mat3x3;
+use crate::utils::rect_packer::RectPacker;
 
 use super::buffer::buffer2d::Buffer2d;
-use super::glyph_render::GlyphRenderBuilder;
-use super::rasterizate::SimpleRasterizate;
+use super::char_set::CharSet;
+use super::color_space::ColorSpace;
+use super::distance_field::{DistanceField, DistanceFieldOptions};
+use super::font_registry::FontFace;
+use super::glyph_render::{GlyphRender, GlyphRenderBuilder};
+use super::kerning;
 
-const LATIN_ALPHABET_LENGTH: usize = 26;
+/// Empty pixels around every glyph in the atlas, prevents bleeding of
+/// neighbours while sampling
+pub(crate) const ATLAS_PADDING: usize = 1;
+
+/// Placed characters and the image of the atlas
+type Atlas = (HashMap<char, CharInfo>, Buffer2d<Vec4<f32>>);
 
 // TODO pub
 pub struct Font {
@@ -30,152 +39,557 @@ pub struct Font {
     pub px_size: u32,
     pub chars_info: HashMap<char, CharInfo>,
     pub buffer2d: Buffer2d<Vec4<f32>>,
-    pub image: Arc<ImmutableImage>,
+
+    /// The primary face and the fallback chain
+    faces: Vec<Arc<FontFace>>,
+
+    /// Font units to pixels scale of every face
+    scales: Vec<f64>,
+    ascender: f32,
+    descender: f32,
+    line_gap: f32,
 }
 
-#[derive(Clone, Copy)]
+/// Glyph placement in the atlas and its metrics in pixels.
+///
+/// Metrics are relative to the pen position on the baseline, Y axis is
+/// directed down.
+#[derive(Clone, Copy, Debug)]
 pub struct CharInfo {
-    bounding_box: Rect<i32>,
+    face: usize,
+    glyph_id: GlyphId,
+    atlas_rect: Rect<usize>,
+    uv_rect: Rect<f32>,
+    bearing: Vec2<f32>,
+    advance_width: f32,
+    bounding_box: Rect<f32>,
+}
+
+impl CharInfo {
+    /// Index of the face in the font fallback chain, 0 is the primary face
+    pub fn face(&self) -> usize {
+        self.face
+    }
+
+    pub fn glyph_id(&self) -> GlyphId {
+        self.glyph_id
+    }
+
+    /// Glyph image in the atlas (`p2` is exclusive), empty for glyphs without
+    /// an outline
+    pub fn atlas_rect(&self) -> Rect<usize> {
+        self.atlas_rect
+    }
+
+    /// Normalized atlas texture coordinates of the glyph image
+    pub fn uv_rect(&self) -> Rect<f32> {
+        self.uv_rect
+    }
+
+    /// Offset from the pen position to the top-left corner of the glyph image
+    pub fn bearing(&self) -> Vec2<f32> {
+        self.bearing
+    }
+
+    pub fn advance_width(&self) -> f32 {
+        self.advance_width
+    }
+
+    /// Exact glyph outline bounds
+    pub fn bounding_box(&self) -> Rect<f32> {
+        self.bounding_box
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.atlas_rect.p1 == self.atlas_rect.p2
+    }
 }
 
 impl CharInfo {
-    fn new(bounding_box: Rect<i32>) -> Self {
+    /// `atlas_size` is the size of the atlas containing `placement.atlas_rect`
+    pub(crate) fn new(face: &Face, placement: &GlyphPlacement, k: f64, atlas_size: Vec2<usize>) -> Self {
+        let glyph_id = placement.glyph_id;
+
+        let advance_width: f64 = face.glyph_hor_advance(glyph_id).unwrap_or(0).into();
+        let bounding_box = match face.glyph_bounding_box(glyph_id) {
+            Some(val) => Rect::new(
+                Vec2::new(val.x_min as f64 * k, -val.y_max as f64 * k).cast(),
+                Vec2::new(val.x_max as f64 * k, -val.y_min as f64 * k).cast(),
+            ),
+            None => Rect::default(),
+        };
+
+        let atlas_size: Vec2<f32> = atlas_size.cast();
+        let atlas_rect: Rect<f32> = placement.atlas_rect.cast();
+
         Self {
+            face: placement.face,
+            glyph_id,
+            atlas_rect: placement.atlas_rect,
+            uv_rect: Rect::new(
+                Vec2::new(atlas_rect.p1.x / atlas_size.x, atlas_rect.p1.y / atlas_size.y),
+                Vec2::new(atlas_rect.p2.x / atlas_size.x, atlas_rect.p2.y / atlas_size.y),
+            ),
+            bearing: placement.bearing,
+            advance_width: (advance_width * k) as f32,
             bounding_box,
         }
     }
 }
 
+/// Pixel aligned glyph image, `transform` maps font units into the image
+pub(crate) struct GlyphPlacement {
+    pub face: usize,
+    pub glyph_id: GlyphId,
+    pub size: Vec2<usize>,
+    pub bearing: Vec2<f32>,
+    pub transform: Mat3x3<f64>,
+    pub atlas_rect: Rect<usize>,
+}
+
+impl GlyphPlacement {
+    pub fn new(face: &Face, glyph_id: GlyphId, k: f64) -> Self {
+        let bounding_box = match face.glyph_bounding_box(glyph_id) {
+            Some(val) => val,
+            None => {
+                return Self {
+                    face: 0,
+                    glyph_id,
+                    size: Vec2::ZERO,
+                    bearing: Vec2::ZERO,
+                    transform: Mat3x3::default(),
+                    atlas_rect: Rect::default(),
+                };
+            }
+        };
+
+        // Pixel aligned glyph image bounds, Y axis is directed down
+        let p1 = Vec2::new(bounding_box.x_min as f64 * k, -bounding_box.y_max as f64 * k).floor();
+        let p2 = Vec2::new(bounding_box.x_max as f64 * k, -bounding_box.y_min as f64 * k).ceil();
+
+        let translate = Mat3x3::with_translate(-p1);
+        let scale = Mat3x3::with_scale(Vec2::new(k, -k));
+
+        Self {
+            face: 0,
+            glyph_id,
+            size: (p2 - p1).cast(),
+            bearing: p1.cast(),
+            transform: translate * scale,
+            atlas_rect: Rect::default(),
+        }
+    }
+
+    /// Extends the image by `margin` pixels on every side
+    pub fn with_margin(mut self, margin: usize) -> Self {
+        if self.is_empty() || margin == 0 {
+            return self;
+        }
+
+        let offset: Vec2<f64> = Vec2::from(margin).cast();
+        self.size += 2 * margin;
+        self.bearing -= margin as f32;
+        self.transform = Mat3x3::with_translate(offset) * self.transform;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size.x == 0 || self.size.y == 0
+    }
+
+    fn glyph_render(&self, face: &Face) -> Option<GlyphRender> {
+        let offset: Vec2<f64> = self.atlas_rect.p1.cast();
+        let transform = Mat3x3::with_translate(offset) * self.transform;
+
+        let mut builder = GlyphRenderBuilder::new(transform);
+        face.outline_glyph(self.glyph_id, &mut builder)?;
+
+        Some(builder.build())
+    }
+
+    /// Rasterizates the glyph into `atlas_rect` of the buffer
+    pub fn rasterizate(&self, face: &Face, buffer: &mut Buffer2d<Vec4<f32>>) {
+        if self.is_empty() {
+            return;
+        }
+
+        if let Some(glyph_render) = self.glyph_render(face) {
+            glyph_render.rasterizate(buffer);
+        }
+    }
+
+    /// Writes the distance field of the glyph into `atlas_rect` of the buffer
+    pub fn distance_field(&self, face: &Face, buffer: &mut Buffer2d<Vec4<f32>>, options: &DistanceFieldOptions) {
+        if self.is_empty() {
+            return;
+        }
+
+        let field = match self.glyph_render(face) {
+            Some(val) => DistanceField::new(val.outlines(), val.fill_rule()),
+            None => return,
+        };
+
+        for y in self.atlas_rect.p1.y..self.atlas_rect.p2.y {
+            for x in self.atlas_rect.p1.x..self.atlas_rect.p2.x {
+                let position = Vec2::new(x, y);
+                let value = field.pixel(position, options);
+
+                // Safety: atlas_rect is inside of the atlas
+                unsafe { buffer.set_value(position, value) };
+            }
+        }
+    }
+}
+
 impl Font {
-    pub fn new(name: String, raw_data: &[u8], px_size: u32, queue: Arc<Queue>) 
-        -> anyhow::Result<(Self, CommandBufferExecFuture<NowFuture, PrimaryAutoCommandBuffer>)> 
+    /// `faces` are the primary face and the fallback chain for characters
+    /// missing in it. `px_size` is the height of capital letters of the
+    /// primary face in pixels.
+    pub fn new(name: String, faces: Vec<Arc<FontFace>>, px_size: u32, char_set: &CharSet) -> anyhow::Result<Self> {
+        let primary = match faces.first() {
+            Some(val) => val.face(),
+            None => bail!("no font faces"),
+        };
+
+        // Fallback faces have the same em size
+        let k = Font::scale(primary, px_size)?;
+        let units_per_em = f64::from(primary.units_per_em());
+        let scales: Vec<f64> = faces.iter()
+            .map(|face| k * units_per_em / f64::from(face.face().units_per_em()))
+            .collect();
+
+        let faces_and_scales: Vec<(&Face, f64)> = faces.iter()
+            .map(|face| face.face())
+            .zip(scales.iter().copied())
+            .collect();
+
+        // White glyphs with coverage in alpha on a transparent background
+        let (chars_info, buffer2d) = Font::create_atlas(&faces_and_scales, char_set, 0, Vec4::from(0.0), |placement, buffer2d| {
+            placement.rasterizate(faces_and_scales[placement.face].0, buffer2d);
+        })?;
+
+        let ascender = (f64::from(primary.ascender()) * k) as f32;
+        let descender = (-f64::from(primary.descender()) * k) as f32;
+        let line_gap = (f64::from(primary.line_gap()) * k) as f32;
+
+        Ok(Self {
+            name,
+            px_size,
+            chars_info,
+            buffer2d,
+
+            faces,
+            scales,
+            ascender,
+            descender,
+            line_gap,
+        })
+    }
+
+    /// Uploads the atlas into a GPU image
+    pub fn create_image(&self, queue: Arc<Queue>) 
+        -> anyhow::Result<(Arc<ImmutableImage>, CommandBufferExecFuture<NowFuture, PrimaryAutoCommandBuffer>)> 
     {
+        let size: Vec2<u32> = self.buffer2d.size().cast();
+        let (width, height) = size.into();
+        let (format, data) = self.buffer2d.to_image_data();
+
+        match ImmutableImage::from_iter(
+            data,
+            ImageDimensions::Dim2d {width, height, array_layers: 1},
+            MipmapsCount::One,
+            format,
+            queue,
+        ) {
+            Ok(val) => Ok(val),
+            Err(err) => bail!(format!("{} ({:?})", err, format)),
+        }
+    }
+
+    /// The primary face
+    pub fn face(&self) -> &Face<'_> {
+        self.faces[0].face()
+    }
+
+    /// The primary face and the fallback chain, indexed by
+    /// [`CharInfo::face`]
+    pub fn faces(&self) -> &[Arc<FontFace>] {
+        &self.faces
+    }
+
+    /// Distance from the baseline to the top of the line
+    pub fn ascender(&self) -> f32 {
+        self.ascender
+    }
+
+    /// Distance from the baseline to the bottom of the line
+    pub fn descender(&self) -> f32 {
+        self.descender
+    }
+
+    pub fn line_gap(&self) -> f32 {
+        self.line_gap
+    }
+
+    /// Distance between baselines of neighbouring lines
+    pub fn line_height(&self) -> f32 {
+        self.ascender + self.descender + self.line_gap
+    }
+
+    /// Returns outlines of the glyph scaled to pixels, `position` is the pen
+    /// position on the baseline.
+    pub fn glyph_render(&self, char_info: &CharInfo, position: Vec2<f32>) -> Option<GlyphRender> {
+        let k = self.scales[char_info.face];
+        let position: Vec2<f64> = position.cast();
+        let transform = Mat3x3::with_translate(position) * Mat3x3::with_scale(Vec2::new(k, -k));
+
+        let mut builder = GlyphRenderBuilder::new(transform);
+        self.faces[char_info.face].face().outline_glyph(char_info.glyph_id, &mut builder)?;
+
+        Some(builder.build())
+    }
+
+    /// Returns the pen position adjustment between two glyphs in pixels.
+    /// Glyphs of different faces aren't kerned.
+    pub fn kerning(&self, left: &CharInfo, right: &CharInfo) -> f32 {
+        if left.face != right.face {
+            return 0.0;
+        }
+
+        let face = self.faces[left.face].face();
+        match kerning::glyph_kerning(face, left.glyph_id, right.glyph_id) {
+            Some(val) => (f64::from(val) * self.scales[left.face]) as f32,
+            None => 0.0,
+        }
+    }
+
+    /// Returns the font units to pixels scale, `px_size` is the height of
+    /// capital letters in pixels
+    pub(crate) fn scale(face: &Face, px_size: u32) -> anyhow::Result<f64> {
         if px_size == 0 {
             bail!("invalid font px size ({px_size} px)");
         }
 
-        let face = Face::from_slice(&raw_data, 0).context("invalid font")?;
-
-        let capital_height = face.capital_height().context("no capital height")?;
+        let capital_height = Font::capital_height(face).context("no capital height")?;
         if capital_height < 1 {
             bail!("invalid capital height ({capital_height})");
         }
 
         let px_size_f64: f64 = px_size.into();
         let capital_height: f64 = capital_height.into();
-        let k = px_size_f64 / capital_height;
-
-        let mut chars_info: Vec<(GlyphId, Mat3x3<f64>, CharInfo)> = Vec::with_capacity(LATIN_ALPHABET_LENGTH * 2);
-
-        let mut offset = 0;
-        let mut max_height = 0;
-        
-        for char in ('A'..='Z').chain('a'..='z') {
-        //for char in 'A'..='A' {
-            let glyph_id = face.glyph_index(char).context("invalid glyph index")?;
-            let bounding_box = face.glyph_bounding_box(glyph_id).context("invalid glyph bounding box")?;
-    
-            let bounding_box: Rect<f64> = Rect::new(
-                Vec2::new(bounding_box.x_min, bounding_box.y_min).into(),
-                Vec2::new(bounding_box.x_max, bounding_box.y_max).into(),
-            );
-
-            let translate = Mat3x3::with_translate(Vec2::new(-bounding_box.p1.x, -bounding_box.p1.y));
-            let scale = Mat3x3::with_scale(Vec2::new(k, -k));
-    
-            let transform = scale * translate;
-            
-            let height_rect = Rect::new(
-                (transform * Vec3::new(bounding_box.p1.x, bounding_box.p1.y, 1.0)).xy(),
-                (transform * Vec3::new(bounding_box.p2.x, bounding_box.p2.y, 1.0)).xy(),
-            );
-
-            let box_height = height_rect.p1.y - height_rect.p2.y;
-
-            dbg!(box_height);
-            dbg!(transform);
-
-            let translate = Mat3x3::with_translate(Vec2::new(offset.into(), box_height));
-            let transform = translate * transform;
-    
-            dbg!(transform);
-            dbg!(bounding_box);
-
-            //let transform = Mat3x3::default();
-
-            let mut bounding_box: Rect<i32> = Rect::new(
-                (transform * Vec3::new(bounding_box.p1.x, bounding_box.p1.y, 1.0)).xy(), 
-                (transform * Vec3::new(bounding_box.p2.x, bounding_box.p2.y, 1.0)).xy(),
-            ).round().cast();
-
-            // Rotate bounding box
-            bounding_box.p2.y = bounding_box.p1.y;
-            bounding_box.p1.y = 0;
-
-            dbg!(bounding_box);
-            offset = bounding_box.p2.x + 1;
-
-            max_height = max(max_height, bounding_box.p2.y + 1);
-            chars_info.push((glyph_id, transform, CharInfo::new(bounding_box)));
-        }
-
-        let mut buffer2d = Buffer2d::new(
-            Vec2::new(offset, max_height).cast(),
+        Ok(px_size_f64 / capital_height)
+    }
+
+    /// Old fonts don't have capital height in the OS/2 table, so the height of
+    /// 'H' is used instead
+    fn capital_height(face: &Face) -> Option<i16> {
+        face.capital_height().or_else(|| {
+            let glyph_id = face.glyph_index('H')?;
+            Some(face.glyph_bounding_box(glyph_id)?.y_max)
+        })
+    }
+
+    /// Creates a distance field atlas of the character set with the glyph
+    /// size of this font.
+    ///
+    /// Glyph images include `options.padding`, so the quads of
+    /// [`CharInfo::atlas_rect`] contain the whole distance range.
+    pub fn create_distance_field_atlas(&self, char_set: &CharSet, options: &DistanceFieldOptions) 
+        -> anyhow::Result<DistanceFieldAtlas>
+    {
+        if options.range.is_nan() || options.range <= 0.0 {
+            bail!("invalid distance field range ({})", options.range);
+        }
+
+        let faces_and_scales: Vec<(&Face, f64)> = self.faces.iter()
+            .map(|face| face.face())
+            .zip(self.scales.iter().copied())
+            .collect();
+
+        let (chars_info, buffer2d) = Font::create_atlas(
+            &faces_and_scales,
+            char_set,
+            options.padding,
             Vec4::from(0.0),
-        );
-
-        dbg!(buffer2d.size());
-
-        // TODO
-        buffer2d.fill(Vec4::new(0.0, 0.0, 0.0, 1.0));
-      
-        for (glyph_id, transform, char_info) in chars_info {
-            let mut builder = GlyphRenderBuilder::new(transform);
-            loop {
-                let _ = face.outline_glyph(
-                    glyph_id, 
-                    &mut builder
-                ).context("no raster glyph image")?;
-    
-                if builder.was_closed() {
-                    break;
+            |placement, buffer2d| {
+                placement.distance_field(faces_and_scales[placement.face].0, buffer2d, options);
+            },
+        )?;
+
+        // Distances are sampled without sRGB decoding
+        Ok(DistanceFieldAtlas {
+            px_size: self.px_size,
+            options: *options,
+            chars_info,
+            buffer2d: buffer2d.with_color_space(ColorSpace::Linear),
+        })
+    }
+
+    /// Characters are searched in `faces` in order. `margin` extends every
+    /// glyph image, `rasterizate` draws a glyph into its `atlas_rect`.
+    fn create_atlas<F>(faces: &[(&Face, f64)], char_set: &CharSet, margin: usize, background: Vec4<f32>, mut rasterizate: F) 
+        -> anyhow::Result<Atlas>
+        where F: FnMut(&GlyphPlacement, &mut Buffer2d<Vec4<f32>>),
+    {
+        let mut chars = Vec::new();
+        let mut placements: Vec<GlyphPlacement> = Vec::new();
+        let mut placement_indices: HashMap<(usize, GlyphId), usize> = HashMap::new();
+
+        for char in char_set.chars() {
+            let glyph = faces.iter()
+                .enumerate()
+                .find_map(|(i, (face, _))| Some((i, face.glyph_index(char)?)));
+
+            let glyph = match glyph {
+                Some(val) => val,
+                None => {
+                    warn!("no glyph for {char:?} character");
+                    continue;
                 }
             };
 
-            let render = builder.build();
-            render.rasterizate(&mut buffer2d);
+            if let Entry::Vacant(entry) = placement_indices.entry(glyph) {
+                let (face_index, glyph_id) = glyph;
+                let (face, k) = faces[face_index];
+
+                let mut placement = GlyphPlacement::new(face, glyph_id, k).with_margin(margin);
+                placement.face = face_index;
+
+                entry.insert(placements.len());
+                placements.push(placement);
+            }
 
-            buffer2d.draw_rect_border(
-                char_info.bounding_box.p1,
-                char_info.bounding_box.p2,
-                Vec4::new(0.0, 1.0, 0.0, 1.0),
-            );
+            chars.push((char, glyph));
         }
 
-        let size: Vec2<u32> = buffer2d.size().cast();
-        let (width, height) = size.into();
-        let data = buffer2d.to_r8g8b8a8();
+        if chars.is_empty() {
+            bail!("no glyphs for the character set");
+        }
 
-        const FORMAT: Format = Format::R8G8B8A8_SRGB;
-        let (image, future) = match ImmutableImage::from_iter(
-            data,
-            ImageDimensions::Dim2d {width, height, array_layers: 1},
-            MipmapsCount::One,
-            FORMAT, // TODO choose srgb or default
-            queue,
-        ) {
-            Ok(val) => val,
-            Err(err) => bail!(format!("{} ({:?})", err, FORMAT)),
+        // Higher glyphs first give denser packing
+        let mut order: Vec<usize> = (0..placements.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(placements[i].size.y));
+
+        let padded_size = |size: Vec2<usize>| {
+            if size.x == 0 || size.y == 0 {
+                Vec2::ZERO
+            }
+            else {
+                size + 2 * ATLAS_PADDING
+            }
         };
 
-        let font = Self {
-            name,
-            px_size,
-            chars_info: HashMap::new(),
-            buffer2d,
-            image,
+        let area: usize = placements.iter()
+            .map(|placement| padded_size(placement.size))
+            .map(|size| size.x * size.y)
+            .sum();
+
+        let max_width = placements.iter()
+            .map(|placement| padded_size(placement.size).x)
+            .max()
+            .unwrap_or(0);
+
+        let width = ((area as f64).sqrt().ceil() as usize)
+            .max(max_width)
+            .max(1)
+            .next_power_of_two();
+
+        let mut height = width;
+        let packer = 'pack_loop: loop {
+            let mut packer = RectPacker::new(Vec2::new(width, height));
+
+            for &i in &order {
+                match packer.pack(padded_size(placements[i].size)) {
+                    Some(rect) => placements[i].atlas_rect = rect,
+                    None => {
+                        height *= 2;
+                        continue 'pack_loop;
+                    }
+                }
+            }
+
+            break packer;
         };
 
-        Ok((font, future))
+        let used_size = packer.used_size();
+        let atlas_size = Vec2::new(used_size.x.max(1), used_size.y.max(1));
+
+        let mut buffer2d = Buffer2d::new(atlas_size, background);
+
+        for placement in &mut placements {
+            if placement.is_empty() {
+                placement.atlas_rect = Rect::default();
+                continue;
+            }
+
+            let p1 = placement.atlas_rect.p1 + ATLAS_PADDING;
+            placement.atlas_rect = Rect::new(p1, p1 + placement.size);
+            rasterizate(placement, &mut buffer2d);
+        }
+
+        let mut chars_info = HashMap::with_capacity(chars.len());
+        for (char, glyph) in chars {
+            let placement = &placements[placement_indices[&glyph]];
+            let (face, k) = faces[placement.face];
+            chars_info.insert(char, CharInfo::new(face, placement, k, atlas_size));
+        }
+
+        Ok((chars_info, buffer2d))
     }
-}
\ No newline at end of file
+}
+
+/// Distance field glyph atlas, see [`Font::create_distance_field_atlas`].
+pub struct DistanceFieldAtlas {
+    px_size: u32,
+    options: DistanceFieldOptions,
+    chars_info: HashMap<char, CharInfo>,
+    buffer2d: Buffer2d<Vec4<f32>>,
+}
+
+impl DistanceFieldAtlas {
+    /// Glyph size the atlas was generated with, the atlas can be rendered at
+    /// other sizes
+    pub fn px_size(&self) -> u32 {
+        self.px_size
+    }
+
+    pub fn options(&self) -> &DistanceFieldOptions {
+        &self.options
+    }
+
+    pub fn chars_info(&self) -> &HashMap<char, CharInfo> {
+        &self.chars_info
+    }
+
+    pub fn buffer2d(&self) -> &Buffer2d<Vec4<f32>> {
+        &self.buffer2d
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vulkano::format::{Format, NumericType};
+
+    use crate::graphics::font_registry::tests::test_font;
+
+    use super::*;
+
+    #[test]
+    fn font_distance_field_range() {
+        let data = test_font("Test Sans", "Regular", &['H']);
+        let face = Arc::new(FontFace::new(Arc::new(data), 0).unwrap());
+        let font = Font::new("test".to_string(), vec![face], 7, &CharSet::Chars("H".to_string())).unwrap();
+
+        for range in [f32::NAN, 0.0, -1.0] {
+            let options = DistanceFieldOptions { range, ..Default::default() };
+            assert!(font.create_distance_field_atlas(&CharSet::Chars("H".to_string()), &options).is_err());
+        }
+
+        let atlas = font.create_distance_field_atlas(&CharSet::Chars("H".to_string()), &DistanceFieldOptions::default()).unwrap();
+        assert_eq!(atlas.chars_info().len(), 1);
+
+        let (format, _) = atlas.buffer2d().to_image_data();
+        assert_eq!(format, Format::R32G32B32A32_SFLOAT);
+        assert_ne!(format.type_color(), Some(NumericType::SRGB));
+    }
+}
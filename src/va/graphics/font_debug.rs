@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use crate::utils::cast::Cast;
+use crate::utils::math::geometry::rect::Rect;
+use crate::utils::math::vector::vector4::Vec4;
+
+use super::buffer::buffer2d::{save_buffer, Buffer2d};
+use super::font::{CharInfo, Font};
+use super::rasterizate::SimpleRasterizate;
+
+/// Opt-in diagnostics of font loading, files are written only into
+/// `directory`.
+#[derive(Clone, Debug)]
+pub struct FontDebugOptions {
+    directory: PathBuf,
+    glyph_boxes: bool,
+}
+
+impl FontDebugOptions {
+    /// Atlas images are saved into `directory`, it's created if missing
+    pub fn new<T>(directory: T) -> Self
+        where T: Into<PathBuf>,
+    {
+        Self {
+            directory: directory.into(),
+            glyph_boxes: false,
+        }
+    }
+
+    /// Also saves a copy of the atlas with glyph bounding boxes and a text
+    /// file with glyph metrics
+    pub fn with_glyph_boxes(mut self, glyph_boxes: bool) -> Self {
+        self.glyph_boxes = glyph_boxes;
+        self
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub fn glyph_boxes(&self) -> bool {
+        self.glyph_boxes
+    }
+
+    pub fn dump_font(&self, font: &Font) -> anyhow::Result<()> {
+        self.dump(&format!("{}_{}", font.name, font.px_size), &font.chars_info, &font.buffer2d)
+    }
+
+    /// Writes `{name}.png`, with glyph boxes also `{name}_boxes.png` and
+    /// `{name}_boxes.txt`
+    pub fn dump(&self, name: &str, chars_info: &HashMap<char, CharInfo>, buffer2d: &Buffer2d<Vec4<f32>>)
+        -> anyhow::Result<()>
+    {
+        // Font names are usually file names
+        let name: String = name.chars()
+            .map(|char| if char.is_alphanumeric() || char == '-' || char == '_' { char } else { '_' })
+            .collect();
+
+        fs::create_dir_all(&self.directory)
+            .with_context(|| format!("failed to create {:?} directory", self.directory))?;
+
+        save_buffer(self.directory.join(format!("{name}.png")), buffer2d)?;
+
+        if !self.glyph_boxes {
+            return Ok(());
+        }
+
+        let mut chars: Vec<(&char, &CharInfo)> = chars_info.iter().collect();
+        chars.sort_unstable_by_key(|(&char, _)| char);
+
+        let mut boxes = buffer2d.clone();
+        let mut metrics = String::new();
+        for (char, char_info) in chars {
+            let rect = char_info.atlas_rect();
+            let bearing = char_info.bearing();
+
+            // Infallible for String
+            let _ = writeln!(
+                metrics,
+                "{char:?} face: {} atlas: ({}, {}) - ({}, {}) bearing: ({}, {}) advance: {}",
+                char_info.face(),
+                rect.p1.x, rect.p1.y, rect.p2.x, rect.p2.y,
+                bearing.x, bearing.y,
+                char_info.advance_width(),
+            );
+
+            if char_info.is_empty() {
+                continue;
+            }
+
+            // Drawn in the padding around the glyph
+            let border: Rect<i32> = rect.cast();
+            boxes.draw_rect_border(border.p1 - 1, border.p2, Vec4::new(0.0, 1.0, 0.0, 1.0));
+        }
+
+        save_buffer(self.directory.join(format!("{name}_boxes.png")), &boxes)?;
+
+        let path = self.directory.join(format!("{name}_boxes.txt"));
+        fs::write(&path, metrics).with_context(|| format!("failed to write {path:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::graphics::char_set::CharSet;
+    use crate::graphics::font_registry::tests::test_font;
+    use crate::graphics::font_registry::FontFace;
+
+    use super::*;
+
+    #[test]
+    fn font_debug_dump() {
+        let data = test_font("Test Sans", "Regular", &[' ', 'H']);
+        let face = Arc::new(FontFace::new(Arc::new(data), 0).unwrap());
+        let font = Font::new("../Test Sans".to_string(), vec![face], 7, &CharSet::Chars(" H".to_string())).unwrap();
+
+        let directory = std::env::temp_dir().join(format!("va_font_debug_{}", std::process::id()));
+        let options = FontDebugOptions::new(&directory);
+        options.dump_font(&font).unwrap();
+        assert!(directory.join("___Test_Sans_7.png").is_file());
+        assert!(!directory.join("___Test_Sans_7_boxes.png").exists());
+
+        options.clone().with_glyph_boxes(true).dump_font(&font).unwrap();
+        assert!(directory.join("___Test_Sans_7_boxes.png").is_file());
+
+        let metrics = fs::read_to_string(directory.join("___Test_Sans_7_boxes.txt")).unwrap();
+        assert_eq!(metrics.lines().count(), 2);
+        assert!(metrics.lines().nth(1).unwrap().starts_with("'H' face: 0"));
+
+        // The directory can't be a file
+        assert!(FontDebugOptions::new(directory.join("___Test_Sans_7.png")).dump_font(&font).is_err());
+
+        // Only the dumped files are written
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 3);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+use ttf_parser::{name_id, Face, FaceParsingError, GlyphId};
+
+/// Face parsed once and kept together with the file data it borrows
+struct OwnedFace {
+    // Declared before `raw_data`, so it's dropped first
+    face: Face<'static>,
+    raw_data: Arc<Vec<u8>>,
+}
+
+impl OwnedFace {
+    fn new(raw_data: Arc<Vec<u8>>, index: u32) -> Result<Self, FaceParsingError> {
+        #[cfg(test)]
+        tests::PARSE_COUNT.with(|count| count.set(count.get() + 1));
+
+        let face = Face::from_slice(&raw_data, index)?;
+
+        // Safety: the face borrows the heap buffer of the vector, which
+        // doesn't move with the Arc and can't be mutated while the Arc is
+        // shared. The buffer outlives the face and the face is lent out only
+        // for the lifetime of `self`.
+        let face = unsafe { std::mem::transmute::<Face<'_>, Face<'static>>(face) };
+
+        Ok(Self {
+            face,
+            raw_data,
+        })
+    }
+
+    fn face(&self) -> &Face<'_> {
+        &self.face
+    }
+}
+
+/// Parsed face of a font file, the file data is shared by all faces of a
+/// collection.
+pub struct FontFace {
+    owned_face: OwnedFace,
+    index: u32,
+
+    family: String,
+    style: String,
+}
+
+impl FontFace {
+    /// `index` is the face index in a `.ttc` collection, 0 for single fonts
+    pub fn new(raw_data: Arc<Vec<u8>>, index: u32) -> anyhow::Result<Self> {
+        let owned_face = OwnedFace::new(raw_data, index)
+            .with_context(|| format!("invalid font face ({index})"))?;
+        let face = owned_face.face();
+
+        let family = FontFace::name(face, name_id::TYPOGRAPHIC_FAMILY)
+            .or_else(|| FontFace::name(face, name_id::FAMILY))
+            .unwrap_or_default();
+
+        let style = FontFace::name(face, name_id::TYPOGRAPHIC_SUBFAMILY)
+            .or_else(|| FontFace::name(face, name_id::SUBFAMILY))
+            .unwrap_or_default();
+
+        Ok(Self {
+            owned_face,
+            index,
+
+            family,
+            style,
+        })
+    }
+
+    pub fn face(&self) -> &Face<'_> {
+        self.owned_face.face()
+    }
+
+    pub fn raw_data(&self) -> &Arc<Vec<u8>> {
+        &self.owned_face.raw_data
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn family(&self) -> &str {
+        &self.family
+    }
+
+    pub fn style(&self) -> &str {
+        &self.style
+    }
+
+    pub fn glyph_index(&self, char: char) -> Option<GlyphId> {
+        self.face().glyph_index(char)
+    }
+
+    fn name(face: &Face, name_id: u16) -> Option<String> {
+        face.names()
+            .into_iter()
+            .filter(|name| name.name_id == name_id)
+            .find_map(|name| name.to_string())
+    }
+}
+
+/// Loaded font files and the fallback chain for characters missing in a
+/// face.
+#[derive(Default)]
+pub struct FontRegistry {
+    files: HashMap<String, Vec<Arc<FontFace>>>,
+    fallbacks: Vec<Arc<FontFace>>,
+}
+
+impl FontRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses all faces of the font file or collection. Already added files
+    /// are not parsed again.
+    pub fn add<F>(&mut self, file_name: &str, load: F) -> anyhow::Result<Vec<Arc<FontFace>>>
+        where F: FnOnce() -> anyhow::Result<Vec<u8>>,
+    {
+        if let Some(faces) = self.files.get(file_name) {
+            return Ok(faces.clone());
+        }
+
+        let raw_data = Arc::new(load()?);
+        let count = ttf_parser::fonts_in_collection(&raw_data).unwrap_or(1);
+
+        let faces = (0..count)
+            .map(|index| FontFace::new(Arc::clone(&raw_data), index).map(Arc::new))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .with_context(|| format!("failed to parse {file_name} font"))?;
+
+        if faces.is_empty() {
+            bail!("empty font collection ({file_name})");
+        }
+
+        self.files.insert(file_name.to_string(), faces.clone());
+        Ok(faces)
+    }
+
+    pub fn faces(&self, file_name: &str) -> Option<&[Arc<FontFace>]> {
+        self.files.get(file_name).map(Vec::as_slice)
+    }
+
+    pub fn face(&self, file_name: &str, index: u32) -> Option<Arc<FontFace>> {
+        self.files.get(file_name)?
+            .iter()
+            .find(|face| face.index() == index)
+            .cloned()
+    }
+
+    /// Case-insensitive search among added files. An empty style matches
+    /// "Regular" or any face of the family.
+    pub fn find(&self, family: &str, style: &str) -> Option<Arc<FontFace>> {
+        let mut faces: Vec<&Arc<FontFace>> = self.files.values()
+            .flatten()
+            .filter(|face| face.family().eq_ignore_ascii_case(family))
+            .collect();
+
+        faces.sort_by_key(|face| face.index());
+
+        if style.is_empty() {
+            faces.iter()
+                .find(|face| face.style().eq_ignore_ascii_case("Regular"))
+                .or_else(|| faces.first())
+                .map(|&face| Arc::clone(face))
+        }
+        else {
+            faces.into_iter()
+                .find(|face| face.style().eq_ignore_ascii_case(style))
+                .cloned()
+        }
+    }
+
+    /// Faces searched in order for characters missing in the primary face
+    pub fn fallbacks(&self) -> &[Arc<FontFace>] {
+        &self.fallbacks
+    }
+
+    pub fn set_fallbacks(&mut self, fallbacks: Vec<Arc<FontFace>>) {
+        self.fallbacks = fallbacks;
+    }
+
+    pub fn push_fallback(&mut self, face: Arc<FontFace>) {
+        self.fallbacks.push(face);
+    }
+
+    /// Returns the first face of the chain which has a glyph for the
+    /// character, `primary` is checked first.
+    pub fn resolve(&self, primary: &Arc<FontFace>, char: char) -> Option<(Arc<FontFace>, GlyphId)> {
+        std::iter::once(primary)
+            .chain(&self.fallbacks)
+            .find_map(|face| Some((Arc::clone(face), face.glyph_index(char)?)))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    const UNITS_PER_EM: u16 = 1000;
+
+    thread_local! {
+        /// Faces parsed on the thread of the test
+        pub(super) static PARSE_COUNT: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// Minimal TrueType font, every character is a box of 400x700 units with
+    /// the advance of 500 units
+    pub(crate) fn test_font(family: &str, style: &str, chars: &[char]) -> Vec<u8> {
+        test_font_with_kerning(family, style, chars, &[])
+    }
+
+    /// [`test_font`] with a `kern` table of the character pairs in font units
+    pub(crate) fn test_font_with_kerning(family: &str, style: &str, chars: &[char], kerning: &[(char, char, i16)]) -> Vec<u8> {
+        let mut output = Vec::new();
+        write_font(&mut output, &font_tables(family, style, chars, kerning));
+        output
+    }
+
+    /// `.ttc` collection of the faces
+    pub(crate) fn test_collection(faces: &[(&str, &str, &[char])]) -> Vec<u8> {
+        let mut output = b"ttcf".to_vec();
+        output.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        output.extend_from_slice(&(faces.len() as u32).to_be_bytes());
+        output.resize(output.len() + 4 * faces.len(), 0);
+
+        for (i, &(family, style, chars)) in faces.iter().enumerate() {
+            let offset = (output.len() as u32).to_be_bytes();
+            output[12 + 4 * i..16 + 4 * i].copy_from_slice(&offset);
+            write_font(&mut output, &font_tables(family, style, chars, &[]));
+        }
+
+        output
+    }
+
+    /// Tables are sorted by their tags, offsets are from the start of the
+    /// file
+    fn write_font(output: &mut Vec<u8>, tables: &[(&[u8; 4], Vec<u8>)]) {
+        output.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        output.extend_from_slice(&(tables.len() as u16).to_be_bytes());
+        output.extend_from_slice(&[0; 6]);
+
+        let mut offset = output.len() + 16 * tables.len();
+        for (tag, data) in tables {
+            output.extend_from_slice(*tag);
+            output.extend_from_slice(&[0; 4]);
+            output.extend_from_slice(&(offset as u32).to_be_bytes());
+            output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            offset += data.len().next_multiple_of(4);
+        }
+
+        for (_, data) in tables {
+            output.extend_from_slice(data);
+            output.resize(output.len().next_multiple_of(4), 0);
+        }
+    }
+
+    fn font_tables<'a>(family: &str, style: &str, chars: &[char], kerning: &[(char, char, i16)]) -> Vec<(&'a [u8; 4], Vec<u8>)> {
+        let mut chars = chars.to_vec();
+        chars.sort();
+
+        // `.notdef` is empty
+        let glyph_count = chars.len() as u16 + 1;
+        let be = |values: &[i32]| -> Vec<u8> {
+            values.iter().flat_map(|&val| (val as u16).to_be_bytes()).collect()
+        };
+
+        let mut cmap = be(&[0, 1, 3, 10, 0, 12, 12, 0]);
+        cmap.extend_from_slice(&(16 + 12 * chars.len() as u32).to_be_bytes());
+        cmap.extend_from_slice(&0u32.to_be_bytes());
+        cmap.extend_from_slice(&(chars.len() as u32).to_be_bytes());
+        for (i, &char) in chars.iter().enumerate() {
+            for value in [char as u32, char as u32, i as u32 + 1] {
+                cmap.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+
+        // Clockwise box from (50, 0) to (450, 700)
+        let glyph = be(&[1, 50, 0, 450, 700, 3, 0, 0x0101, 0x0101, 50, 0, 400, 0, 0, 700, 0, -700]);
+        let glyf: Vec<u8> = chars.iter().flat_map(|_| glyph.iter().copied()).collect();
+
+        let mut loca = 0u32.to_be_bytes().to_vec();
+        for i in 0..glyph_count as u32 {
+            loca.extend_from_slice(&(i * glyph.len() as u32).to_be_bytes());
+        }
+
+        let mut head = be(&[1, 0, 0, 0, 0, 0, 0x5f0f, 0x3cf5, 0, UNITS_PER_EM as i32]);
+        head.extend_from_slice(&[0; 16]);
+        head.extend_from_slice(&be(&[0, 0, 450, 700, 0, 8, 2, 1, 0]));
+
+        let mut hhea = be(&[1, 0, 800, -200, 0, 500, 0, 0, 450, 1, 0, 0, 0, 0, 0, 0, 0]);
+        hhea.extend_from_slice(&glyph_count.to_be_bytes());
+
+        let hmtx: Vec<u8> = (0..glyph_count).flat_map(|_| be(&[500, 50])).collect();
+        let maxp = be(&[0, 0x5000, i32::from(glyph_count)]);
+
+        let strings: Vec<Vec<u8>> = [family, style].iter()
+            .map(|name| name.encode_utf16().flat_map(u16::to_be_bytes).collect())
+            .collect();
+        let mut name = be(&[0, 2, 30]);
+        let mut name_offset = 0;
+        for (name_id, string) in [name_id::FAMILY, name_id::SUBFAMILY].into_iter().zip(&strings) {
+            name.extend_from_slice(&be(&[3, 1, 0x409, i32::from(name_id), string.len() as i32, name_offset]));
+            name_offset += string.len() as i32;
+        }
+        strings.iter().for_each(|string| name.extend_from_slice(string));
+
+        let mut tables = vec![
+            (b"cmap", cmap),
+            (b"glyf", glyf),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"hmtx", hmtx),
+            (b"loca", loca),
+            (b"maxp", maxp),
+            (b"name", name),
+        ];
+
+        if !kerning.is_empty() {
+            // Horizontal format 0 subtable with pairs sorted by glyph ids
+            let glyph_id = |char| chars.binary_search(&char).unwrap() as i32 + 1;
+            let mut pairs: Vec<(i32, i32, i32)> = kerning.iter()
+                .map(|&(left, right, value)| (glyph_id(left), glyph_id(right), i32::from(value)))
+                .collect();
+            pairs.sort();
+
+            let mut kern = be(&[0, 1, 0, 14 + 6 * pairs.len() as i32, 0x0001, pairs.len() as i32, 0, 0, 0]);
+            pairs.iter().for_each(|&(left, right, value)| kern.extend_from_slice(&be(&[left, right, value])));
+            tables.insert(5, (b"kern", kern));
+        }
+
+        tables
+    }
+
+    #[test]
+    fn font_registry_find() {
+        let mut registry = FontRegistry::new();
+        registry.add("sans.ttf", || Ok(test_font("Test Sans", "Regular", &['A', 'H']))).unwrap();
+        registry.add("sans-bold.ttf", || Ok(test_font("Test Sans", "Bold", &['A']))).unwrap();
+
+        let face = registry.find("test sans", "").unwrap();
+        assert_eq!((face.family(), face.style(), face.index()), ("Test Sans", "Regular", 0));
+        assert_eq!(registry.find("Test Sans", "bold").unwrap().style(), "Bold");
+        assert!(registry.find("Test Sans", "Italic").is_none());
+        assert!(registry.find("Test Serif", "").is_none());
+
+        // Added files aren't loaded again
+        let faces = registry.add("sans.ttf", || panic!("loaded again")).unwrap();
+        assert!(Arc::ptr_eq(&faces[0], &registry.face("sans.ttf", 0).unwrap()));
+        assert!(registry.face("sans.ttf", 1).is_none());
+        assert!(registry.faces("serif.ttf").is_none());
+
+        assert!(registry.add("broken.ttf", || Ok(vec![0; 64])).is_err());
+        assert!(registry.faces("broken.ttf").is_none());
+    }
+
+    #[test]
+    fn font_registry_collection() {
+        let data = test_collection(&[("Test Sans", "Regular", &['A']), ("Test Sans", "Italic", &['A', 'B'])]);
+
+        let mut registry = FontRegistry::new();
+        let faces = registry.add("sans.ttc", || Ok(data)).unwrap();
+        assert_eq!(faces.len(), 2);
+        assert_eq!(registry.faces("sans.ttc").unwrap().len(), 2);
+
+        let italic = registry.face("sans.ttc", 1).unwrap();
+        assert_eq!((italic.style(), italic.index()), ("Italic", 1));
+        assert!(Arc::ptr_eq(italic.raw_data(), faces[0].raw_data()));
+        assert_eq!(italic.glyph_index('B'), Some(GlyphId(2)));
+        assert_eq!(faces[0].glyph_index('B'), None);
+
+        assert_eq!(registry.find("Test Sans", "italic").unwrap().index(), 1);
+        assert_eq!(registry.find("Test Sans", "").unwrap().index(), 0);
+    }
+
+    #[test]
+    fn font_registry_fallbacks() {
+        let mut registry = FontRegistry::new();
+        let primary = registry.add("primary.ttf", || Ok(test_font("Primary", "Regular", &['A']))).unwrap().remove(0);
+        let first = registry.add("first.ttf", || Ok(test_font("First", "Regular", &['B']))).unwrap().remove(0);
+        let second = registry.add("second.ttf", || Ok(test_font("Second", "Regular", &['B', 'C']))).unwrap().remove(0);
+
+        registry.set_fallbacks(vec![Arc::clone(&first)]);
+        registry.push_fallback(Arc::clone(&second));
+        assert_eq!(registry.fallbacks().len(), 2);
+
+        let resolve = |char| registry.resolve(&primary, char).map(|(face, glyph_id)| (face.family().to_string(), glyph_id));
+        assert_eq!(resolve('A'), Some(("Primary".to_string(), GlyphId(1))));
+        assert_eq!(resolve('B'), Some(("First".to_string(), GlyphId(1))));
+        assert_eq!(resolve('C'), Some(("Second".to_string(), GlyphId(2))));
+        assert_eq!(resolve('D'), None);
+
+        // Faces are parsed only by `FontRegistry::add`
+        let parse_count = PARSE_COUNT.with(Cell::get);
+        for char in ['A', 'B', 'C', 'D'] {
+            registry.resolve(&primary, char);
+            assert_eq!(second.glyph_index(char).is_some(), ['B', 'C'].contains(&char));
+        }
+        assert!(std::ptr::eq(second.face(), second.face()));
+        assert_eq!(PARSE_COUNT.with(Cell::get), parse_count);
+
+        // Faces own the file data
+        drop(registry);
+        assert_eq!(second.face().units_per_em(), UNITS_PER_EM);
+        assert_eq!(second.glyph_index('C'), Some(GlyphId(2)));
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::bail;
+use ttf_parser::GlyphId;
+
+use crate::utils::math::geometry::rect::Rect;
+use crate::utils::math::vector::vector2::Vec2;
+use crate::utils::math::vector::vector4::Vec4;
+use crate::utils::shelf_packer::ShelfPacker;
+
+use super::buffer::buffer2d::Buffer2d;
+use super::font::{CharInfo, Font, GlyphPlacement, ATLAS_PADDING};
+use super::font_registry::FontFace;
+use super::rasterizate::SimpleRasterizate;
+
+/// Changed region of an atlas page, the GPU image of the page should be
+/// updated from `GlyphCachePage::buffer2d()` in this region
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasUpdate {
+    pub page: usize,
+    pub rect: Rect<usize>,
+}
+
+pub struct GlyphCachePage {
+    buffer2d: Buffer2d<Vec4<f32>>,
+    packer: ShelfPacker,
+}
+
+impl GlyphCachePage {
+    fn new(size: Vec2<usize>) -> Self {
+        Self {
+            buffer2d: Buffer2d::new(size, Vec4::from(0.0)),
+            packer: ShelfPacker::new(size),
+        }
+    }
+
+    pub fn buffer2d(&self) -> &Buffer2d<Vec4<f32>> {
+        &self.buffer2d
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CachedGlyph {
+    /// `None` for glyphs without an outline (e.g. space)
+    pub page: Option<usize>,
+    pub char_info: CharInfo,
+
+    /// Packed rect including padding
+    padded_rect: Rect<usize>,
+    last_used: u64,
+}
+
+/// Glyph atlas which rasterizates glyphs on demand.
+///
+/// Glyphs are packed into pages of the same size, when all pages are full the
+/// least recently used glyphs are evicted.
+pub struct GlyphCache {
+    face: Arc<FontFace>,
+    px_size: u32,
+    k: f64,
+
+    page_size: Vec2<usize>,
+    max_pages: usize,
+    pages: Vec<GlyphCachePage>,
+
+    glyphs: HashMap<GlyphId, CachedGlyph>,
+    updates: Vec<AtlasUpdate>,
+    time: u64,
+}
+
+impl GlyphCache {
+    /// `px_size` is the height of capital letters in pixels
+    pub fn new(face: Arc<FontFace>, px_size: u32, page_size: Vec2<usize>, max_pages: usize)
+        -> anyhow::Result<Self>
+    {
+        if max_pages == 0 {
+            bail!("invalid glyph cache max pages count ({max_pages})");
+        }
+
+        let k = Font::scale(face.face(), px_size)?;
+
+        Ok(Self {
+            face,
+            px_size,
+            k,
+
+            page_size,
+            max_pages,
+            pages: Vec::new(),
+
+            glyphs: HashMap::new(),
+            updates: Vec::new(),
+            time: 0,
+        })
+    }
+
+    pub fn px_size(&self) -> u32 {
+        self.px_size
+    }
+
+    pub fn pages(&self) -> &[GlyphCachePage] {
+        &self.pages
+    }
+
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Returns the cached glyph without rasterization.
+    pub fn get(&self, char: char) -> Option<&CachedGlyph> {
+        self.glyphs.get(&self.face.glyph_index(char)?)
+    }
+
+    /// Returns the glyph, rasterizates it if it isn't cached. `None` if the
+    /// font has no glyph for the character.
+    pub fn glyph(&mut self, char: char) -> anyhow::Result<Option<CachedGlyph>> {
+        self.time += 1;
+        let protected_since = self.time;
+
+        self.cache_char(char, protected_since)
+    }
+
+    /// Caches all characters of the text, glyphs of the text are never evicted
+    /// by each other.
+    pub fn prepare(&mut self, text: &str) -> anyhow::Result<()> {
+        self.time += 1;
+        let protected_since = self.time;
+
+        for char in text.chars() {
+            self.cache_char(char, protected_since)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns atlas regions changed since the last call.
+    pub fn take_updates(&mut self) -> Vec<AtlasUpdate> {
+        std::mem::take(&mut self.updates)
+    }
+
+    fn cache_char(&mut self, char: char, protected_since: u64) -> anyhow::Result<Option<CachedGlyph>> {
+        let face = Arc::clone(&self.face);
+        let face = face.face();
+
+        let glyph_id = match face.glyph_index(char) {
+            Some(val) => val,
+            None => return Ok(None),
+        };
+
+        if let Some(glyph) = self.glyphs.get_mut(&glyph_id) {
+            glyph.last_used = self.time;
+            return Ok(Some(*glyph));
+        }
+
+        let mut placement = GlyphPlacement::new(face, glyph_id, self.k);
+
+        let (page, padded_rect) = if placement.is_empty() {
+            (None, Rect::default())
+        }
+        else {
+            let size = placement.size + 2 * ATLAS_PADDING;
+            if size.x > self.page_size.x || size.y > self.page_size.y {
+                bail!("glyph of {char:?} character is bigger than the glyph cache page");
+            }
+
+            let (page, padded_rect) = self.allocate(size, protected_since)?;
+
+            let p1 = padded_rect.p1 + ATLAS_PADDING;
+            placement.atlas_rect = Rect::new(p1, p1 + placement.size);
+
+            let buffer2d = &mut self.pages[page].buffer2d;
+            buffer2d.mut_slice(padded_rect).fill(Vec4::from(0.0));
+            placement.rasterizate(face, buffer2d);
+
+            self.updates.push(AtlasUpdate { page, rect: padded_rect });
+            (Some(page), padded_rect)
+        };
+
+        let glyph = CachedGlyph {
+            page,
+            char_info: CharInfo::new(face, &placement, self.k, self.page_size),
+            padded_rect,
+            last_used: self.time,
+        };
+
+        self.glyphs.insert(glyph_id, glyph);
+        Ok(Some(glyph))
+    }
+
+    fn allocate(&mut self, size: Vec2<usize>, protected_since: u64) -> anyhow::Result<(usize, Rect<usize>)> {
+        loop {
+            for (i, page) in self.pages.iter_mut().enumerate() {
+                if let Some(rect) = page.packer.pack(size) {
+                    return Ok((i, rect));
+                }
+            }
+
+            if self.pages.len() < self.max_pages {
+                self.pages.push(GlyphCachePage::new(self.page_size));
+                self.updates.push(AtlasUpdate {
+                    page: self.pages.len() - 1,
+                    rect: Rect::new(Vec2::ZERO, self.page_size),
+                });
+                continue;
+            }
+
+            if !self.evict(protected_since) {
+                bail!("glyph cache is full");
+            }
+        }
+    }
+
+    /// Removes the least recently used glyph, returns `false` if all glyphs
+    /// are used after `protected_since`
+    fn evict(&mut self, protected_since: u64) -> bool {
+        let glyph_id = self.glyphs.iter()
+            .filter(|(_, glyph)| glyph.page.is_some() && glyph.last_used < protected_since)
+            .min_by_key(|(_, glyph)| glyph.last_used)
+            .map(|(&glyph_id, _)| glyph_id);
+
+        let glyph = match glyph_id.and_then(|glyph_id| self.glyphs.remove(&glyph_id)) {
+            Some(val) => val,
+            None => return false,
+        };
+
+        if let Some(page) = glyph.page {
+            self.pages[page].packer.unpack(glyph.padded_rect);
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::buffer::buffer2d::Buffer2dRead;
+    use crate::graphics::font_registry::tests::test_font;
+
+    use super::*;
+
+    /// Glyphs are 5x7 pixels, 7x9 with padding, so the page holds two of them
+    fn test_cache() -> GlyphCache {
+        let data = test_font("Test Sans", "Regular", &['A', 'B', 'C', 'H']);
+        let face = Arc::new(FontFace::new(Arc::new(data), 0).unwrap());
+        GlyphCache::new(face, 7, Vec2::new(14, 9), 1).unwrap()
+    }
+
+    #[test]
+    fn glyph_cache_updates() {
+        let mut cache = test_cache();
+        let a = cache.glyph('A').unwrap().unwrap();
+        let b = cache.glyph('B').unwrap().unwrap();
+        assert_eq!((a.page, a.padded_rect), (Some(0), Rect::new(Vec2::new(0, 0), Vec2::new(7, 9))));
+        assert_eq!((b.page, b.padded_rect), (Some(0), Rect::new(Vec2::new(7, 0), Vec2::new(14, 9))));
+
+        // The new page, then every rasterizated glyph
+        assert_eq!(cache.take_updates(), vec![
+            AtlasUpdate { page: 0, rect: Rect::new(Vec2::ZERO, Vec2::new(14, 9)) },
+            AtlasUpdate { page: 0, rect: a.padded_rect },
+            AtlasUpdate { page: 0, rect: b.padded_rect },
+        ]);
+
+        // Cached glyphs aren't rasterizated again
+        cache.glyph('A').unwrap();
+        assert!(cache.take_updates().is_empty());
+        assert!(cache.glyph('Z').unwrap().is_none());
+        assert_eq!(cache.len(), 2);
+
+        // The padding stays empty around the glyph
+        let buffer = cache.pages()[0].buffer2d();
+        assert_eq!(buffer.value(Vec2::new(0, 0)).w, 0.0);
+        assert_eq!(buffer.value(Vec2::new(3, 4)).w, 1.0);
+    }
+
+    #[test]
+    fn glyph_cache_eviction() {
+        let mut cache = test_cache();
+        cache.glyph('A').unwrap();
+        let b = cache.glyph('B').unwrap().unwrap();
+        cache.take_updates();
+
+        // 'B' is the least recently used glyph
+        cache.glyph('A').unwrap();
+        let c = cache.glyph('C').unwrap().unwrap();
+        assert_eq!(c.padded_rect, b.padded_rect);
+        assert!(cache.get('B').is_none());
+        assert!(cache.get('A').is_some());
+        assert_eq!(cache.take_updates(), vec![AtlasUpdate { page: 0, rect: c.padded_rect }]);
+
+        // Glyphs of one text don't evict each other
+        cache.prepare("AB").unwrap();
+        assert!(cache.get('C').is_none());
+        assert!(cache.prepare("ABC").is_err());
+    }
+}
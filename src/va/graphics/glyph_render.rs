@@ -3,30 +3,23 @@ use std::mem;
 
 use ttf_parser::OutlineBuilder;
 
-use crate::graphics::rasterizate::SimpleRasterizate;
 use crate::utils::cast::Cast;
 use crate::utils::math::geometry::curve;
-use crate::utils::math::geometry::line::LineEquation;
 use crate::utils::math::matrix::matrix3x3::Mat3x3;
 use crate::utils::math::vector::vector2::Vec2;
-use crate::utils::math::vector::vector3::Vec3;
 use crate::utils::math::vector::vector4::Vec4;
 
-use super::{buffer::buffer2d::{Buffer2d, Buffer2dRead}, rasterizate::Rasterizate};
-
-#[derive(Clone, Copy)]
-enum SweepDirection {
-    Clockwise,
-    Counterclockwise,
-}
+use super::{buffer::buffer2d::Buffer2d, rasterizate::Rasterizate};
+use super::coverage_accumulator::FillRule;
 
 pub struct GlyphRenderBuilder {
     transform: Mat3x3<f64>,
 
     points: Vec<Vec2<f32>>,
     prev_point: Vec2<f32>,
-    outlines: Vec<(SweepDirection, Vec<Vec2<f32>>)>,
+    outlines: Vec<Vec<Vec2<f32>>>,
     was_closed: bool,
+    fill_rule: FillRule,
 }
 
 impl GlyphRenderBuilder {
@@ -38,16 +31,22 @@ impl GlyphRenderBuilder {
             prev_point: Vec2::default(),
             outlines: Vec::new(),
             was_closed: false,
+            fill_rule: FillRule::default(),
         }
     }
 
+    /// TrueType and CFF outlines use [`FillRule::NonZero`]
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
     pub fn was_closed(&self) -> bool {
         self.was_closed
     }
 
     fn do_transform(&self, point: Vec2<f32>) -> Vec2<f32> {
-        let vector: Vec3<f64> = Vec3::new(point.x, point.y, 1.0).into();
-        (self.transform * vector).xy().cast()
+        self.transform.transform_point(point.into()).cast()
     }
 
     pub fn push_outline(&mut self) {
@@ -55,51 +54,30 @@ impl GlyphRenderBuilder {
             self.points.clear();
             return;
         }
-        
-        let mut direction_counter = 0.0;
-        let mut prev_n = (self.points[1] - self.points[0]).normalize();
-
-        let mut closure = |p1: Vec2<f32>, p2: Vec2<f32>| {
-            let n = (p2 - p1).normalize();
-            let z = prev_n.x * n.y - prev_n.y * n.x;
-
-            let angle = prev_n.dot(n).clamp(-1.0, 1.0).acos();
-            prev_n = n;
-
-            // By clockwise
-            if z >= 0.0 {
-                direction_counter += angle;
-            }
-            else {
-                direction_counter -= angle;
-            }
-        };
-
-        for i in 1..self.points.len() - 1 {
-            closure(self.points[i], self.points[i + 1]);
-        }
-        closure(*self.points.last().unwrap(), self.points[0]);
 
-        let direction = if direction_counter >= 0.0 {
-            SweepDirection::Clockwise
-        }
-        else {
-            SweepDirection::Counterclockwise
-        };
-        
-        self.outlines.push((direction, mem::replace(&mut self.points, Vec::new())));
+        self.outlines.push(mem::take(&mut self.points));
     }
 
-    pub fn build(self) -> GlyphRender {
-        GlyphRender { 
+    /// The last outline is pushed even without [`OutlineBuilder::close`]
+    pub fn build(mut self) -> GlyphRender {
+        self.push_outline();
+
+        GlyphRender {
             outlines: self.outlines,
+            fill_rule: self.fill_rule,
         }
     }
 }
 
 impl OutlineBuilder for GlyphRenderBuilder {
     fn move_to(&mut self, x: f32, y: f32) {
+        // CFF outlines are closed implicitly, so the first point is required
+        if !self.points.is_empty() {
+            self.push_outline();
+        }
+
         self.prev_point = self.do_transform(Vec2::new(x, y));
+        self.points.push(self.prev_point);
     }
 
     fn line_to(&mut self, x: f32, y: f32) {
@@ -124,15 +102,17 @@ impl OutlineBuilder for GlyphRenderBuilder {
     }
 
     fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
-        unimplemented!(); // TODO
-
         let p1 = self.do_transform(Vec2::new(x1, y1));
         let p2 = self.do_transform(Vec2::new(x2, y2));
         let p3 = self.do_transform(Vec2::new(x, y));
-        
-        self.points.push(p1);
-        self.points.push(p2);
-        self.points.push(p3);
+
+        let t_values = curve::subdivide_cube_curve(self.prev_point.into(), p1.into(), p2.into(), p3.into());
+
+        // Skip 0.0
+        for &t in &t_values[1..] {
+            let point = curve::get_cube_curve_point(self.prev_point.into(), p1.into(), p2.into(), p3.into(), t);
+            self.points.push(point.cast());
+        }
 
         self.prev_point = p3;
     }
@@ -144,85 +124,103 @@ impl OutlineBuilder for GlyphRenderBuilder {
 }
 
 pub struct GlyphRender {
-    outlines: Vec<(SweepDirection, Vec<Vec2<f32>>)>,
+    outlines: Vec<Vec<Vec2<f32>>>,
+    fill_rule: FillRule,
 }
 
 impl GlyphRender {
+    pub fn outlines(&self) -> &Vec<Vec<Vec2<f32>>> {
+        &self.outlines
+    }
+
+    pub fn fill_rule(&self) -> FillRule {
+        self.fill_rule
+    }
+
     pub fn rasterizate(&self, buffer: &mut Buffer2d<Vec4<f32>>) {
-        for y in 0..buffer.height() as i32 {
-            let mut cw_buffers = Vec::new();
-            let mut cc_buffers = Vec::new();
-
-            for (dir, points) in &self.outlines {
-                let mut buffer = Vec::new();
-
-                let mut closure = |p1: Vec2<f32>, p2: Vec2<f32>| {
-                    let p1: Vec2<i32> = p1.round().cast();
-                    let p2: Vec2<i32> = p2.round().cast();
-
-                    let (min, max) = if p1.y <= p2.y {
-                        (p1.y, p2.y)
-                    }
-                    else {
-                        (p2.y, p1.y)
-                    };
-                    
-                    if !(min < y && y <= max) {
-                        return;
-                    }
-
-                    let equation = LineEquation::new(p1, p2);
-                    let x: i32 = equation.get_x_by_y(y.into()).round().cast();
-                    buffer.push(x);
-                };
-
-                for i in 1..points.len() {
-                    closure(points[i - 1], points[i]);
-                }
-
-                closure(*points.last().unwrap(), points[0]);
-
-                buffer.sort_unstable();
-
-                let buffers = match dir {
-                    SweepDirection::Clockwise => &mut cw_buffers,
-                    SweepDirection::Counterclockwise => &mut cc_buffers,
-                };
-
-                if !buffer.is_empty() {
-                    buffers.push(buffer);
-                }                
-            }
-
-            for cw_buffer in cw_buffers {
-                for i in (0..cw_buffer.len()).step_by(2) {
-                    buffer.draw_horizontal_line(
-                        cw_buffer[i], 
-                        cw_buffer[i + 1],
-                        y,
-                        Vec4::from(1.0)
-                    );
-                }
-            }
-
-            for cc_buffer in cc_buffers {
-                for i in (0..cc_buffer.len()).step_by(2) {
-                    buffer.draw_horizontal_line(
-                        cc_buffer[i],
-                        cc_buffer[i + 1], 
-                        y, 
-                        Vec4::new(0.0, 0.0, 0.0, 1.0)
-                    );
-                }
-            }
-        }
+        buffer.fill_outlines(&self.outlines, self.fill_rule, Vec4::from(1.0));
+    }
+}
 
-        for (_, points) in &self.outlines {
-            for i in 1..points.len() {
-                buffer.draw_line(points[i - 1].round().cast(), points[i].round().cast(), Vec4::from(1.0));
-            }
+#[cfg(test)]
+mod tests {
+    use crate::graphics::snapshot::Snapshot;
+
+    use super::*;
+
+    /// Outlines of "O" with quadratic curves, the inner one is reversed
+    fn glyph_o(builder: &mut GlyphRenderBuilder) {
+        builder.move_to(10.0, 0.0);
+        builder.quad_to(20.0, 0.0, 20.0, 12.0);
+        builder.quad_to(20.0, 24.0, 10.0, 24.0);
+        builder.quad_to(0.0, 24.0, 0.0, 12.0);
+        builder.quad_to(0.0, 0.0, 10.0, 0.0);
+        builder.close();
+
+        builder.move_to(10.0, 4.0);
+        builder.quad_to(4.0, 4.0, 4.0, 12.0);
+        builder.quad_to(4.0, 20.0, 10.0, 20.0);
+        builder.quad_to(16.0, 20.0, 16.0, 12.0);
+        builder.quad_to(16.0, 4.0, 10.0, 4.0);
+        builder.close();
+    }
 
-            buffer.draw_line((*points.last().unwrap()).round().cast(), points[0].round().cast(), Vec4::from(1.0));
-        }
+    #[test]
+    fn glyph_render_quad_outlines() {
+        let mut builder = GlyphRenderBuilder::new(Mat3x3::with_translate(Vec2::new(4.0, 4.0)));
+        glyph_o(&mut builder);
+        assert!(builder.was_closed());
+
+        let glyph_render = builder.build();
+        assert_eq!(glyph_render.outlines().len(), 2);
+
+        let mut buffer = Buffer2d::new(Vec2::new(28, 32), Vec4::new(0.0, 0.0, 0.0, 1.0));
+        glyph_render.rasterizate(&mut buffer);
+
+        Snapshot::new("glyph_render_quad_outlines").with_tolerance(1).assert(&buffer);
+    }
+
+    #[test]
+    fn glyph_render_cube_outline() {
+        // Scaled and flipped like font units
+        let mut transform = Mat3x3::with_translate(Vec2::new(2.0, 30.0));
+        transform.scale(Vec2::new(0.5, -0.5));
+
+        let mut builder = GlyphRenderBuilder::new(transform);
+        builder.move_to(0.0, 0.0);
+        builder.curve_to(10.0, 60.0, 50.0, 60.0, 56.0, 0.0);
+        builder.line_to(28.0, 12.0);
+
+        // Closed implicitly by the next outline like CFF
+        builder.move_to(20.0, 40.0);
+        builder.line_to(36.0, 40.0);
+        builder.line_to(28.0, 50.0);
+        builder.close();
+
+        let glyph_render = builder.build();
+        assert_eq!(glyph_render.outlines().len(), 2);
+
+        let mut buffer = Buffer2d::new(Vec2::new(32, 32), Vec4::new(0.0, 0.0, 0.0, 1.0));
+        glyph_render.rasterizate(&mut buffer);
+
+        // Subdivision of the curve depends on float rounding
+        Snapshot::new("glyph_render_cube_outline")
+            .with_tolerance(2)
+            .with_max_mismatched(4)
+            .with_min_ssim(0.99)
+            .assert(&buffer);
+    }
+
+    #[test]
+    fn glyph_render_unclosed_outline() {
+        let mut builder = GlyphRenderBuilder::new(Mat3x3::default());
+        builder.move_to(0.0, 0.0);
+        builder.line_to(10.0, 0.0);
+        builder.line_to(10.0, 10.0);
+        assert!(!builder.was_closed());
+
+        let glyph_render = builder.build();
+        assert_eq!(glyph_render.outlines().len(), 1);
+        assert_eq!(glyph_render.outlines()[0].len(), 3);
     }
-}
\ No newline at end of file
+}
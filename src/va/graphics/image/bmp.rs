@@ -0,0 +1,263 @@
+use anyhow::{bail, ensure};
+
+use crate::graphics::buffer::buffer2d::{Buffer2d, Buffer2dRead};
+use crate::graphics::color_space::ColorSpace;
+use crate::utils::math::vector::vector2::Vec2;
+use crate::utils::math::vector::vector4::Vec4;
+
+use super::codec::{bytes, checked_size, rgba8_bytes, rgba8_to_rgba, u16_le, u32_le, ImageCodec};
+
+// https://en.wikipedia.org/wiki/BMP_file_format
+const MAGIC: &[u8; 2] = b"BM";
+const FILE_HEADER_SIZE: usize = 14;
+const CORE_HEADER_SIZE: usize = 12;
+const INFO_HEADER_SIZE: usize = 40;
+const V4_HEADER_SIZE: usize = 108;
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/// "sRGB" color space tag of V4 headers
+const LCS_SRGB: u32 = 0x7352_4742;
+
+/// Windows bitmap, uncompressed 1, 4, 8, 16, 24 and 32 bits per pixel
+pub struct BmpCodec;
+
+impl ImageCodec for BmpCodec {
+    fn name(&self) -> &str {
+        "BMP"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["bmp", "dib"]
+    }
+
+    fn is_format(&self, data: &[u8]) -> bool {
+        data.starts_with(MAGIC) && data.len() >= FILE_HEADER_SIZE + CORE_HEADER_SIZE
+    }
+
+    fn decode(&self, data: &[u8]) -> anyhow::Result<Buffer2d<Vec4<f32>>> {
+        ensure!(data.starts_with(MAGIC), "invalid BMP header");
+        let data_offset = u32_le(data, 10)? as usize;
+        let header_size = u32_le(data, FILE_HEADER_SIZE)? as usize;
+        let header = bytes(data, FILE_HEADER_SIZE, header_size)?;
+
+        let (width, height, bit_count, compression, colors_used) = if header_size == CORE_HEADER_SIZE {
+            let width = i64::from(u16_le(header, 4)?);
+            let height = i64::from(u16_le(header, 6)? as i16);
+
+            (width, height, u16_le(header, 10)?, BI_RGB, 0)
+        }
+        else {
+            ensure!(header_size >= INFO_HEADER_SIZE, "unsupported BMP header size {header_size}");
+            let width = i64::from(u32_le(header, 4)? as i32);
+            let height = i64::from(u32_le(header, 8)? as i32);
+
+            (width, height, u16_le(header, 14)?, u32_le(header, 16)?, u32_le(header, 32)? as usize)
+        };
+
+        ensure!(width > 0 && height != 0, "invalid BMP size {width}x{height}");
+        let is_top_down = height < 0;
+        let (width, height) = (width as usize, height.unsigned_abs() as usize);
+
+        // Masks are in the header since V2, or follow the info header
+        let masks = match compression {
+            BI_RGB => match bit_count {
+                16 => [0x7c00, 0x03e0, 0x001f, 0],
+                24 | 32 => [0xff_0000, 0xff00, 0xff, 0],
+                _ => [0; 4],
+            },
+            BI_BITFIELDS | BI_ALPHABITFIELDS => {
+                let offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+                let has_alpha = compression == BI_ALPHABITFIELDS || header_size >= 56;
+
+                [
+                    u32_le(data, offset)?,
+                    u32_le(data, offset + 4)?,
+                    u32_le(data, offset + 8)?,
+                    if has_alpha { u32_le(data, offset + 12)? } else { 0 },
+                ]
+            }
+            _ => bail!("unsupported BMP compression {compression}"),
+        };
+
+        let palette = if bit_count <= 8 {
+            let (entry_size, offset) = if header_size == CORE_HEADER_SIZE {
+                (3, FILE_HEADER_SIZE + CORE_HEADER_SIZE)
+            }
+            else {
+                (4, FILE_HEADER_SIZE + header_size)
+            };
+
+            let count = if colors_used == 0 { 1 << bit_count } else { colors_used };
+            bytes(data, offset, count * entry_size)?
+                .chunks_exact(entry_size)
+                .map(|entry| [entry[2], entry[1], entry[0], 255])
+                .collect()
+        }
+        else {
+            Vec::new()
+        };
+
+        let bit_count = usize::from(bit_count);
+        ensure!(matches!(bit_count, 1 | 4 | 8 | 16 | 24 | 32), "unsupported BMP bit count {bit_count}");
+
+        // Rows are aligned by 4 bytes
+        let stride = (bit_count * width).div_ceil(32) * 4;
+        let pixels = bytes(data, data_offset, stride * height)?;
+
+        let mut values = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let row_index = if is_top_down { y } else { height - 1 - y };
+            let row = &pixels[row_index * stride..(row_index + 1) * stride];
+
+            for x in 0..width {
+                let pixel = match bit_count {
+                    1 | 4 | 8 => {
+                        let bit = x * bit_count;
+                        let index = (row[bit / 8] >> (8 - bit_count - bit % 8)) & ((1 << bit_count) - 1) as u8;
+
+                        match palette.get(usize::from(index)) {
+                            Some(&color) => color,
+                            None => bail!("BMP palette index {index} out of range"),
+                        }
+                    }
+                    _ => {
+                        let bytes = &row[x * bit_count / 8..(x + 1) * bit_count / 8];
+                        let value = bytes.iter().rev().fold(0u32, |value, &byte| value << 8 | u32::from(byte));
+
+                        let alpha = if masks[3] == 0 { 255 } else { channel(value, masks[3]) };
+                        [channel(value, masks[0]), channel(value, masks[1]), channel(value, masks[2]), alpha]
+                    }
+                };
+
+                values.push(rgba8_to_rgba(pixel));
+            }
+        }
+
+        Ok(Buffer2d::from_iter(Vec2::new(width, height), values.into_iter()).with_color_space(ColorSpace::Srgb))
+    }
+
+    /// 32-bit BGRA with alpha mask in a V4 header, rows from the bottom
+    fn encode(&self, buffer: &Buffer2d<Vec4<f32>>) -> anyhow::Result<Vec<u8>> {
+        let (width, height) = checked_size(buffer, i32::MAX as usize)?;
+
+        let image_size = buffer.width() * buffer.height() * 4;
+        let data_offset = FILE_HEADER_SIZE + V4_HEADER_SIZE;
+        let file_size = u32::try_from(data_offset + image_size)?;
+
+        let mut output = Vec::with_capacity(file_size as usize);
+        output.extend_from_slice(MAGIC);
+        output.extend_from_slice(&file_size.to_le_bytes());
+        output.extend_from_slice(&[0; 4]);
+        output.extend_from_slice(&(data_offset as u32).to_le_bytes());
+
+        output.extend_from_slice(&(V4_HEADER_SIZE as u32).to_le_bytes());
+        output.extend_from_slice(&width.to_le_bytes());
+        output.extend_from_slice(&height.to_le_bytes());
+        output.extend_from_slice(&1u16.to_le_bytes());
+        output.extend_from_slice(&32u16.to_le_bytes());
+        output.extend_from_slice(&BI_BITFIELDS.to_le_bytes());
+        output.extend_from_slice(&(image_size as u32).to_le_bytes());
+
+        // 72 DPI
+        output.extend_from_slice(&2835u32.to_le_bytes());
+        output.extend_from_slice(&2835u32.to_le_bytes());
+
+        // Colors used and important
+        output.extend_from_slice(&[0; 8]);
+
+        for mask in [0xff_0000u32, 0xff00, 0xff, 0xff00_0000] {
+            output.extend_from_slice(&mask.to_le_bytes());
+        }
+        output.extend_from_slice(&LCS_SRGB.to_le_bytes());
+
+        // Endpoints and gamma, unused with sRGB
+        output.extend_from_slice(&[0; 48]);
+
+        let bytes = rgba8_bytes(buffer);
+        for row in bytes.chunks(buffer.width() * 4).rev() {
+            for pixel in row.chunks_exact(4) {
+                output.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// Masked channel scaled to 8 bits
+fn channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+
+    let value = u64::from((value & mask) >> mask.trailing_zeros());
+    let max = u64::from(mask >> mask.trailing_zeros());
+
+    ((value * 255 + max / 2) / max) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::image::codec::tests::{assert_same, test_buffer};
+
+    use super::*;
+
+    fn info_header(width: i32, height: i32, bit_count: u16, colors_used: u32, data_offset: u32) -> Vec<u8> {
+        let mut data = b"BM".to_vec();
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&data_offset.to_le_bytes());
+        data.extend_from_slice(&40u32.to_le_bytes());
+        data.extend_from_slice(&width.to_le_bytes());
+        data.extend_from_slice(&height.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&bit_count.to_le_bytes());
+        data.extend_from_slice(&[0; 16]);
+        data.extend_from_slice(&colors_used.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+
+        data
+    }
+
+    #[test]
+    fn bmp_round_trip() {
+        let buffer = test_buffer();
+        let data = BmpCodec.encode(&buffer).unwrap();
+
+        assert!(BmpCodec.is_format(&data));
+        assert_eq!(data.len(), FILE_HEADER_SIZE + V4_HEADER_SIZE + 5 * 3 * 4);
+        assert_same(&BmpCodec.decode(&data).unwrap(), &buffer);
+    }
+
+    #[test]
+    fn bmp_decode_24_bit() {
+        // 2x2 from the bottom, rows padded to 8 bytes
+        let mut data = info_header(2, 2, 24, 0, 54);
+        data.extend_from_slice(&[0, 0, 255, 0, 255, 0, 0, 0]);
+        data.extend_from_slice(&[255, 0, 0, 255, 255, 255, 0, 0]);
+
+        let buffer = BmpCodec.decode(&data).unwrap();
+        assert_eq!(rgba8_bytes(&buffer), vec![
+            0, 0, 255, 255, 255, 255, 255, 255,
+            255, 0, 0, 255, 0, 255, 0, 255,
+        ]);
+    }
+
+    #[test]
+    fn bmp_decode_palette() {
+        // 3x1 top-down 4-bit with 2 palette colors
+        let mut data = info_header(3, -1, 4, 2, 62);
+        data.extend_from_slice(&[0, 0, 0, 0, 255, 128, 0, 0]);
+        data.extend_from_slice(&[0x10, 0x10, 0, 0]);
+
+        let buffer = BmpCodec.decode(&data).unwrap();
+        assert_eq!(rgba8_bytes(&buffer), vec![0, 128, 255, 255, 0, 0, 0, 255, 0, 128, 255, 255]);
+
+        // Index out of the palette
+        let last = data.len() - 3;
+        data[last] = 0x20;
+        assert!(BmpCodec.decode(&data).is_err());
+    }
+}
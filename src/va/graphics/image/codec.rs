@@ -0,0 +1,224 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use png::ColorType;
+
+use crate::graphics::buffer::buffer2d::{Buffer2d, Buffer2dRead};
+use crate::graphics::pixel_format::{PixelFormat, Rgba8};
+use crate::utils::math::vector::vector4::Vec4;
+
+use super::bmp::BmpCodec;
+use super::ppm::PpmCodec;
+use super::qoi::QoiCodec;
+use super::tga::TgaCodec;
+use super::{decode_png, write_png, PngColorMetadata};
+
+/// Encoder and decoder of an image file format
+pub trait ImageCodec {
+    fn name(&self) -> &str;
+
+    /// Lowercase file extensions without the dot
+    fn extensions(&self) -> &[&str];
+
+    /// Checks the magic bytes of the encoded data
+    fn is_format(&self, data: &[u8]) -> bool;
+
+    fn decode(&self, data: &[u8]) -> anyhow::Result<Buffer2d<Vec4<f32>>>;
+    fn encode(&self, buffer: &Buffer2d<Vec4<f32>>) -> anyhow::Result<Vec<u8>>;
+}
+
+/// Set of codecs searched by the data or the file extension
+pub struct ImageCodecs {
+    codecs: Vec<Box<dyn ImageCodec>>,
+}
+
+impl ImageCodecs {
+    /// Built-in PNG, QOI, BMP, PPM, PGM and TGA codecs
+    pub fn new() -> Self {
+        Self {
+            codecs: vec![
+                Box::new(PngCodec),
+                Box::new(QoiCodec),
+                Box::new(BmpCodec),
+                Box::new(PpmCodec::ppm()),
+                Box::new(PpmCodec::pgm()),
+
+                // TGA has no magic bytes, so it's checked last
+                Box::new(TgaCodec::default()),
+            ],
+        }
+    }
+
+    /// Added codecs are searched before the existing ones
+    pub fn add(&mut self, codec: Box<dyn ImageCodec>) {
+        self.codecs.insert(0, codec);
+    }
+
+    pub fn codecs(&self) -> impl Iterator<Item = &dyn ImageCodec> {
+        self.codecs.iter().map(|codec| codec.as_ref())
+    }
+
+    pub fn find_by_data(&self, data: &[u8]) -> Option<&dyn ImageCodec> {
+        self.codecs().find(|codec| codec.is_format(data))
+    }
+
+    pub fn find_by_extension(&self, extension: &str) -> Option<&dyn ImageCodec> {
+        let extension = extension.to_lowercase();
+        self.codecs().find(|codec| codec.extensions().contains(&extension.as_str()))
+    }
+
+    pub fn decode(&self, data: &[u8]) -> anyhow::Result<Buffer2d<Vec4<f32>>> {
+        match self.find_by_data(data) {
+            Some(codec) => codec.decode(data).with_context(|| format!("failed to decode {} image", codec.name())),
+            None => bail!("unknown image format"),
+        }
+    }
+
+    /// The encoder is picked by the file extension
+    pub fn save<T>(&self, path: T, buffer: &Buffer2d<Vec4<f32>>) -> anyhow::Result<()>
+        where T: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|val| val.to_str()).unwrap_or("");
+        let codec = self.find_by_extension(extension)
+            .with_context(|| format!("no image codec for {path:?}"))?;
+
+        let data = codec.encode(buffer).with_context(|| format!("failed to encode {} image", codec.name()))?;
+        fs::write(path, data).with_context(|| format!("failed to write {path:?}"))
+    }
+}
+
+impl Default for ImageCodecs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 8-bit RGBA PNG with the color space of the buffer
+pub struct PngCodec;
+
+impl ImageCodec for PngCodec {
+    fn name(&self) -> &str {
+        "PNG"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["png"]
+    }
+
+    fn is_format(&self, data: &[u8]) -> bool {
+        data.starts_with(b"\x89PNG\r\n\x1a\n")
+    }
+
+    fn decode(&self, data: &[u8]) -> anyhow::Result<Buffer2d<Vec4<f32>>> {
+        decode_png(data)
+    }
+
+    fn encode(&self, buffer: &Buffer2d<Vec4<f32>>) -> anyhow::Result<Vec<u8>> {
+        let (width, height) = checked_size(buffer, u32::MAX as usize)?;
+
+        let mut output = Vec::new();
+        write_png(
+            &mut output,
+            &rgba8_bytes(buffer),
+            ColorType::Rgba,
+            width,
+            height,
+            &buffer.color_space_tag().map(PngColorMetadata::from).unwrap_or_default(),
+        )?;
+
+        Ok(output)
+    }
+}
+
+/// Width and height of the buffer, both must be not bigger than `max`
+pub(crate) fn checked_size(buffer: &Buffer2d<Vec4<f32>>, max: usize) -> anyhow::Result<(u32, u32)> {
+    if buffer.width() > max || buffer.height() > max {
+        bail!("too big image {}x{} (max {max})", buffer.width(), buffer.height());
+    }
+
+    Ok((buffer.width() as u32, buffer.height() as u32))
+}
+
+pub(crate) fn rgba8_bytes(buffer: &Buffer2d<Vec4<f32>>) -> Vec<u8> {
+    buffer.convert::<Rgba8>().to_bytes()
+}
+
+pub(crate) fn rgba8_to_rgba(value: [u8; 4]) -> Vec4<f32> {
+    Rgba8(value).to_rgba()
+}
+
+pub(crate) fn bytes(data: &[u8], offset: usize, len: usize) -> anyhow::Result<&[u8]> {
+    offset.checked_add(len)
+        .and_then(|end| data.get(offset..end))
+        .context("unexpected end of image data")
+}
+
+pub(crate) fn u16_le(data: &[u8], offset: usize) -> anyhow::Result<u16> {
+    let bytes = bytes(data, offset, 2)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+pub(crate) fn u32_le(data: &[u8], offset: usize) -> anyhow::Result<u32> {
+    let bytes = bytes(data, offset, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::graphics::color_space::ColorSpace;
+    use crate::utils::math::vector::vector2::Vec2;
+
+    use super::*;
+
+    /// Pixels representable by all 8-bit formats with alpha
+    pub(crate) fn test_buffer() -> Buffer2d<Vec4<f32>> {
+        let size = Vec2::new(5, 3);
+        let values = (0..size.x * size.y).map(|i| {
+            match i % 4 {
+                0 => rgba8_to_rgba([255, 0, 0, 255]),
+                1 => rgba8_to_rgba([10, 200, 30, 128]),
+                2 => rgba8_to_rgba([i as u8 * 17, 255, 0, 255]),
+                _ => rgba8_to_rgba([0, 0, 0, 0]),
+            }
+        });
+
+        Buffer2d::from_iter(size, values).with_color_space(ColorSpace::Srgb)
+    }
+
+    pub(crate) fn assert_same(a: &Buffer2d<Vec4<f32>>, b: &Buffer2d<Vec4<f32>>) {
+        assert_eq!(a.size(), b.size());
+        assert_eq!(rgba8_bytes(a), rgba8_bytes(b));
+    }
+
+    #[test]
+    fn image_codecs_detection() {
+        let codecs = ImageCodecs::new();
+        let buffer = test_buffer();
+
+        for extension in ["png", "qoi", "bmp", "tga", "PNG"] {
+            let codec = codecs.find_by_extension(extension).unwrap();
+            let data = codec.encode(&buffer).unwrap();
+
+            assert_eq!(codecs.find_by_data(&data).unwrap().name(), codec.name());
+            assert_same(&codecs.decode(&data).unwrap(), &buffer);
+        }
+
+        assert!(codecs.find_by_extension("jpg").is_none());
+        assert!(codecs.decode(b"not an image").is_err());
+    }
+
+    #[test]
+    fn image_codecs_save() {
+        let path = std::env::temp_dir().join(format!("va_image_codecs_save_{}.qoi", std::process::id()));
+        let buffer = test_buffer();
+
+        ImageCodecs::new().save(&path, &buffer).unwrap();
+        let data = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(data.starts_with(b"qoif"));
+        assert_same(&ImageCodecs::new().decode(&data).unwrap(), &buffer);
+    }
+}
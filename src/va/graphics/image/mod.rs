@@ -0,0 +1,338 @@
+pub mod bmp;
+pub mod codec;
+pub mod ppm;
+pub mod qoi;
+pub mod tga;
+
+use std::{path::Path, io::BufWriter, io::Cursor, io::Write, fs::File};
+
+use anyhow::bail;
+use png::{BitDepth, ColorType, ScaledFloat, SourceChromaticities, SrgbRenderingIntent, Transformations};
+
+use crate::utils::math::vector::vector2::Vec2;
+use crate::utils::math::vector::vector4::Vec4;
+
+use super::buffer::buffer2d::Buffer2d;
+use super::color_space::ColorSpace;
+
+/// Color space chunks of a PNG file: gAMA, sRGB and iCCP
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PngColorMetadata {
+    gamma: Option<f32>,
+    srgb: Option<SrgbRenderingIntent>,
+    icc_profile: Option<Vec<u8>>,
+}
+
+impl PngColorMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the chunks of a decoded PNG header
+    pub fn from_info(info: &png::Info) -> Self {
+        Self {
+            gamma: info.source_gamma.or(info.gama_chunk).map(ScaledFloat::into_value),
+            srgb: info.srgb,
+            icc_profile: info.icc_profile.as_ref().map(|profile| profile.to_vec()),
+        }
+    }
+
+    /// Encoding gamma, e.g. 1/2.2 for sRGB like values and 1 for linear ones
+    pub fn with_gamma(mut self, gamma: Option<f32>) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// sRGB chunk, it overrides the gamma and the ICC profile
+    pub fn with_srgb(mut self, srgb: Option<SrgbRenderingIntent>) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    /// Uncompressed ICC profile
+    pub fn with_icc_profile(mut self, icc_profile: Option<Vec<u8>>) -> Self {
+        self.icc_profile = icc_profile;
+        self
+    }
+
+    pub fn gamma(&self) -> Option<f32> {
+        self.gamma
+    }
+
+    pub fn srgb(&self) -> Option<SrgbRenderingIntent> {
+        self.srgb
+    }
+
+    pub fn icc_profile(&self) -> Option<&[u8]> {
+        self.icc_profile.as_deref()
+    }
+
+    /// Images without color chunks and with ICC profiles are treated as sRGB
+    pub fn color_space(&self) -> ColorSpace {
+        match (self.srgb, self.gamma) {
+            (None, Some(gamma)) if (gamma - 1.0).abs() < 0.01 => ColorSpace::Linear,
+            _ => ColorSpace::Srgb,
+        }
+    }
+}
+
+impl From<ColorSpace> for PngColorMetadata {
+    fn from(color_space: ColorSpace) -> Self {
+        match color_space {
+            ColorSpace::Linear => PngColorMetadata::new().with_gamma(Some(1.0)),
+            ColorSpace::Srgb => PngColorMetadata::new().with_srgb(Some(SrgbRenderingIntent::Perceptual)),
+        }
+    }
+}
+
+/// Reads only the header of the PNG file
+pub fn read_png_color_metadata(data: &[u8]) -> anyhow::Result<PngColorMetadata> {
+    let reader = png::Decoder::new(Cursor::new(data)).read_info()?;
+    Ok(PngColorMetadata::from_info(reader.info()))
+}
+
+/// Decodes all PNG color types and bit depths, palettes and tRNS chunks are
+/// expanded into alpha, interlaced images are deinterlaced. The color space
+/// of the buffer is taken from the color chunks.
+pub fn decode_png(data: &[u8]) -> anyhow::Result<Buffer2d<Vec4<f32>>> {
+    let mut decoder = png::Decoder::new(Cursor::new(data));
+    decoder.set_transformations(Transformations::EXPAND);
+    let mut reader = decoder.read_info()?;
+
+    let color_space = PngColorMetadata::from_info(reader.info()).color_space();
+
+    // Low bit depths are expanded to 8 bits, 16 bits are kept
+    let (color_type, bit_depth) = reader.output_color_type();
+
+    let mut image_data = vec![0; reader.output_buffer_size()];
+    let output_info = reader.next_frame(&mut image_data)?;
+    image_data.truncate(output_info.buffer_size());
+
+    let samples: Vec<f32> = match bit_depth {
+        BitDepth::Eight => image_data.iter().map(|&val| f32::from(val) / 255.0).collect(),
+        BitDepth::Sixteen => image_data.chunks_exact(2)
+            .map(|val| f32::from(u16::from_be_bytes([val[0], val[1]])) / 65535.0)
+            .collect(),
+        _ => bail!("unsupported PNG output bit depth {:?}", bit_depth),
+    };
+
+    let pixels: Vec<Vec4<f32>> = match color_type {
+        ColorType::Grayscale => samples.iter().map(|&val| Vec4::new(val, val, val, 1.0)).collect(),
+        ColorType::GrayscaleAlpha => samples.chunks_exact(2)
+            .map(|val| Vec4::new(val[0], val[0], val[0], val[1]))
+            .collect(),
+        ColorType::Rgb => samples.chunks_exact(3).map(|val| Vec4::new(val[0], val[1], val[2], 1.0)).collect(),
+        ColorType::Rgba => samples.chunks_exact(4).map(|val| Vec4::new(val[0], val[1], val[2], val[3])).collect(),
+        ColorType::Indexed => bail!("PNG palette is not expanded"),
+    };
+
+    let size = Vec2::new(output_info.width as usize, output_info.height as usize);
+    Ok(Buffer2d::from_iter(size, pixels.into_iter()).with_color_space(color_space))
+}
+
+pub fn save_image<T>(
+    path: T,
+    data: &[u8],
+    color_type: ColorType,
+    width: u32,
+    height: u32,
+    metadata: &PngColorMetadata,
+) -> anyhow::Result<()>
+    where T: AsRef<Path>,
+{
+    let file = File::create(path)?;
+    write_png(BufWriter::new(file), data, color_type, width, height, metadata)
+}
+
+/// Writes 8-bit PNG image
+pub fn write_png<W>(
+    w: W,
+    data: &[u8],
+    color_type: ColorType,
+    width: u32,
+    height: u32,
+    metadata: &PngColorMetadata,
+) -> anyhow::Result<()>
+    where W: Write,
+{
+    let mut info = png::Info::with_size(width, height);
+    info.color_type = color_type;
+    info.bit_depth = BitDepth::Eight;
+
+    // https://www.w3.org/TR/png-3/#sRGB-gAMA-cHRM
+    if let Some(srgb) = metadata.srgb {
+        info.srgb = Some(srgb);
+        info.source_gamma = Some(ScaledFloat::from_scaled(45455)); // 1.0 / 2.2, scaled by 100000
+        info.source_chromaticities = Some(srgb_chromaticities());
+    }
+    else {
+        // sRGB primaries, also written without any metadata like before
+        // color spaces were tracked
+        info.source_chromaticities = Some(srgb_chromaticities());
+        info.source_gamma = metadata.gamma.map(ScaledFloat::new);
+
+        info.icc_profile = metadata.icc_profile.as_deref().map(Into::into);
+    }
+
+    let encoder = png::Encoder::with_info(w, info)?;
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(data)?;
+    writer.finish()?;
+
+    Ok(())
+}
+
+fn srgb_chromaticities() -> SourceChromaticities {
+    SourceChromaticities::new(
+        (0.31270, 0.32900),
+        (0.64000, 0.33000),
+        (0.30000, 0.60000),
+        (0.15000, 0.06000),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::graphics::buffer::buffer2d::Buffer2dRead;
+    use crate::utils::math::is_equal::IsCopyTypeEqual;
+
+    use super::codec::{rgba8_bytes, ImageCodec, PngCodec};
+    use super::*;
+
+    const EPSILON: f64 = 0.001;
+
+    fn encode<F>(width: u32, height: u32, color_type: ColorType, bit_depth: BitDepth, data: &[u8], setup: F) -> Vec<u8>
+        where F: FnOnce(&mut png::Encoder<&mut Vec<u8>>),
+    {
+        let mut output = Vec::new();
+        let mut encoder = png::Encoder::new(&mut output, width, height);
+        encoder.set_color(color_type);
+        encoder.set_depth(bit_depth);
+        setup(&mut encoder);
+
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(data).unwrap();
+        writer.finish().unwrap();
+
+        output
+    }
+
+    #[test]
+    fn decode_png_color_types() {
+        // 2-bit grayscale, rows are padded to bytes
+        let data = encode(3, 1, ColorType::Grayscale, BitDepth::Two, &[0b00_01_11_00], |_| ());
+        let buffer = decode_png(&data).unwrap();
+        assert_eq!(buffer.size(), Vec2::new(3, 1));
+        assert!(buffer.value(Vec2::new(1, 0)).is_equal(Vec4::new(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0, 1.0), EPSILON));
+        assert!(buffer.value(Vec2::new(2, 0)).is_equal(Vec4::from(1.0), EPSILON));
+        assert_eq!(buffer.color_space(), ColorSpace::Srgb);
+
+        let data = encode(1, 1, ColorType::GrayscaleAlpha, BitDepth::Eight, &[255, 51], |_| ());
+        assert!(decode_png(&data).unwrap().value(Vec2::ZERO).is_equal(Vec4::new(1.0, 1.0, 1.0, 0.2), EPSILON));
+
+        // Palette with transparency of the first entry
+        let data = encode(2, 1, ColorType::Indexed, BitDepth::Eight, &[0, 1], |encoder| {
+            encoder.set_palette(vec![255, 0, 0, 0, 0, 255]);
+            encoder.set_trns(vec![0]);
+        });
+        let buffer = decode_png(&data).unwrap();
+        assert!(buffer.value(Vec2::new(0, 0)).is_equal(Vec4::new(1.0, 0.0, 0.0, 0.0), EPSILON));
+        assert!(buffer.value(Vec2::new(1, 0)).is_equal(Vec4::new(0.0, 0.0, 1.0, 1.0), EPSILON));
+
+        // Transparent color of RGB images
+        let data = encode(2, 1, ColorType::Rgb, BitDepth::Eight, &[1, 2, 3, 4, 5, 6], |encoder| {
+            encoder.set_trns(vec![0, 4, 0, 5, 0, 6]);
+        });
+        let buffer = decode_png(&data).unwrap();
+        assert_eq!(buffer.value(Vec2::new(0, 0)).w, 1.0);
+        assert_eq!(buffer.value(Vec2::new(1, 0)).w, 0.0);
+    }
+
+    #[test]
+    fn decode_png_16_bit() {
+        let data = encode(1, 1, ColorType::Rgba, BitDepth::Sixteen, &[0x80, 0x01, 0, 1, 0xff, 0xff, 0, 0], |encoder| {
+            encoder.set_source_gamma(ScaledFloat::new(1.0));
+        });
+        let buffer = decode_png(&data).unwrap();
+        let pixel = buffer.value(Vec2::ZERO);
+
+        // Differences smaller than 8 bits are kept
+        assert_eq!(pixel.x, 32769.0 / 65535.0);
+        assert_eq!(pixel.y, 1.0 / 65535.0);
+        assert_eq!(pixel.z, 1.0);
+        assert_eq!(pixel.w, 0.0);
+        assert_eq!(buffer.color_space(), ColorSpace::Linear);
+    }
+
+    #[test]
+    fn decode_png_interlaced() {
+        // 3x3 Adam7 interlaced 8-bit grayscale, pixels are 20 * (3 * y + x) + 10
+        const DATA: [u8; 80] = [
+            0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+            0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x03, 0x08, 0x00, 0x00, 0x00, 0x01, 0x04, 0x44, 0xda,
+            0xf5, 0x00, 0x00, 0x00, 0x17, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0xe0, 0x62, 0x30, 0x62,
+            0x68, 0x5a, 0xc5, 0x20, 0xc7, 0x30, 0x8d, 0xc1, 0x2d, 0x2a, 0x0f, 0x00, 0x13, 0xb5, 0x03, 0x2b,
+            0xe6, 0x69, 0xb3, 0xbb, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+        ];
+
+        let buffer = decode_png(&DATA).unwrap();
+        assert_eq!(buffer.size(), Vec2::new(3, 3));
+
+        for y in 0..3 {
+            for x in 0..3 {
+                let value = (20 * (3 * y + x) + 10) as f32 / 255.0;
+                assert!(buffer.value(Vec2::new(x, y)).is_equal(Vec4::new(value, value, value, 1.0), EPSILON));
+            }
+        }
+    }
+
+    #[test]
+    fn png_color_metadata() {
+        let path = std::env::temp_dir().join(format!("va_png_color_metadata_{}.png", std::process::id()));
+        let data = [255, 0, 0, 255];
+
+        let metadatas = [
+            PngColorMetadata::from(ColorSpace::Srgb),
+            PngColorMetadata::from(ColorSpace::Linear),
+            PngColorMetadata::new().with_gamma(Some(0.5)).with_icc_profile(Some(vec![1, 2, 3, 4])),
+            PngColorMetadata::new(),
+        ];
+
+        for metadata in metadatas {
+            save_image(&path, &data, ColorType::Rgba, 1, 1, &metadata).unwrap();
+            let read = read_png_color_metadata(&fs::read(&path).unwrap()).unwrap();
+
+            assert_eq!(read.srgb(), metadata.srgb());
+            assert_eq!(read.icc_profile(), metadata.icc_profile());
+            assert_eq!(read.color_space(), metadata.color_space());
+            match metadata.srgb() {
+                Some(_) => assert!((read.gamma().unwrap() - 0.45455).abs() < 1.0e-5),
+                None => assert_eq!(read.gamma(), metadata.gamma()),
+            }
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn png_encode_untagged() {
+        // Buffers without a color space are written like before color spaces
+        // were tracked: only the sRGB chromaticities, no gAMA chunk
+        let buffer = Buffer2d::from_iter(Vec2::new(2, 1), [Vec4::new(1.0, 0.5, 0.0, 1.0), Vec4::from(0.25)].into_iter());
+        let expected = encode(2, 1, ColorType::Rgba, BitDepth::Eight, &rgba8_bytes(&buffer), |encoder| {
+            encoder.set_source_chromaticities(srgb_chromaticities());
+        });
+        assert_eq!(PngCodec.encode(&buffer).unwrap(), expected);
+
+        let read = read_png_color_metadata(&expected).unwrap();
+        assert_eq!(read.gamma(), None);
+        assert_eq!(read.color_space(), ColorSpace::Srgb);
+
+        // Tagged linear buffers still get gAMA
+        let buffer = buffer.with_color_space(ColorSpace::Linear);
+        let read = read_png_color_metadata(&PngCodec.encode(&buffer).unwrap()).unwrap();
+        assert_eq!(read.gamma(), Some(1.0));
+    }
+}
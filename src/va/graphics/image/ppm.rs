@@ -0,0 +1,184 @@
+use anyhow::{bail, ensure, Context};
+
+use crate::graphics::buffer::buffer2d::Buffer2d;
+use crate::graphics::color_space::ColorSpace;
+use crate::utils::math::vector::vector2::Vec2;
+use crate::utils::math::vector::vector4::Vec4;
+
+use super::codec::{bytes, checked_size, rgba8_bytes, ImageCodec};
+
+// https://netpbm.sourceforge.net/doc/ppm.html
+const PGM_MAGIC: &[u8; 2] = b"P5";
+const PPM_MAGIC: &[u8; 2] = b"P6";
+
+/// Binary Netpbm images, both PPM (P6) and PGM (P5) are decoded. Alpha is
+/// not stored.
+#[derive(Clone, Copy, Debug)]
+pub struct PpmCodec {
+    is_gray: bool,
+}
+
+impl PpmCodec {
+    /// Encodes RGB images
+    pub fn ppm() -> Self {
+        Self {
+            is_gray: false,
+        }
+    }
+
+    /// Encodes grayscale images
+    pub fn pgm() -> Self {
+        Self {
+            is_gray: true,
+        }
+    }
+}
+
+impl ImageCodec for PpmCodec {
+    fn name(&self) -> &str {
+        if self.is_gray { "PGM" } else { "PPM" }
+    }
+
+    fn extensions(&self) -> &[&str] {
+        if self.is_gray { &["pgm"] } else { &["ppm", "pnm"] }
+    }
+
+    fn is_format(&self, data: &[u8]) -> bool {
+        let magic = if self.is_gray { PGM_MAGIC } else { PPM_MAGIC };
+        data.starts_with(magic) && data.get(2).is_some_and(u8::is_ascii_whitespace)
+    }
+
+    fn decode(&self, data: &[u8]) -> anyhow::Result<Buffer2d<Vec4<f32>>> {
+        let channels = if data.starts_with(PGM_MAGIC) {
+            1
+        }
+        else if data.starts_with(PPM_MAGIC) {
+            3
+        }
+        else {
+            bail!("invalid PPM header");
+        };
+
+        let mut offset = 2;
+        let mut header = [0usize; 3];
+        for value in &mut header {
+            *value = read_number(data, &mut offset)?;
+        }
+
+        let [width, height, max_value] = header;
+        ensure!(max_value > 0 && max_value <= u16::MAX.into(), "invalid PPM max value {max_value}");
+
+        // Single whitespace before the raster
+        offset += 1;
+
+        let sample_size = if max_value < 256 { 1 } else { 2 };
+        let count = width.checked_mul(height).and_then(|count| count.checked_mul(channels * sample_size))
+            .with_context(|| format!("too big PPM image {width}x{height}"))?;
+        let raster = bytes(data, offset, count)?;
+
+        let samples: Vec<f32> = if sample_size == 1 {
+            raster.iter().map(|&val| val as f32 / max_value as f32).collect()
+        }
+        else {
+            raster.chunks_exact(2)
+                .map(|val| f32::from(u16::from_be_bytes([val[0], val[1]])) / max_value as f32)
+                .collect()
+        };
+
+        let values = samples.chunks_exact(channels).map(|val| {
+            let val = val.iter().map(|val| val.min(1.0)).collect::<Vec<f32>>();
+            if channels == 1 {
+                Vec4::new(val[0], val[0], val[0], 1.0)
+            }
+            else {
+                Vec4::new(val[0], val[1], val[2], 1.0)
+            }
+        });
+
+        Ok(Buffer2d::from_iter(Vec2::new(width, height), values).with_color_space(ColorSpace::Srgb))
+    }
+
+    /// 8-bit samples, grayscale is the Rec. 709 luma
+    fn encode(&self, buffer: &Buffer2d<Vec4<f32>>) -> anyhow::Result<Vec<u8>> {
+        let (width, height) = checked_size(buffer, u32::MAX as usize)?;
+        let magic = if self.is_gray { PGM_MAGIC } else { PPM_MAGIC };
+
+        let mut output = magic.to_vec();
+        output.extend_from_slice(format!("\n{width} {height}\n255\n").as_bytes());
+
+        for pixel in rgba8_bytes(buffer).chunks_exact(4) {
+            if self.is_gray {
+                let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(f32::from);
+                output.push((0.2126 * r + 0.7152 * g + 0.0722 * b).round() as u8);
+            }
+            else {
+                output.extend_from_slice(&pixel[..3]);
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// Skips whitespace and `#` comments, then reads a decimal number
+fn read_number(data: &[u8], offset: &mut usize) -> anyhow::Result<usize> {
+    loop {
+        match data.get(*offset) {
+            Some(byte) if byte.is_ascii_whitespace() => *offset += 1,
+            Some(b'#') => {
+                while data.get(*offset).is_some_and(|&byte| byte != b'\n') {
+                    *offset += 1;
+                }
+            }
+            _ => break,
+        }
+    }
+
+    let start = *offset;
+    while data.get(*offset).is_some_and(u8::is_ascii_digit) {
+        *offset += 1;
+    }
+
+    std::str::from_utf8(&data[start..*offset])?
+        .parse()
+        .context("invalid PPM header")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::buffer::buffer2d::Buffer2dRead;
+    use crate::graphics::image::codec::rgba8_to_rgba;
+    use crate::graphics::image::codec::tests::assert_same;
+
+    use super::*;
+
+    #[test]
+    fn ppm_round_trip() {
+        let values = (0..6).map(|i| rgba8_to_rgba([i * 40, 255 - i * 40, 7, 255]));
+        let buffer = Buffer2d::from_iter(Vec2::new(3, 2), values);
+
+        let data = PpmCodec::ppm().encode(&buffer).unwrap();
+        assert!(data.starts_with(b"P6\n3 2\n255\n"));
+        assert!(PpmCodec::ppm().is_format(&data));
+        assert!(!PpmCodec::pgm().is_format(&data));
+        assert_same(&PpmCodec::ppm().decode(&data).unwrap(), &buffer);
+
+        let gray = Buffer2d::new(Vec2::new(2, 2), rgba8_to_rgba([90, 90, 90, 255]));
+        let data = PpmCodec::pgm().encode(&gray).unwrap();
+        assert_eq!(data.len(), b"P5\n2 2\n255\n".len() + 4);
+        assert_same(&PpmCodec::pgm().decode(&data).unwrap(), &gray);
+    }
+
+    #[test]
+    fn ppm_decode_comments_16_bit() {
+        let mut data = b"P5 # comment\n2 # width\n1\n1000\n".to_vec();
+        data.extend_from_slice(&[0x01, 0xf4, 0x03, 0xe8]);
+
+        let buffer = PpmCodec::pgm().decode(&data).unwrap();
+        assert_eq!(buffer.value(Vec2::new(0, 0)), Vec4::new(0.5, 0.5, 0.5, 1.0));
+        assert_eq!(buffer.value(Vec2::new(1, 0)), Vec4::from(1.0));
+
+        data.pop();
+        assert!(PpmCodec::pgm().decode(&data).is_err());
+    }
+}
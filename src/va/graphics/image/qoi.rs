@@ -0,0 +1,260 @@
+use anyhow::{bail, ensure, Context};
+
+use crate::graphics::buffer::buffer2d::{Buffer2d, Buffer2dRead};
+use crate::graphics::color_space::ColorSpace;
+use crate::utils::math::vector::vector2::Vec2;
+use crate::utils::math::vector::vector4::Vec4;
+
+use super::codec::{checked_size, rgba8_bytes, rgba8_to_rgba, ImageCodec};
+
+// https://qoiformat.org/qoi-specification.pdf
+const MAGIC: &[u8; 4] = b"qoif";
+const HEADER_SIZE: usize = 14;
+const END_MARKER: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+/// Limit of the reference implementation
+const MAX_PIXELS: usize = 400_000_000;
+
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xc0;
+const OP_RGB: u8 = 0xfe;
+const OP_RGBA: u8 = 0xff;
+const OP_MASK: u8 = 0xc0;
+
+/// "Quite OK Image" format, lossless 8-bit RGB(A)
+pub struct QoiCodec;
+
+impl ImageCodec for QoiCodec {
+    fn name(&self) -> &str {
+        "QOI"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["qoi"]
+    }
+
+    fn is_format(&self, data: &[u8]) -> bool {
+        data.starts_with(MAGIC)
+    }
+
+    fn decode(&self, data: &[u8]) -> anyhow::Result<Buffer2d<Vec4<f32>>> {
+        ensure!(data.len() >= HEADER_SIZE + END_MARKER.len() && data.starts_with(MAGIC), "invalid QOI header");
+
+        let width = u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        let height = u32::from_be_bytes([data[8], data[9], data[10], data[11]]) as usize;
+        let channels = data[12];
+        let color_space = match data[13] {
+            0 => ColorSpace::Srgb,
+            1 => ColorSpace::Linear,
+            val => bail!("invalid QOI color space {val}"),
+        };
+
+        ensure!(channels == 3 || channels == 4, "invalid QOI channels {channels}");
+        let count = width.checked_mul(height)
+            .filter(|&count| count <= MAX_PIXELS)
+            .with_context(|| format!("too big QOI image {width}x{height}"))?;
+
+        let chunks = &data[HEADER_SIZE..data.len() - END_MARKER.len()];
+        let mut position = 0;
+        let mut next = || {
+            let value = chunks.get(position).copied().context("unexpected end of QOI data");
+            position += 1;
+            value
+        };
+
+        let mut index = [[0u8; 4]; 64];
+        let mut pixel = [0, 0, 0, 255];
+        let mut run = 0;
+
+        // The header isn't trusted, a chunk byte has up to 62 pixels
+        let mut pixels = Vec::with_capacity(count.min(chunks.len() * 62));
+        for _ in 0..count {
+            if run > 0 {
+                run -= 1;
+            }
+            else {
+                let op = next()?;
+                if op == OP_RGB {
+                    for channel in &mut pixel[..3] {
+                        *channel = next()?;
+                    }
+                }
+                else if op == OP_RGBA {
+                    for channel in &mut pixel {
+                        *channel = next()?;
+                    }
+                }
+                else {
+                    match op & OP_MASK {
+                        OP_INDEX => pixel = index[op as usize],
+                        OP_DIFF => {
+                            pixel[0] = pixel[0].wrapping_add((op >> 4) & 0x03).wrapping_sub(2);
+                            pixel[1] = pixel[1].wrapping_add((op >> 2) & 0x03).wrapping_sub(2);
+                            pixel[2] = pixel[2].wrapping_add(op & 0x03).wrapping_sub(2);
+                        }
+                        OP_LUMA => {
+                            let second = next()?;
+                            let dg = (op & 0x3f).wrapping_sub(32);
+
+                            pixel[0] = pixel[0].wrapping_add(dg).wrapping_add(second >> 4).wrapping_sub(8);
+                            pixel[1] = pixel[1].wrapping_add(dg);
+                            pixel[2] = pixel[2].wrapping_add(dg).wrapping_add(second & 0x0f).wrapping_sub(8);
+                        }
+                        _ => run = op & 0x3f,
+                    }
+                }
+
+                index[hash(pixel)] = pixel;
+            }
+
+            pixels.push(rgba8_to_rgba(pixel));
+        }
+
+        Ok(Buffer2d::from_iter(Vec2::new(width, height), pixels.into_iter()).with_color_space(color_space))
+    }
+
+    fn encode(&self, buffer: &Buffer2d<Vec4<f32>>) -> anyhow::Result<Vec<u8>> {
+        let (width, height) = checked_size(buffer, u32::MAX as usize)?;
+        ensure!(buffer.width() * buffer.height() <= MAX_PIXELS, "too big QOI image {width}x{height}");
+
+        let bytes = rgba8_bytes(buffer);
+        let is_opaque = bytes.chunks_exact(4).all(|pixel| pixel[3] == 255);
+
+        let mut output = Vec::with_capacity(HEADER_SIZE + bytes.len() + END_MARKER.len());
+        output.extend_from_slice(MAGIC);
+        output.extend_from_slice(&width.to_be_bytes());
+        output.extend_from_slice(&height.to_be_bytes());
+        output.push(if is_opaque { 3 } else { 4 });
+        // Untagged buffers are written as sRGB like before color spaces were tracked
+        output.push(match buffer.color_space_tag().unwrap_or(ColorSpace::Srgb) {
+            ColorSpace::Srgb => 0,
+            ColorSpace::Linear => 1,
+        });
+
+        let mut index = [[0u8; 4]; 64];
+        let mut previous = [0, 0, 0, 255];
+        let mut run = 0;
+
+        for pixel in bytes.chunks_exact(4) {
+            let pixel = [pixel[0], pixel[1], pixel[2], pixel[3]];
+
+            if pixel == previous {
+                run += 1;
+                if run == 62 {
+                    output.push(OP_RUN | (run - 1));
+                    run = 0;
+                }
+
+                continue;
+            }
+
+            if run > 0 {
+                output.push(OP_RUN | (run - 1));
+                run = 0;
+            }
+
+            let hash = hash(pixel);
+            if index[hash] == pixel {
+                output.push(OP_INDEX | hash as u8);
+            }
+            else if pixel[3] == previous[3] {
+                let dr = pixel[0].wrapping_sub(previous[0]) as i8;
+                let dg = pixel[1].wrapping_sub(previous[1]) as i8;
+                let db = pixel[2].wrapping_sub(previous[2]) as i8;
+                let dr_dg = dr.wrapping_sub(dg);
+                let db_dg = db.wrapping_sub(dg);
+
+                let is_small = |val: i8| (-2..=1).contains(&val);
+                if is_small(dr) && is_small(dg) && is_small(db) {
+                    output.push(OP_DIFF | ((dr + 2) as u8) << 4 | ((dg + 2) as u8) << 2 | (db + 2) as u8);
+                }
+                else if (-32..=31).contains(&dg) && (-8..=7).contains(&dr_dg) && (-8..=7).contains(&db_dg) {
+                    output.push(OP_LUMA | (dg + 32) as u8);
+                    output.push(((dr_dg + 8) as u8) << 4 | (db_dg + 8) as u8);
+                }
+                else {
+                    output.extend_from_slice(&[OP_RGB, pixel[0], pixel[1], pixel[2]]);
+                }
+            }
+            else {
+                output.extend_from_slice(&[OP_RGBA, pixel[0], pixel[1], pixel[2], pixel[3]]);
+            }
+
+            index[hash] = pixel;
+            previous = pixel;
+        }
+
+        if run > 0 {
+            output.push(OP_RUN | (run - 1));
+        }
+
+        output.extend_from_slice(&END_MARKER);
+        Ok(output)
+    }
+}
+
+fn hash(pixel: [u8; 4]) -> usize {
+    let [r, g, b, a] = pixel.map(usize::from);
+    (r * 3 + g * 5 + b * 7 + a * 11) % 64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::image::codec::tests::{assert_same, test_buffer};
+
+    use super::*;
+
+    #[test]
+    fn qoi_round_trip() {
+        let buffer = test_buffer();
+        let data = QoiCodec.encode(&buffer).unwrap();
+
+        assert!(QoiCodec.is_format(&data));
+        assert!(data.ends_with(&END_MARKER));
+
+        let decoded = QoiCodec.decode(&data).unwrap();
+        assert_same(&decoded, &buffer);
+        assert_eq!(decoded.color_space(), ColorSpace::Srgb);
+
+        // Long runs and small differences
+        let values = (0..200).map(|i| rgba8_to_rgba([(i / 70) as u8 * 3, 100 + (i / 70) as u8 * 20, 7, 255]));
+        let buffer = Buffer2d::from_iter(Vec2::new(20, 10), values);
+        let data = QoiCodec.encode(&buffer).unwrap();
+        assert!(data.len() < 40);
+        assert_same(&QoiCodec.decode(&data).unwrap(), &buffer);
+    }
+
+    #[test]
+    fn qoi_decode() {
+        // 2x2: RGB, index of the first pixel, diff, run
+        let mut data = b"qoif\0\0\0\x02\0\0\0\x02\x03\x01".to_vec();
+        data.extend_from_slice(&[OP_RGB, 10, 20, 30]);
+        data.push(OP_INDEX | hash([10, 20, 30, 255]) as u8);
+        data.push(OP_DIFF | 3 << 4 | 2 << 2 | 1);
+        data.push(OP_RUN);
+        data.extend_from_slice(&END_MARKER);
+
+        let buffer = QoiCodec.decode(&data).unwrap();
+        assert_eq!(buffer.color_space(), ColorSpace::Linear);
+        assert_eq!(rgba8_bytes(&buffer), vec![10, 20, 30, 255, 10, 20, 30, 255, 11, 20, 29, 255, 11, 20, 29, 255]);
+
+        data.truncate(data.len() - END_MARKER.len() - 1);
+        assert!(QoiCodec.decode(&data).is_err());
+    }
+
+    #[test]
+    fn qoi_decode_truncated_huge() {
+        // 20000x20000 with a single run
+        let mut data = MAGIC.to_vec();
+        data.extend_from_slice(&20000u32.to_be_bytes());
+        data.extend_from_slice(&20000u32.to_be_bytes());
+        data.extend_from_slice(&[4, 0]);
+        data.push(OP_RUN | 61);
+        data.extend_from_slice(&END_MARKER);
+
+        let error = QoiCodec.decode(&data).unwrap_err();
+        assert_eq!(error.to_string(), "unexpected end of QOI data");
+    }
+}
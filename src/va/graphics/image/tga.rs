@@ -0,0 +1,341 @@
+use anyhow::{bail, ensure};
+
+use crate::graphics::buffer::buffer2d::Buffer2d;
+use crate::graphics::color_space::ColorSpace;
+use crate::utils::math::vector::vector2::Vec2;
+use crate::utils::math::vector::vector4::Vec4;
+
+use super::codec::{bytes, checked_size, rgba8_bytes, rgba8_to_rgba, u16_le, ImageCodec};
+
+// http://www.paulbourke.net/dataformats/tga/
+const HEADER_SIZE: usize = 18;
+const FOOTER_SIGNATURE: &[u8; 18] = b"TRUEVISION-XFILE.\0";
+
+const COLOR_MAPPED: u8 = 1;
+const TRUE_COLOR: u8 = 2;
+const GRAYSCALE: u8 = 3;
+const RLE_COLOR_MAPPED: u8 = 9;
+const RLE_TRUE_COLOR: u8 = 10;
+const RLE_GRAYSCALE: u8 = 11;
+
+/// Rows are stored from the top
+const DESCRIPTOR_TOP: u8 = 0x20;
+const DESCRIPTOR_RIGHT: u8 = 0x10;
+const DESCRIPTOR_ALPHA_BITS: u8 = 0x0f;
+
+/// Truevision TGA, uncompressed and RLE compressed true color, grayscale and
+/// color mapped images
+#[derive(Clone, Copy, Debug)]
+pub struct TgaCodec {
+    rle: bool,
+}
+
+impl TgaCodec {
+    /// Encoded images are RLE compressed by default
+    pub fn with_rle(mut self, rle: bool) -> Self {
+        self.rle = rle;
+        self
+    }
+
+    pub fn rle(&self) -> bool {
+        self.rle
+    }
+}
+
+impl Default for TgaCodec {
+    fn default() -> Self {
+        Self {
+            rle: true,
+        }
+    }
+}
+
+impl ImageCodec for TgaCodec {
+    fn name(&self) -> &str {
+        "TGA"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tga"]
+    }
+
+    /// TGA has no magic bytes, files without the TGA 2.0 footer are
+    /// recognized by a valid header
+    fn is_format(&self, data: &[u8]) -> bool {
+        if data.ends_with(FOOTER_SIGNATURE) {
+            return true;
+        }
+
+        if data.len() < HEADER_SIZE {
+            return false;
+        }
+
+        let (color_map_type, image_type, depth) = (data[1], data[2], data[16]);
+        let is_color_mapped = matches!(image_type, COLOR_MAPPED | RLE_COLOR_MAPPED);
+        let width = u16::from_le_bytes([data[12], data[13]]);
+        let height = u16::from_le_bytes([data[14], data[15]]);
+
+        matches!(image_type, COLOR_MAPPED | TRUE_COLOR | GRAYSCALE | RLE_COLOR_MAPPED | RLE_TRUE_COLOR | RLE_GRAYSCALE)
+            && (color_map_type == 1) == is_color_mapped
+            && matches!(depth, 8 | 15 | 16 | 24 | 32)
+            && width > 0
+            && height > 0
+    }
+
+    fn decode(&self, data: &[u8]) -> anyhow::Result<Buffer2d<Vec4<f32>>> {
+        let header = bytes(data, 0, HEADER_SIZE)?;
+        let id_length = usize::from(header[0]);
+        let (color_map_type, image_type) = (header[1], header[2]);
+        let color_map_first = usize::from(u16_le(header, 3)?);
+        let color_map_length = usize::from(u16_le(header, 5)?);
+        let color_map_depth = header[7];
+        let width = usize::from(u16_le(header, 12)?);
+        let height = usize::from(u16_le(header, 14)?);
+        let depth = header[16];
+        let descriptor = header[17];
+
+        let has_alpha = descriptor & DESCRIPTOR_ALPHA_BITS != 0;
+        let mut offset = HEADER_SIZE + id_length;
+
+        let color_map = if color_map_type == 1 {
+            let entry_size = pixel_size(color_map_depth)?;
+            let entries = bytes(data, offset, color_map_length * entry_size)?;
+            offset += entries.len();
+
+            entries.chunks_exact(entry_size)
+                .map(|entry| true_color(entry, color_map_depth, has_alpha))
+                .collect()
+        }
+        else {
+            Vec::new()
+        };
+
+        let read_pixel = |bytes: &[u8]| -> anyhow::Result<[u8; 4]> {
+            match image_type {
+                COLOR_MAPPED | RLE_COLOR_MAPPED => {
+                    let index = match depth {
+                        8 => usize::from(bytes[0]),
+                        16 => usize::from(u16::from_le_bytes([bytes[0], bytes[1]])),
+                        _ => bail!("unsupported TGA color map index depth {depth}"),
+                    };
+
+                    index.checked_sub(color_map_first)
+                        .and_then(|index| color_map.get(index).copied())
+                        .ok_or_else(|| anyhow::anyhow!("TGA color map index {index} out of range"))
+                }
+                GRAYSCALE | RLE_GRAYSCALE => {
+                    ensure!(depth == 8 || depth == 16, "unsupported TGA grayscale depth {depth}");
+                    let alpha = if depth == 16 && has_alpha { bytes[1] } else { 255 };
+                    Ok([bytes[0], bytes[0], bytes[0], alpha])
+                }
+                _ => Ok(true_color(bytes, depth, has_alpha)),
+            }
+        };
+
+        ensure!(
+            matches!(image_type, COLOR_MAPPED | TRUE_COLOR | GRAYSCALE | RLE_COLOR_MAPPED | RLE_TRUE_COLOR | RLE_GRAYSCALE),
+            "unsupported TGA image type {image_type}",
+        );
+
+        let size = pixel_size(depth)?;
+        let count = width * height;
+
+        let pixels = if matches!(image_type, RLE_COLOR_MAPPED | RLE_TRUE_COLOR | RLE_GRAYSCALE) {
+            // The header isn't trusted, a packet of `1 + size` bytes has up
+            // to 128 pixels
+            let max_count = data.len().saturating_sub(offset) / (1 + size) * 128;
+            let mut pixels = Vec::with_capacity(count.min(max_count));
+
+            while pixels.len() < count {
+                let packet = bytes(data, offset, 1)?[0];
+                let length = usize::from(packet & 0x7f) + 1;
+                offset += 1;
+
+                if packet & 0x80 != 0 {
+                    let pixel = read_pixel(bytes(data, offset, size)?)?;
+                    offset += size;
+                    pixels.extend(std::iter::repeat_n(pixel, length));
+                }
+                else {
+                    for pixel in bytes(data, offset, length * size)?.chunks_exact(size) {
+                        pixels.push(read_pixel(pixel)?);
+                    }
+                    offset += length * size;
+                }
+            }
+
+            // Packets may cross rows, the last one may be longer
+            pixels.truncate(count);
+            pixels
+        }
+        else {
+            bytes(data, offset, count * size)?
+                .chunks_exact(size)
+                .map(read_pixel)
+                .collect::<anyhow::Result<Vec<_>>>()?
+        };
+
+        let is_top = descriptor & DESCRIPTOR_TOP != 0;
+        let is_right = descriptor & DESCRIPTOR_RIGHT != 0;
+        let values = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let x = if is_right { width - 1 - x } else { x };
+                let y = if is_top { y } else { height - 1 - y };
+
+                rgba8_to_rgba(pixels[y * width + x])
+            });
+
+        Ok(Buffer2d::from_iter(Vec2::new(width, height), values).with_color_space(ColorSpace::Srgb))
+    }
+
+    /// 32-bit BGRA from the top-left corner
+    fn encode(&self, buffer: &Buffer2d<Vec4<f32>>) -> anyhow::Result<Vec<u8>> {
+        let (width, height) = checked_size(buffer, u16::MAX as usize)?;
+
+        let pixels: Vec<[u8; 4]> = rgba8_bytes(buffer)
+            .chunks_exact(4)
+            .map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+            .collect();
+
+        let mut output = Vec::with_capacity(HEADER_SIZE + pixels.len() * 4 + 26);
+        output.extend_from_slice(&[0, 0, if self.rle { RLE_TRUE_COLOR } else { TRUE_COLOR }]);
+        output.extend_from_slice(&[0; 5]);
+        output.extend_from_slice(&[0; 4]);
+        output.extend_from_slice(&(width as u16).to_le_bytes());
+        output.extend_from_slice(&(height as u16).to_le_bytes());
+        output.extend_from_slice(&[32, DESCRIPTOR_TOP | 8]);
+
+        if self.rle {
+            // Packets don't cross rows, as required by TGA 2.0
+            for row in pixels.chunks(width.max(1) as usize) {
+                encode_rle_row(row, &mut output);
+            }
+        }
+        else {
+            pixels.iter().for_each(|pixel| output.extend_from_slice(pixel));
+        }
+
+        // Extension and developer area offsets
+        output.extend_from_slice(&[0; 8]);
+        output.extend_from_slice(FOOTER_SIGNATURE);
+
+        Ok(output)
+    }
+}
+
+fn encode_rle_row(row: &[[u8; 4]], output: &mut Vec<u8>) {
+    let mut i = 0;
+    while i < row.len() {
+        let run = row[i..].iter().take(128).take_while(|&&pixel| pixel == row[i]).count();
+        if run > 1 {
+            output.push(0x80 | (run - 1) as u8);
+            output.extend_from_slice(&row[i]);
+            i += run;
+            continue;
+        }
+
+        // Raw packet until the next run
+        let start = i;
+        while i < row.len() && i - start < 128 && !(i + 1 < row.len() && row[i] == row[i + 1]) {
+            i += 1;
+        }
+
+        output.push((i - start - 1) as u8);
+        row[start..i].iter().for_each(|pixel| output.extend_from_slice(pixel));
+    }
+}
+
+fn pixel_size(depth: u8) -> anyhow::Result<usize> {
+    match depth {
+        8 => Ok(1),
+        15 | 16 => Ok(2),
+        24 => Ok(3),
+        32 => Ok(4),
+        _ => bail!("unsupported TGA pixel depth {depth}"),
+    }
+}
+
+/// BGR(A) or 16-bit ARRRRRGG GGGBBBBB pixel as RGBA
+fn true_color(bytes: &[u8], depth: u8, has_alpha: bool) -> [u8; 4] {
+    match depth {
+        15 | 16 => {
+            let value = u16::from_le_bytes([bytes[0], bytes[1]]);
+            let channel = |shift: u16| {
+                let value = ((value >> shift) & 0x1f) as u8;
+                (value << 3) | (value >> 2)
+            };
+            let alpha = if depth == 16 && has_alpha && value & 0x8000 == 0 { 0 } else { 255 };
+
+            [channel(10), channel(5), channel(0), alpha]
+        }
+        24 => [bytes[2], bytes[1], bytes[0], 255],
+        32 => [bytes[2], bytes[1], bytes[0], if has_alpha { bytes[3] } else { 255 }],
+        _ => [bytes[0], bytes[0], bytes[0], 255],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::image::codec::tests::{assert_same, test_buffer};
+
+    use super::*;
+
+    #[test]
+    fn tga_round_trip() {
+        let buffer = test_buffer();
+
+        for codec in [TgaCodec::default(), TgaCodec::default().with_rle(false)] {
+            let data = codec.encode(&buffer).unwrap();
+
+            assert!(codec.is_format(&data));
+            assert_same(&codec.decode(&data).unwrap(), &buffer);
+        }
+
+        // Runs are compressed
+        let buffer = Buffer2d::new(Vec2::new(300, 2), Vec4::from(1.0));
+        let data = TgaCodec::default().encode(&buffer).unwrap();
+        assert!(data.len() < 100);
+        assert_same(&TgaCodec::default().decode(&data).unwrap(), &buffer);
+    }
+
+    #[test]
+    fn tga_decode_bottom_up_rle_gray() {
+        // 2x2 RLE grayscale from the bottom-left corner, the run crosses rows
+        let mut data = vec![0, 0, RLE_GRAYSCALE, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 2, 0, 8, 0];
+        data.extend_from_slice(&[0x82, 50, 0x00, 200]);
+
+        let codec = TgaCodec::default();
+        assert!(codec.is_format(&data));
+
+        let buffer = codec.decode(&data).unwrap();
+        let gray: Vec<u8> = rgba8_bytes(&buffer).chunks_exact(4).map(|pixel| pixel[0]).collect();
+        assert_eq!(gray, vec![50, 200, 50, 50]);
+    }
+
+    #[test]
+    fn tga_decode_color_mapped() {
+        // 2x1 with a 24-bit color map starting from index 1
+        let mut data = vec![0, 1, COLOR_MAPPED, 1, 0, 2, 0, 24, 0, 0, 0, 0, 2, 0, 1, 0, 8, DESCRIPTOR_TOP];
+        data.extend_from_slice(&[0, 0, 255, 255, 0, 0]);
+        data.extend_from_slice(&[2, 1]);
+
+        let buffer = TgaCodec::default().decode(&data).unwrap();
+        assert_eq!(rgba8_bytes(&buffer), vec![0, 0, 255, 255, 255, 0, 0, 255]);
+
+        data[HEADER_SIZE + 6] = 0;
+        assert!(TgaCodec::default().decode(&data).is_err());
+    }
+
+    #[test]
+    fn tga_decode_truncated_huge() {
+        // 65535x65535 without pixel data
+        for image_type in [TRUE_COLOR, RLE_TRUE_COLOR] {
+            let data = [0, 0, image_type, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 32, 8];
+            assert!(TgaCodec::default().decode(&data).is_err());
+
+            let mut data = data.to_vec();
+            data.extend_from_slice(&[0xff, 1, 2, 3, 4]);
+            assert!(TgaCodec::default().decode(&data).is_err());
+        }
+    }
+}
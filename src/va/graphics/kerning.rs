@@ -0,0 +1,60 @@
+use ttf_parser::gpos::{PairAdjustment, PositioningSubtable};
+use ttf_parser::{Face, GlyphId, Tag};
+
+/// Returns the horizontal kerning of the glyph pair in font units.
+///
+/// GPOS pair adjustments are preferred, the legacy `kern` table is used only
+/// if GPOS has no value for the pair.
+pub fn glyph_kerning(face: &Face, left: GlyphId, right: GlyphId) -> Option<i16> {
+    gpos_kerning(face, left, right).or_else(|| kern_kerning(face, left, right))
+}
+
+// https://docs.microsoft.com/en-us/typography/opentype/spec/gpos#lookup-type-2-pair-adjustment-positioning-subtable
+fn gpos_kerning(face: &Face, left: GlyphId, right: GlyphId) -> Option<i16> {
+    let table = face.tables().gpos?;
+    let feature = table.features.find(Tag::from_bytes(b"kern"))?;
+
+    for lookup_index in feature.lookup_indices {
+        let lookup = match table.lookups.get(lookup_index) {
+            Some(val) => val,
+            None => continue,
+        };
+
+        for subtable in lookup.subtables.into_iter::<PositioningSubtable>() {
+            if let PositioningSubtable::Pair(adjustment) = subtable {
+                if let Some(val) = pair_adjustment(&adjustment, left, right) {
+                    return Some(val);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn pair_adjustment(adjustment: &PairAdjustment, left: GlyphId, right: GlyphId) -> Option<i16> {
+    match adjustment {
+        PairAdjustment::Format1 { coverage, sets } => {
+            let index = coverage.get(left)?;
+            let (record, _) = sets.get(index)?.get(right)?;
+            Some(record.x_advance)
+        }
+        PairAdjustment::Format2 { coverage, classes, matrix } => {
+            if !coverage.contains(left) {
+                return None;
+            }
+
+            let (record, _) = matrix.get((classes.0.get(left), classes.1.get(right)))?;
+            Some(record.x_advance)
+        }
+    }
+}
+
+// https://docs.microsoft.com/en-us/typography/opentype/spec/kern
+fn kern_kerning(face: &Face, left: GlyphId, right: GlyphId) -> Option<i16> {
+    let table = face.tables().kern?;
+
+    table.subtables.into_iter()
+        .filter(|subtable| subtable.horizontal && !subtable.variable && !subtable.has_cross_stream)
+        .find_map(|subtable| subtable.glyphs_kerning(left, right))
+}
@@ -1,16 +1,32 @@
+pub mod blend;
+pub mod char_set;
+pub mod color_space;
+pub mod coverage_accumulator;
+pub mod distance_field;
+pub mod edge_table;
 pub mod font;
+pub mod font_debug;
+pub mod font_registry;
 pub mod framerate_counter;
+pub mod glyph_cache;
 pub mod glyph_render;
 pub mod image;
+pub mod kerning;
 pub mod layer_render_data_handle;
 pub mod layer_render_data;
 pub mod buffer;
 pub mod mesh;
+pub mod path;
+pub mod pixel_format;
 pub mod rasterizate;
 pub mod render_data;
 pub mod render;
 pub mod render_state;
 pub mod shaders;
+#[cfg(test)]
+pub(crate) mod snapshot;
+pub mod stroke;
+pub mod text_layout;
 pub mod window_graphics;
 pub mod window_render;
 
@@ -0,0 +1,442 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+use std::mem;
+
+use ttf_parser::OutlineBuilder;
+
+use crate::utils::cast::Cast;
+use crate::utils::math::geometry::curve;
+use crate::utils::math::matrix::matrix3x3::Mat3x3;
+use crate::utils::math::vector::vector2::Vec2;
+
+/// Max distance between a flattened curve and the curve in pixels
+const FLATTEN_TOLERANCE: f64 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathSegment {
+    MoveTo(Vec2<f32>),
+    LineTo(Vec2<f32>),
+    QuadTo(Vec2<f32>, Vec2<f32>),
+    CubicTo(Vec2<f32>, Vec2<f32>, Vec2<f32>),
+    Close,
+}
+
+/// Builds a [`Path`] the same way as `ttf_parser::OutlineBuilder` builds
+/// glyph outlines.
+///
+/// Drawing commands without a current point start a new contour at their
+/// first point.
+#[derive(Clone, Debug, Default)]
+pub struct PathBuilder {
+    segments: Vec<PathSegment>,
+    start: Option<Vec2<f32>>,
+    current: Option<Vec2<f32>>,
+}
+
+impl PathBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn move_to(mut self, point: Vec2<f32>) -> Self {
+        self.segments.push(PathSegment::MoveTo(point));
+        self.start = Some(point);
+        self.current = Some(point);
+        self
+    }
+
+    pub fn line_to(mut self, point: Vec2<f32>) -> Self {
+        if self.current.is_none() {
+            return self.move_to(point);
+        }
+
+        self.segments.push(PathSegment::LineTo(point));
+        self.current = Some(point);
+        self
+    }
+
+    pub fn quad_to(mut self, control: Vec2<f32>, point: Vec2<f32>) -> Self {
+        if self.current.is_none() {
+            self = self.move_to(control);
+        }
+
+        self.segments.push(PathSegment::QuadTo(control, point));
+        self.current = Some(point);
+        self
+    }
+
+    pub fn cubic_to(mut self, control1: Vec2<f32>, control2: Vec2<f32>, point: Vec2<f32>) -> Self {
+        if self.current.is_none() {
+            self = self.move_to(control1);
+        }
+
+        self.segments.push(PathSegment::CubicTo(control1, control2, point));
+        self.current = Some(point);
+        self
+    }
+
+    /// Rounds the corner at `corner` between the lines to `corner` and from
+    /// `corner` to `point` with a circular arc, like `arcTo` of the HTML
+    /// canvas. The path continues from the arc end, not from `point`.
+    // https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-arcto
+    pub fn arc_to(self, corner: Vec2<f32>, point: Vec2<f32>, radius: f32) -> Self {
+        let current = match self.current {
+            Some(val) => val,
+            None => return self.move_to(corner),
+        };
+
+        let d0 = current - corner;
+        let d1 = point - corner;
+        if radius.is_nan() || radius <= 0.0 || d0.lenght() < f32::EPSILON || d1.lenght() < f32::EPSILON {
+            return self.line_to(corner);
+        }
+
+        let d0 = d0.normalize();
+        let d1 = d1.normalize();
+
+        // Angle between the lines
+        let angle = d0.dot(d1).clamp(-1.0, 1.0).acos();
+        if angle < 1.0e-4 || PI - angle < 1.0e-4 {
+            return self.line_to(corner);
+        }
+
+        let tangent = radius / (angle * 0.5).tan();
+        let center = corner + (d0 + d1).normalize() * (radius / (angle * 0.5).sin());
+
+        let p0 = corner + d0 * tangent;
+        let p1 = corner + d1 * tangent;
+
+        let start_angle = (p0.y - center.y).atan2(p0.x - center.x);
+        let end_angle = (p1.y - center.y).atan2(p1.x - center.x);
+
+        // The arc is shorter than a half turn
+        let mut sweep = end_angle - start_angle;
+        if sweep > PI {
+            sweep -= 2.0 * PI;
+        }
+        else if sweep < -PI {
+            sweep += 2.0 * PI;
+        }
+
+        self.line_to(p0).arc(center, radius, start_angle, sweep)
+    }
+
+    pub fn close(mut self) -> Self {
+        if self.current.is_some() {
+            self.segments.push(PathSegment::Close);
+            self.current = self.start;
+        }
+
+        self
+    }
+
+    pub fn build(self) -> Path {
+        Path {
+            segments: self.segments,
+        }
+    }
+
+    /// Appends the arc from the current point as cubic curves, angles are
+    /// in radians
+    fn arc(mut self, center: Vec2<f32>, radius: f32, start_angle: f32, sweep: f32) -> Self {
+        let count = (sweep.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+        let step = sweep / count as f32;
+
+        // https://pomax.github.io/bezierinfo/#circles_cubic
+        let k = 4.0 / 3.0 * (step / 4.0).tan() * radius;
+
+        for i in 0..count {
+            let a0 = start_angle + step * i as f32;
+            let a1 = a0 + step;
+
+            let (sin0, cos0) = a0.sin_cos();
+            let (sin1, cos1) = a1.sin_cos();
+
+            let p1 = center + Vec2::new(cos1, sin1) * radius;
+            let control1 = center + Vec2::new(cos0, sin0) * radius + Vec2::new(-sin0, cos0) * k;
+            let control2 = p1 - Vec2::new(-sin1, cos1) * k;
+
+            self = self.cubic_to(control1, control2, p1);
+        }
+
+        self
+    }
+}
+
+/// Glyph outlines are built into a path by `Face::outline_glyph`, in font
+/// units with Y axis directed up
+impl OutlineBuilder for PathBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        *self = mem::take(self).move_to(Vec2::new(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        *self = mem::take(self).line_to(Vec2::new(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        *self = mem::take(self).quad_to(Vec2::new(x1, y1), Vec2::new(x, y));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        *self = mem::take(self).cubic_to(Vec2::new(x1, y1), Vec2::new(x2, y2), Vec2::new(x, y));
+    }
+
+    fn close(&mut self) {
+        *self = mem::take(self).close();
+    }
+}
+
+/// Flattened contour of a path.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Contour {
+    points: Vec<Vec2<f32>>,
+    closed: bool,
+}
+
+impl Contour {
+    pub fn points(&self) -> &[Vec2<f32>] {
+        &self.points
+    }
+
+    /// Closed contours are connected from the last point to the first one
+    /// when stroked
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    fn push(&mut self, point: Vec2<f32>) {
+        if self.points.last() != Some(&point) {
+            self.points.push(point);
+        }
+    }
+}
+
+impl AsRef<[Vec2<f32>]> for Contour {
+    fn as_ref(&self) -> &[Vec2<f32>] {
+        &self.points
+    }
+}
+
+/// Vector shape made of lines and curves, built by [`PathBuilder`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+}
+
+impl Path {
+    pub fn builder() -> PathBuilder {
+        PathBuilder::new()
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn transform(&self, transform: &Mat3x3<f64>) -> Path {
+        let do_transform = |point: Vec2<f32>| -> Vec2<f32> {
+            transform.transform_point(point.into()).cast()
+        };
+
+        let segments = self.segments.iter()
+            .map(|&segment| match segment {
+                PathSegment::MoveTo(p) => PathSegment::MoveTo(do_transform(p)),
+                PathSegment::LineTo(p) => PathSegment::LineTo(do_transform(p)),
+                PathSegment::QuadTo(p1, p) => PathSegment::QuadTo(do_transform(p1), do_transform(p)),
+                PathSegment::CubicTo(p1, p2, p) => {
+                    PathSegment::CubicTo(do_transform(p1), do_transform(p2), do_transform(p))
+                }
+                PathSegment::Close => PathSegment::Close,
+            })
+            .collect();
+
+        Path { segments }
+    }
+
+    /// Approximates curves with lines. Consecutive equal points are merged,
+    /// so zero length contours have a single point.
+    pub fn flatten(&self) -> Vec<Contour> {
+        let mut contours = Vec::new();
+        let mut contour = Contour::default();
+        let mut current = Vec2::ZERO;
+
+        // A contour without drawing commands (e.g. only move_to) is skipped
+        let mut is_drawn = false;
+
+        for &segment in &self.segments {
+            if !matches!(segment, PathSegment::MoveTo(_)) && contour.points.is_empty() {
+                contour.push(current);
+            }
+
+            match segment {
+                PathSegment::MoveTo(p) => {
+                    if is_drawn {
+                        contours.push(std::mem::take(&mut contour));
+                    }
+
+                    contour = Contour::default();
+                    contour.push(p);
+                    current = p;
+                    is_drawn = false;
+                }
+                PathSegment::LineTo(p) => {
+                    contour.push(p);
+                    current = p;
+                    is_drawn = true;
+                }
+                PathSegment::QuadTo(p1, p2) => {
+                    let (p0, p1, p2): (Vec2<f64>, Vec2<f64>, Vec2<f64>) = (current.cast(), p1.cast(), p2.cast());
+                    let t_values = curve::subdivide_quad_curve(p0, p1, p2);
+
+                    // Skip 0.0 and 1.0, the end point is exact
+                    for &t in &t_values[1..t_values.len() - 1] {
+                        contour.push(curve::get_quad_curve_point(p0, p1, p2, t).cast());
+                    }
+
+                    current = p2.cast();
+                    contour.push(current);
+                    is_drawn = true;
+                }
+                PathSegment::CubicTo(p1, p2, p3) => {
+                    let (p0, p1, p2, p3): (Vec2<f64>, Vec2<f64>, Vec2<f64>, Vec2<f64>) =
+                        (current.cast(), p1.cast(), p2.cast(), p3.cast());
+                    let t_values = curve::subdivide_cube_curve_with_tolerance(p0, p1, p2, p3, FLATTEN_TOLERANCE);
+
+                    // Skip 0.0 and 1.0, the end point is exact
+                    for &t in &t_values[1..t_values.len() - 1] {
+                        contour.push(curve::get_cube_curve_point(p0, p1, p2, p3, t).cast());
+                    }
+
+                    current = p3.cast();
+                    contour.push(current);
+                    is_drawn = true;
+                }
+                PathSegment::Close => {
+                    // The closing line is implicit
+                    if contour.points.len() > 1 && contour.points.first() == contour.points.last() {
+                        contour.points.pop();
+                    }
+
+                    // Next segments without move_to continue from the start
+                    current = contour.points[0];
+                    contour.closed = true;
+                    contours.push(std::mem::take(&mut contour));
+                    is_drawn = false;
+                }
+            }
+        }
+
+        if is_drawn {
+            contours.push(contour);
+        }
+
+        contours
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ttf_parser::Face;
+
+    use crate::graphics::font_registry::tests::test_font;
+    use crate::utils::math::is_equal::IsCopyTypeEqual;
+
+    use super::*;
+
+    const EPSILON: f64 = 0.001;
+
+    #[test]
+    fn path_outline_builder() {
+        let data = test_font("Test Sans", "Regular", &['A']);
+        let face = Face::from_slice(&data, 0).unwrap();
+
+        let mut builder = Path::builder();
+        face.outline_glyph(face.glyph_index('A').unwrap(), &mut builder).unwrap();
+
+        let contours = builder.build().flatten();
+        assert_eq!(contours.len(), 1);
+        assert!(contours[0].is_closed());
+        assert_eq!(contours[0].points().len(), 4);
+        assert!(contours[0].points().contains(&Vec2::new(450.0, 700.0)));
+
+        // Trait methods match the builder ones
+        let mut builder = PathBuilder::new();
+        OutlineBuilder::move_to(&mut builder, 0.0, 0.0);
+        OutlineBuilder::quad_to(&mut builder, 1.0, 2.0, 3.0, 4.0);
+        OutlineBuilder::curve_to(&mut builder, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0);
+        OutlineBuilder::line_to(&mut builder, 0.0, 1.0);
+        OutlineBuilder::close(&mut builder);
+
+        let expected = Path::builder()
+            .move_to(Vec2::new(0.0, 0.0))
+            .quad_to(Vec2::new(1.0, 2.0), Vec2::new(3.0, 4.0))
+            .cubic_to(Vec2::new(5.0, 6.0), Vec2::new(7.0, 8.0), Vec2::new(9.0, 10.0))
+            .line_to(Vec2::new(0.0, 1.0))
+            .close()
+            .build();
+        assert_eq!(builder.build(), expected);
+    }
+
+    #[test]
+    fn path_flatten_lines() {
+        let path = Path::builder()
+            .move_to(Vec2::new(0.0, 0.0))
+            .line_to(Vec2::new(10.0, 0.0))
+            .line_to(Vec2::new(10.0, 0.0))
+            .line_to(Vec2::new(10.0, 10.0))
+            .close()
+            .move_to(Vec2::new(20.0, 20.0))
+            .line_to(Vec2::new(30.0, 20.0))
+            .build();
+
+        let contours = path.flatten();
+        assert_eq!(contours.len(), 2);
+
+        assert!(contours[0].is_closed());
+        assert_eq!(contours[0].points(), &[Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(10.0, 10.0)]);
+
+        assert!(!contours[1].is_closed());
+        assert_eq!(contours[1].points().len(), 2);
+    }
+
+    #[test]
+    fn path_flatten_curve_ends() {
+        let path = Path::builder()
+            .move_to(Vec2::new(0.0, 0.0))
+            .quad_to(Vec2::new(10.0, 20.0), Vec2::new(20.0, 0.0))
+            .cubic_to(Vec2::new(30.0, 10.0), Vec2::new(40.0, -10.0), Vec2::new(50.0, 0.0))
+            .build();
+
+        let contours = path.flatten();
+        assert_eq!(contours.len(), 1);
+
+        let points = contours[0].points();
+        assert!(points.len() > 4);
+        assert!(points.contains(&Vec2::new(20.0, 0.0)));
+        assert_eq!(points.last(), Some(&Vec2::new(50.0, 0.0)));
+    }
+
+    #[test]
+    fn path_arc_to() {
+        let radius = 5.0;
+        let path = Path::builder()
+            .move_to(Vec2::new(0.0, 0.0))
+            .arc_to(Vec2::new(20.0, 0.0), Vec2::new(20.0, 20.0), radius)
+            .build();
+
+        let contours = path.flatten();
+        let points = contours[0].points();
+
+        assert_eq!(points[1], Vec2::new(15.0, 0.0));
+        assert!(points.last().unwrap().is_equal(Vec2::new(20.0, 5.0), EPSILON));
+
+        // All arc points are on the circle
+        let center = Vec2::new(15.0, 5.0);
+        for point in &points[1..] {
+            assert!(f64::from((*point - center).lenght()).is_equal(radius as f64, 0.01));
+        }
+    }
+}
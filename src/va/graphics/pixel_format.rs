@@ -0,0 +1,354 @@
+use std::fmt::Debug;
+
+use vulkano::format::Format;
+
+use crate::utils::math::simd::FloatLanes;
+use crate::utils::math::vector::vector4::Vec4;
+
+use super::buffer::buffer2d::{Buffer2d, Buffer2dRead};
+use super::color_space::ColorSpace;
+
+/// Pixel type of a [`Buffer2d`] which can be uploaded into a GPU image.
+///
+/// Conversions go through straight alpha RGBA colors, missing channels read
+/// as 0 and missing alpha as 1 (like Vulkan samplers do). Single channel
+/// formats store the red channel.
+pub trait PixelFormat: Copy + Debug + Default + PartialEq + 'static {
+    /// Matching format of the GPU image
+    const FORMAT: Format;
+
+    /// Format decoding sRGB values into linear ones on sampling
+    const SRGB_FORMAT: Option<Format> = None;
+
+    fn to_rgba(self) -> Vec4<f32>;
+    fn from_rgba(color: Vec4<f32>) -> Self;
+
+    /// Appends the pixel in the layout of [`Self::FORMAT`]
+    fn write_bytes(self, output: &mut Vec<u8>);
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct R8(pub u8);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rg8(pub [u8; 2]);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rgba8(pub [u8; 4]);
+
+/// Bits of IEEE 754 half precision floats
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rgba16F(pub [u16; 4]);
+
+pub type Rgba32F = Vec4<f32>;
+
+impl PixelFormat for R8 {
+    const FORMAT: Format = Format::R8_UNORM;
+    const SRGB_FORMAT: Option<Format> = Some(Format::R8_SRGB);
+
+    fn to_rgba(self) -> Vec4<f32> {
+        Vec4::new(unorm_to_f32(self.0), 0.0, 0.0, 1.0)
+    }
+
+    fn from_rgba(color: Vec4<f32>) -> Self {
+        Self(f32_to_unorm(color.x))
+    }
+
+    fn write_bytes(self, output: &mut Vec<u8>) {
+        output.push(self.0);
+    }
+}
+
+impl PixelFormat for Rg8 {
+    const FORMAT: Format = Format::R8G8_UNORM;
+    const SRGB_FORMAT: Option<Format> = Some(Format::R8G8_SRGB);
+
+    fn to_rgba(self) -> Vec4<f32> {
+        Vec4::new(unorm_to_f32(self.0[0]), unorm_to_f32(self.0[1]), 0.0, 1.0)
+    }
+
+    fn from_rgba(color: Vec4<f32>) -> Self {
+        Self([f32_to_unorm(color.x), f32_to_unorm(color.y)])
+    }
+
+    fn write_bytes(self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&self.0);
+    }
+}
+
+impl PixelFormat for Rgba8 {
+    const FORMAT: Format = Format::R8G8B8A8_UNORM;
+    const SRGB_FORMAT: Option<Format> = Some(Format::R8G8B8A8_SRGB);
+
+    fn to_rgba(self) -> Vec4<f32> {
+        self.0.into_iter().map(unorm_to_f32).collect()
+    }
+
+    fn from_rgba(color: Vec4<f32>) -> Self {
+        Self(f32::to_unorm8(color.into()))
+    }
+
+    fn write_bytes(self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&self.0);
+    }
+}
+
+impl PixelFormat for Rgba16F {
+    const FORMAT: Format = Format::R16G16B16A16_SFLOAT;
+
+    fn to_rgba(self) -> Vec4<f32> {
+        self.0.into_iter().map(f16_to_f32).collect()
+    }
+
+    fn from_rgba(color: Vec4<f32>) -> Self {
+        Self([
+            f32_to_f16(color.x),
+            f32_to_f16(color.y),
+            f32_to_f16(color.z),
+            f32_to_f16(color.w),
+        ])
+    }
+
+    fn write_bytes(self, output: &mut Vec<u8>) {
+        for channel in self.0 {
+            output.extend_from_slice(&channel.to_ne_bytes());
+        }
+    }
+}
+
+/// Single channel float, e.g. a coverage buffer
+impl PixelFormat for f32 {
+    const FORMAT: Format = Format::R32_SFLOAT;
+
+    fn to_rgba(self) -> Vec4<f32> {
+        Vec4::new(self, 0.0, 0.0, 1.0)
+    }
+
+    fn from_rgba(color: Vec4<f32>) -> Self {
+        color.x
+    }
+
+    fn write_bytes(self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&self.to_ne_bytes());
+    }
+}
+
+impl PixelFormat for Rgba32F {
+    const FORMAT: Format = Format::R32G32B32A32_SFLOAT;
+
+    fn to_rgba(self) -> Vec4<f32> {
+        self
+    }
+
+    fn from_rgba(color: Vec4<f32>) -> Self {
+        color
+    }
+
+    fn write_bytes(self, output: &mut Vec<u8>) {
+        for i in 0..4 {
+            output.extend_from_slice(&self[i].to_ne_bytes());
+        }
+    }
+}
+
+impl<P> Buffer2d<P>
+    where P: PixelFormat,
+{
+    pub fn convert<Q>(&self) -> Buffer2d<Q>
+        where Q: PixelFormat,
+    {
+        Buffer2d::from_iter(self.size(), self.iter().map(|&pixel| Q::from_rgba(pixel.to_rgba())))
+            .with_color_space_tag(self.color_space_tag())
+    }
+
+    /// All channels are 8-bit values, so conversion into [`Rgba8`] keeps
+    /// them exactly
+    pub fn fits_rgba8(&self) -> bool {
+        self.iter().all(|&pixel| {
+            pixel.to_rgba().into_iter().all(|value| {
+                let value = value * 255.0;
+                (value - value.round()).abs() < 1.0e-3
+            })
+        })
+    }
+
+    pub fn to_color_space(&self, color_space: ColorSpace) -> Self {
+        let from = self.color_space();
+        if from == color_space {
+            return self.clone();
+        }
+
+        Buffer2d::from_iter(self.size(), self.iter().map(|&pixel| P::from_rgba(from.convert(pixel.to_rgba(), color_space))))
+            .with_color_space(color_space)
+    }
+
+    /// Format and data of a GPU image sampled as linear colors. sRGB buffers
+    /// without an sRGB format of the pixels are converted into linear space.
+    ///
+    /// Untagged [`Rgba32F`] buffers keep the upload they had before color
+    /// spaces were tracked: 8-bit values sampled as sRGB.
+    pub fn to_image_data(&self) -> (Format, Vec<u8>) {
+        if self.color_space_tag().is_none() && P::FORMAT == Rgba32F::FORMAT {
+            return (Format::R8G8B8A8_SRGB, self.convert::<Rgba8>().to_bytes());
+        }
+
+        match (self.color_space(), P::SRGB_FORMAT) {
+            (ColorSpace::Linear, _) => (P::FORMAT, self.to_bytes()),
+            (ColorSpace::Srgb, Some(format)) => (format, self.to_bytes()),
+            (ColorSpace::Srgb, None) => (P::FORMAT, self.to_color_space(ColorSpace::Linear).to_bytes()),
+        }
+    }
+
+    /// Pixels in the layout of `P::FORMAT`, row by row
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut output = Vec::with_capacity(self.buffer().len() * std::mem::size_of::<P>());
+        self.iter().for_each(|pixel| pixel.write_bytes(&mut output));
+
+        output
+    }
+}
+
+fn unorm_to_f32(value: u8) -> f32 {
+    f32::from(value) / 255.0
+}
+
+fn f32_to_unorm(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+// https://en.wikipedia.org/wiki/Half-precision_floating-point_format
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    // Infinity and NaN
+    if exponent == 0xff {
+        return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    if exponent <= 0 {
+        // Subnormal half, too small values are flushed to zero
+        if exponent < -10 {
+            return sign;
+        }
+
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - exponent) as u32;
+        let round = (mantissa >> (shift - 1)) & 1;
+
+        return sign | ((mantissa >> shift) + round) as u16;
+    }
+
+    // Rounding carry may move the value into the next exponent (or infinity)
+    let half = ((exponent as u32) << 10) | (mantissa >> 13);
+    let round = (mantissa >> 12) & 1;
+
+    sign | (half + round) as u16
+}
+
+pub fn f16_to_f32(value: u16) -> f32 {
+    let sign = u32::from(value & 0x8000) << 16;
+    let exponent = u32::from((value >> 10) & 0x1f);
+    let mantissa = u32::from(value & 0x3ff);
+
+    match exponent {
+        0 => {
+            let value = mantissa as f32 / (1 << 24) as f32;
+            if sign != 0 { -value } else { value }
+        }
+        0x1f => f32::from_bits(sign | 0x7f80_0000 | (mantissa << 13)),
+        _ => f32::from_bits(sign | ((exponent + 127 - 15) << 23) | (mantissa << 13)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::coverage_accumulator::FillRule;
+    use crate::graphics::rasterizate::Rasterizate;
+    use crate::utils::math::is_equal::IsCopyTypeEqual;
+    use crate::utils::math::vector::vector2::Vec2;
+
+    use super::*;
+
+    const EPSILON: f64 = 0.001;
+
+    #[test]
+    fn f16_round_trip() {
+        for value in [0.0, -0.0, 1.0, -2.5, 0.5, 65504.0, 6.1035156e-5, 5.9604645e-8] {
+            assert_eq!(f16_to_f32(f32_to_f16(value)), value);
+        }
+
+        assert_eq!(f32_to_f16(1.0), 0x3c00);
+        assert_eq!(f32_to_f16(-2.0), 0xc000);
+        assert_eq!(f32_to_f16(1.0e6), 0x7c00);
+        assert_eq!(f32_to_f16(1.0e-9), 0);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+        assert!(f64::from(f16_to_f32(f32_to_f16(0.1))).is_equal(0.1, EPSILON));
+    }
+
+    #[test]
+    fn convert_formats() {
+        let color = Vec4::new(1.0, 0.5, 0.25, 0.75);
+        let buffer = Buffer2d::new(Vec2::new(2, 1), color);
+
+        let rgba8 = buffer.convert::<Rgba8>();
+        assert_eq!(rgba8.value(Vec2::new(1, 0)), Rgba8([255, 128, 64, 191]));
+        assert_eq!(rgba8.to_bytes(), vec![255, 128, 64, 191, 255, 128, 64, 191]);
+
+        let rg8 = rgba8.convert::<Rg8>();
+        assert_eq!(rg8.value(Vec2::ZERO).to_rgba().z, 0.0);
+        assert_eq!(rg8.value(Vec2::ZERO).to_rgba().w, 1.0);
+
+        let rgba16f: Buffer2d<Rgba16F> = buffer.convert();
+        assert_eq!(rgba16f.convert::<Rgba32F>().value(Vec2::ZERO), color);
+        assert_eq!(rgba16f.to_bytes().len(), 2 * 8);
+
+        assert_eq!(buffer.convert::<R8>().to_bytes(), vec![255, 255]);
+
+        assert!(rgba8.convert::<Rgba32F>().fits_rgba8());
+        assert!(!buffer.fits_rgba8());
+        assert!(!Buffer2d::new(Vec2::new(1, 1), Vec4::from(32769.0 / 65535.0)).fits_rgba8());
+    }
+
+    #[test]
+    fn image_data_format() {
+        let buffer = Buffer2d::new(Vec2::new(1, 1), Rgba8([128, 0, 255, 255]));
+        assert_eq!(buffer.to_image_data(), (Format::R8G8B8A8_UNORM, vec![128, 0, 255, 255]));
+
+        let buffer = buffer.with_color_space(ColorSpace::Srgb);
+        assert_eq!(buffer.to_image_data(), (Format::R8G8B8A8_SRGB, vec![128, 0, 255, 255]));
+
+        // No sRGB float formats, values are decoded before the upload
+        let (format, data) = buffer.convert::<Rgba16F>().to_image_data();
+        assert_eq!(format, Format::R16G16B16A16_SFLOAT);
+        let red = f16_to_f32(u16::from_ne_bytes([data[0], data[1]]));
+        assert!(f64::from(red).is_equal(0.216, EPSILON));
+
+        let buffer = Buffer2d::new(Vec2::new(1, 1), Vec4::new(0.5, 0.0, 1.0, 1.0));
+        assert_eq!(buffer.to_image_data(), (Format::R8G8B8A8_SRGB, vec![128, 0, 255, 255]));
+
+        let (format, _) = buffer.with_color_space(ColorSpace::Linear).to_image_data();
+        assert_eq!(format, Format::R32G32B32A32_SFLOAT);
+    }
+
+    #[test]
+    fn rasterizate_coverage_buffer() {
+        let mut buffer = Buffer2d::new(Vec2::new(4, 4), R8(0));
+        let square = [Vec2::new(1.0, 1.0), Vec2::new(3.0, 1.0), Vec2::new(3.0, 3.0), Vec2::new(1.0, 3.0)];
+        buffer.fill_outlines(&[square], FillRule::NonZero, Vec4::from(1.0));
+
+        assert_eq!(buffer.value(Vec2::new(0, 0)), R8(0));
+        assert_eq!(buffer.value(Vec2::new(2, 2)), R8(255));
+
+        let mut coverage = Buffer2d::new(Vec2::new(2, 1), 0.0f32);
+        coverage.draw_line(Vec2::new(0, 0), Vec2::new(1, 0), Vec4::from(0.5));
+        assert_eq!(coverage.value(Vec2::new(1, 0)), 0.5);
+    }
+}
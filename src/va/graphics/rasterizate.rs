@@ -1,4 +1,6 @@
 use std::cmp::{min, max};
+use std::f32::consts::TAU;
+use std::ops::Range;
 
 use crate::utils::math::geometry::curve::{QuadCurveIntPointsIter, CubeCurveIntPointsIter};
 use crate::utils::math::geometry::line::LinePointsIter;
@@ -10,7 +12,15 @@ use crate::va::utils::cast::Cast;
 use crate::va::utils::math::vector::vector2::Vec2;
 use crate::va::utils::math::vector::vector4::Vec4;
 
-use super::buffer::buffer2d::{Buffer2d, Buffer2dWrite};
+use super::blend::{BlendMode, Buffer2dBlend};
+use super::buffer::buffer2d::{Buffer2d, Buffer2dRead, Buffer2dWrite};
+use super::coverage_accumulator::{CoverageAccumulator, FillRule};
+use super::edge_table::EdgeTable;
+use super::font::Font;
+use super::path::Path;
+use super::pixel_format::PixelFormat;
+use super::stroke::{self, StrokeStyle};
+use super::text_layout::{TextLayout, TextLayoutBuilder};
 
 pub trait SimpleRasterizate<T>: Buffer2dWrite<T> 
     where T: Clone,
@@ -138,7 +148,11 @@ pub trait SimpleRasterizate<T>: Buffer2dWrite<T>
 
 impl<T: Clone, U: Buffer2dWrite<T>> SimpleRasterizate<T> for U {}
 
-pub trait Rasterizate: SimpleRasterizate<Vec4<f32>> {
+/// Colors are straight alpha RGBA in the color space of the buffer, they are
+/// converted into the pixel format of the buffer
+pub trait Rasterizate<P = Vec4<f32>>: SimpleRasterizate<P>
+    where P: PixelFormat,
+{
     fn draw_line(&mut self, p0: Vec2<i32>, p1: Vec2<i32>, color: Vec4<f32>) {
         let size = self.size();
         if size.x == 0 || size.y == 0 {
@@ -162,9 +176,10 @@ pub trait Rasterizate: SimpleRasterizate<Vec4<f32>> {
             }
         };
 
+        let value = P::from_rgba(color);
         LinePointsIter::new(point, point2).for_each(|val|
             unsafe {
-                self.draw_point_unchecked(val, color);
+                self.draw_point_unchecked(val, value);
             }
         );
     }
@@ -194,7 +209,7 @@ pub trait Rasterizate: SimpleRasterizate<Vec4<f32>> {
 
         LinePointsIter::new(point, point2).for_each(|val|
             unsafe {
-                self.draw_point_unchecked(val, self.pixel(val).unwrap() + color);
+                self.draw_point_unchecked(val, P::from_rgba(self.pixel(val).unwrap().to_rgba() + color));
             }
         );
     }
@@ -263,27 +278,16 @@ pub trait Rasterizate: SimpleRasterizate<Vec4<f32>> {
             let d1 = 1.0 - (j_val - j_val.floor());
             let d2 = 1.0 - (j_val.ceil() - j_val);
 
-            if let Some(bg) = self.pixel(point) {
-                let color = mix(
-                    Vec4::new(color.x, color.y, color.z, d1), 
-                    bg,
-                );
-                let color = Vec4::new(color.x, color.y, color.z, d1);
-
+            let color = Vec4::new(color.x, color.y, color.z, 1.0);
+            if self.pixel(point).is_some() {
                 unsafe {
-                    self.draw_point_unchecked(point, color);
+                    self.blend_point_unchecked(point, color, d1);
                 }
             }
             
-            if let Some(bg) = self.pixel(point2) {
-                let color = mix(
-                    Vec4::new(color.x, color.y, color.z, d2),
-                    bg,
-                );
-                let color = Vec4::new(color.x, color.y, color.z, d2);
-
+            if self.pixel(point2).is_some() {
                 unsafe {
-                    self.draw_point_unchecked(point2, color);
+                    self.blend_point_unchecked(point2, color, d2);
                 }
             }
         }
@@ -302,19 +306,11 @@ pub trait Rasterizate: SimpleRasterizate<Vec4<f32>> {
             let d1 = 1.0 - (j_val - j_val.floor());
             let d2 = 1.0 - (j_val.ceil() - j_val);
 
-            let color1 = mix(
-                Vec4::new(color.x, color.y, color.z, d1), 
-                self.pixel(point).unwrap(),
-            );
-
-            let color2 = mix(
-                Vec4::new(color.x, color.y, color.z, d2),
-                self.pixel(point2).unwrap(),
-            );
+            let color = Vec4::new(color.x, color.y, color.z, 1.0);
 
             unsafe {
-                self.draw_point_unchecked(point, color1);
-                self.draw_point_unchecked(point2, color2);
+                self.blend_point_unchecked(point, color, d1);
+                self.blend_point_unchecked(point2, color, d2);
             }
         }
     }
@@ -336,9 +332,10 @@ pub trait Rasterizate: SimpleRasterizate<Vec4<f32>> {
             return;
         }
 
+        let value = P::from_rgba(color);
         QuadCurveIntPointsIter::new(p0, p1, p2).for_each(|val|
             unsafe {
-                self.draw_point_unchecked(val.round().cast(), color);
+                self.draw_point_unchecked(val.round().cast(), value);
             }
         );
     }
@@ -496,9 +493,10 @@ pub trait Rasterizate: SimpleRasterizate<Vec4<f32>> {
             return;
         }
 
+        let value = P::from_rgba(color);
         CubeCurveIntPointsIter::new(p0, p1, p2, p3).for_each(|val|
             unsafe {
-                self.draw_point_unchecked(val.round().cast(), color);
+                self.draw_point_unchecked(val.round().cast(), value);
             }
         );
     }
@@ -538,24 +536,573 @@ pub trait Rasterizate: SimpleRasterizate<Vec4<f32>> {
 
         self.draw_wu_line(prev, p3, color);
     }
+
+    /// Fills closed outlines at once, so overlapping and nested outlines are
+    /// resolved by their winding numbers according to `fill_rule`.
+    fn fill_outlines<T>(&mut self, outlines: &[T], fill_rule: FillRule, color: Vec4<f32>)
+        where T: AsRef<[Vec2<f32>]>,
+    {
+        let mut min = Vec2::from(f32::MAX);
+        let mut max = Vec2::from(f32::MIN);
+
+        for point in outlines.iter().flat_map(|outline| outline.as_ref().iter()) {
+            min = Vec2::new(min.x.min(point.x), min.y.min(point.y));
+            max = Vec2::new(max.x.max(point.x), max.y.max(point.y));
+        }
+
+        let size: Vec2<f32> = self.size().cast();
+        let min = Vec2::new(min.x.floor().max(0.0), min.y.floor().max(0.0));
+        let max = Vec2::new(max.x.ceil().min(size.x), max.y.ceil().min(size.y));
+
+        if !(min.x < max.x && min.y < max.y) {
+            return;
+        }
+
+        let mut accumulator = CoverageAccumulator::new((max - min).cast());
+        for outline in outlines {
+            let points: Vec<Vec2<f32>> = outline.as_ref().iter().map(|&point| point - min).collect();
+            accumulator.add_outline(&points);
+        }
+
+        self.draw_coverage(min.cast(), &accumulator.build(fill_rule), color);
+    }
+
+    /// Open contours of the path are closed implicitly
+    fn fill_path(&mut self, path: &Path, fill_rule: FillRule, color: Vec4<f32>) {
+        self.fill_outlines(&path.flatten(), fill_rule, color);
+    }
+
+    fn stroke_path(&mut self, path: &Path, style: &StrokeStyle, color: Vec4<f32>) {
+        let polygons = stroke::stroke_polygons(&path.flatten(), style);
+        self.fill_outlines(&polygons, FillRule::NonZero, color);
+    }
+
+    /// Fills pixels whose centers are inside of the polygon, the last point
+    /// is connected with the first one.
+    fn fill_polygon(&mut self, polygon: &[Vec2<f32>], fill_rule: FillRule, color: Vec4<f32>) {
+        self.fill_polygons(&[polygon], fill_rule, color);
+    }
+
+    /// Fills the polygons at once, so nested polygons may make holes
+    fn fill_polygons<T>(&mut self, polygons: &[T], fill_rule: FillRule, color: Vec4<f32>)
+        where T: AsRef<[Vec2<f32>]>,
+    {
+        let table = EdgeTable::new(polygons);
+        let rows = match scanline_rows(&table, self.size()) {
+            Some(val) => val,
+            None => return,
+        };
+
+        let width = self.width() as f32;
+        table.scan(rows.map(|y| y as f32 + 0.5), fill_rule, |y, x0, x1| {
+            let x0 = (x0 - 0.5).ceil().max(0.0) as i32;
+            let x1 = (x1 - 0.5).ceil().min(width) as i32;
+
+            for x in x0..x1 {
+                unsafe {
+                    self.blend_point_unchecked(Vec2::new(x, y as i32), color, 1.0);
+                }
+            }
+        });
+    }
+
+    fn fill_smooth_polygon(&mut self, polygon: &[Vec2<f32>], fill_rule: FillRule, color: Vec4<f32>) {
+        self.fill_smooth_polygons(&[polygon], fill_rule, color);
+    }
+
+    /// Anti-aliased [`Rasterizate::fill_polygons`], every row is sampled by
+    /// several scanlines with exact horizontal coverage of the spans.
+    fn fill_smooth_polygons<T>(&mut self, polygons: &[T], fill_rule: FillRule, color: Vec4<f32>)
+        where T: AsRef<[Vec2<f32>]>,
+    {
+        const SUBSCANLINES: usize = 4;
+
+        let table = EdgeTable::new(polygons);
+        let rows = match scanline_rows(&table, self.size()) {
+            Some(val) => val,
+            None => return,
+        };
+
+        let width = self.width();
+        let mut coverage = vec![0.0; width * rows.len()];
+        let k = 1.0 / SUBSCANLINES as f32;
+
+        let ys = rows.clone().flat_map(|y| (0..SUBSCANLINES).map(move |i| y as f32 + (i as f32 + 0.5) * k));
+        table.scan(ys, fill_rule, |y, x0, x1| {
+            let (x0, x1) = (x0.max(0.0), x1.min(width as f32));
+            if x0 >= x1 {
+                return;
+            }
+
+            let offset = (y as usize - rows.start) * width;
+            let row = &mut coverage[offset..offset + width];
+            let first = x0 as usize;
+            let last = (x1.ceil() as usize).saturating_sub(1).max(first);
+
+            if first == last {
+                row[first] += (x1 - x0) * k;
+            }
+            else {
+                row[first] += (first as f32 + 1.0 - x0) * k;
+                for value in &mut row[first + 1..last] {
+                    *value += k;
+                }
+                row[last] += (x1 - last as f32) * k;
+            }
+        });
+
+        let coverage = Buffer2d::from_iter(Vec2::new(width, rows.len()), coverage.into_iter().map(|val| val.min(1.0)));
+        self.draw_coverage(Vec2::new(0, rows.start as i32), &coverage, color);
+    }
+
+    /// Fills the triangle like GPUs do: pixels are sampled at their centers,
+    /// edges shared by two triangles are drawn once by the top-left rule and
+    /// vertex colors are interpolated by barycentric coordinates.
+    // https://learn.microsoft.com/en-us/windows/win32/direct3d11/d3d10-graphics-programming-guide-rasterizer-stage-rules#triangle-rasterization-rules-without-multisampling
+    fn fill_triangle(&mut self, vertices: [Vec2<f32>; 3], colors: [Vec4<f32>; 3]) {
+        let [mut a, mut b, c] = vertices;
+        let [mut color_a, mut color_b, color_c] = colors;
+
+        let mut area = edge_function(a, b, c);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+
+        // Clockwise on the screen
+        if area < 0.0 {
+            std::mem::swap(&mut a, &mut b);
+            std::mem::swap(&mut color_a, &mut color_b);
+            area = -area;
+        }
+
+        let size: Vec2<f32> = self.size().cast();
+        let x0 = (a.x.min(b.x).min(c.x) - 0.5).ceil().max(0.0) as i32;
+        let y0 = (a.y.min(b.y).min(c.y) - 0.5).ceil().max(0.0) as i32;
+        let x1 = (a.x.max(b.x).max(c.x) + 0.5).ceil().min(size.x) as i32;
+        let y1 = (a.y.max(b.y).max(c.y) + 0.5).ceil().min(size.y) as i32;
+
+        // Edges opposite to the vertices a, b and c
+        let edges = [(b, c), (c, a), (a, b)];
+        let is_top_left = edges.map(|(p0, p1)| (p0.y == p1.y && p1.x > p0.x) || p1.y < p0.y);
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let point = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let weights = edges.map(|(p0, p1)| edge_function(p0, p1, point));
+
+                let is_inside = weights.iter()
+                    .zip(is_top_left)
+                    .all(|(&weight, is_top_left)| weight > 0.0 || (weight == 0.0 && is_top_left));
+
+                if is_inside {
+                    let color = (color_a * weights[0] + color_b * weights[1] + color_c * weights[2]) / area;
+                    unsafe {
+                        self.blend_point_unchecked(Vec2::new(x, y), color, 1.0);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Midpoint circle, points outside of the buffer are skipped like in
+    /// [`SimpleRasterizate::draw_point`].
+    // https://en.wikipedia.org/wiki/Midpoint_circle_algorithm
+    fn draw_circle(&mut self, center: Vec2<i32>, radius: i32, color: Vec4<f32>) {
+        let value = P::from_rgba(color);
+        for_each_circle_octant(radius, |x, y| {
+            for point in circle_symmetric_points(x, y) {
+                self.draw_point(center + point, value);
+            }
+        });
+    }
+
+    /// Every row is drawn once, so blended circles are composited once
+    fn fill_circle(&mut self, center: Vec2<i32>, radius: i32, color: Vec4<f32>) {
+        let value = P::from_rgba(color);
+        let mut draw_rows = |dx: i32, dy: i32| {
+            self.draw_horizontal_line(center.x - dx, center.x + dx, center.y + dy, value);
+            if dy != 0 {
+                self.draw_horizontal_line(center.x - dx, center.x + dx, center.y - dy, value);
+            }
+        };
+
+        // Rows `±x` are drawn with the widest span, when `x` changes
+        let mut previous: Option<(i32, i32)> = None;
+        for_each_circle_octant(radius, |x, y| {
+            draw_rows(x, y);
+
+            if let Some((previous_x, previous_y)) = previous {
+                if previous_x != x {
+                    draw_rows(previous_y, previous_x);
+                }
+            }
+
+            previous = Some((x, y));
+        });
+
+        // The last row `x == y` is already drawn as `y`
+        if let Some((x, y)) = previous {
+            if x != y {
+                draw_rows(y, x);
+            }
+        }
+    }
+
+    /// Midpoint ellipse with the axes parallel to the buffer axes
+    // https://www.geeksforgeeks.org/midpoint-ellipse-drawing-algorithm/
+    fn draw_ellipse(&mut self, center: Vec2<i32>, radii: Vec2<i32>, color: Vec4<f32>) {
+        if radii.x < 0 || radii.y < 0 {
+            return;
+        }
+
+        let value = P::from_rgba(color);
+        if radii.y == 0 {
+            self.draw_horizontal_line(center.x - radii.x, center.x + radii.x, center.y, value);
+            return;
+        }
+
+        if radii.x == 0 {
+            self.draw_vertical_line(center.y - radii.y, center.y + radii.y, center.x, value);
+            return;
+        }
+
+        let mut draw_points = |x: i32, y: i32| {
+            for point in unique_points([(x, y), (-x, y), (x, -y), (-x, -y)]) {
+                self.draw_point(center + point, value);
+            }
+        };
+
+        let (a2, b2) = ((radii.x as f64).powi(2), (radii.y as f64).powi(2));
+        let (mut x, mut y) = (0, radii.y);
+        let (mut dx, mut dy) = (0.0, 2.0 * a2 * y as f64);
+
+        // Slope is less than 1
+        let mut d = b2 - a2 * radii.y as f64 + 0.25 * a2;
+        while dx < dy {
+            draw_points(x, y);
+            x += 1;
+            dx += 2.0 * b2;
+
+            if d < 0.0 {
+                d += dx + b2;
+            }
+            else {
+                y -= 1;
+                dy -= 2.0 * a2;
+                d += dx - dy + b2;
+            }
+        }
+
+        let mut d = b2 * (x as f64 + 0.5).powi(2) + a2 * (y as f64 - 1.0).powi(2) - a2 * b2;
+        while y >= 0 {
+            draw_points(x, y);
+            y -= 1;
+            dy -= 2.0 * a2;
+
+            if d > 0.0 {
+                d += a2 - dy;
+            }
+            else {
+                x += 1;
+                dx += 2.0 * b2;
+                d += dx - dy + a2;
+            }
+        }
+    }
+
+    /// Midpoint circle arc, angles are in radians from the X axis towards the
+    /// Y axis (clockwise on the screen), `sweep_angle` may be negative.
+    fn draw_arc(&mut self, center: Vec2<i32>, radius: i32, start_angle: f32, sweep_angle: f32, color: Vec4<f32>) {
+        let value = P::from_rgba(color);
+        for_each_circle_octant(radius, |x, y| {
+            for point in circle_symmetric_points(x, y) {
+                let angle = (point.y as f32).atan2(point.x as f32);
+                if is_angle_in_arc(angle, start_angle, sweep_angle) {
+                    self.draw_point(center + point, value);
+                }
+            }
+        });
+    }
+
+    /// Fills pixels whose centers are inside of the rect with rounded
+    /// corners, `rect` includes both corners like in
+    /// [`SimpleRasterizate::draw_rect`]. Radii are top-left, top-right,
+    /// bottom-right and bottom-left.
+    fn fill_rounded_rect(&mut self, rect: Rect<i32>, radii: [i32; 4], color: Vec4<f32>) {
+        if rect.p1.x > rect.p2.x || rect.p1.y > rect.p2.y {
+            return;
+        }
+
+        let value = P::from_rgba(color);
+        let p1: Vec2<f32> = rect.p1.cast();
+        let p2: Vec2<f32> = rect.p2.cast();
+        let p2 = p2 + 1.0;
+        let radii = clamp_corner_radii(radii.map(|val| val as f32), p2 - p1);
+
+        // Pixels outside of the buffer are skipped
+        let width = self.width().min(i32::MAX as usize) as i32;
+        let height = self.height().min(i32::MAX as usize) as i32;
+        for y in rect.p1.y.max(0)..=rect.p2.y.min(height - 1) {
+            let center_y = y as f32 + 0.5;
+
+            // Horizontal inset of the corner circle, `dy` is the distance
+            // from the circle center towards the rect border
+            let inset = |radius: f32, dy: f32| {
+                if dy > 0.0 && dy < radius { radius - (radius * radius - dy * dy).sqrt() } else { 0.0 }
+            };
+
+            let left = inset(radii[0], p1.y + radii[0] - center_y).max(inset(radii[3], center_y - p2.y + radii[3]));
+            let right = inset(radii[1], p1.y + radii[1] - center_y).max(inset(radii[2], center_y - p2.y + radii[2]));
+
+            let x0 = ((p1.x + left - 0.5).ceil() as i32).max(0);
+            let x1 = ((p2.x - right - 0.5).floor() as i32).min(width - 1);
+            if x0 <= x1 {
+                self.draw_horizontal_line(x0, x1, y, value);
+            }
+        }
+    }
+
+    /// Anti-aliased circle one pixel wide
+    fn draw_smooth_circle(&mut self, center: Vec2<f32>, radius: f32, color: Vec4<f32>) {
+        let bounds = Rect::new(center - radius - 1.0, center + radius + 1.0);
+        fill_distance(self, bounds, color, |point| ((point - center).lenght() - radius).abs() - 0.5);
+    }
+
+    fn fill_smooth_circle(&mut self, center: Vec2<f32>, radius: f32, color: Vec4<f32>) {
+        let bounds = Rect::new(center - radius - 1.0, center + radius + 1.0);
+        fill_distance(self, bounds, color, |point| (point - center).lenght() - radius);
+    }
+
+    fn draw_smooth_ellipse(&mut self, center: Vec2<f32>, radii: Vec2<f32>, color: Vec4<f32>) {
+        let bounds = Rect::new(center - radii - 1.0, center + radii + 1.0);
+        fill_distance(self, bounds, color, |point| ellipse_distance(point - center, radii).abs() - 0.5);
+    }
+
+    /// Anti-aliased [`Rasterizate::draw_arc`] with round caps
+    fn draw_smooth_arc(&mut self, center: Vec2<f32>, radius: f32, start_angle: f32, sweep_angle: f32, color: Vec4<f32>) {
+        let end_angle = start_angle + sweep_angle;
+        let ends = [start_angle, end_angle].map(|angle| center + Vec2::new(angle.cos(), angle.sin()) * radius);
+
+        let bounds = Rect::new(center - radius - 1.0, center + radius + 1.0);
+        fill_distance(self, bounds, color, |point| {
+            let offset = point - center;
+            let distance = if is_angle_in_arc(offset.y.atan2(offset.x), start_angle, sweep_angle) {
+                (offset.lenght() - radius).abs()
+            }
+            else {
+                (point - ends[0]).lenght().min((point - ends[1]).lenght())
+            };
+
+            distance - 0.5
+        });
+    }
+
+    /// Anti-aliased [`Rasterizate::fill_rounded_rect`], `rect` is the
+    /// exact area between its corners.
+    // https://iquilezles.org/articles/distfunctions2d/
+    fn fill_smooth_rounded_rect(&mut self, rect: Rect<f32>, radii: [f32; 4], color: Vec4<f32>) {
+        if !(rect.p1.x < rect.p2.x && rect.p1.y < rect.p2.y) {
+            return;
+        }
+
+        let center = (rect.p1 + rect.p2) * 0.5;
+        let half_size = (rect.p2 - rect.p1) * 0.5;
+        let radii = clamp_corner_radii(radii, rect.p2 - rect.p1);
+
+        let bounds = Rect::new(rect.p1 - 1.0, rect.p2 + 1.0);
+        fill_distance(self, bounds, color, |point| {
+            let offset = point - center;
+            let radius = match (offset.x < 0.0, offset.y < 0.0) {
+                (true, true) => radii[0],
+                (false, true) => radii[1],
+                (false, false) => radii[2],
+                (true, false) => radii[3],
+            };
+
+            let q = offset.abs() - half_size + radius;
+            q.x.max(q.y).min(0.0) + Vec2::new(q.x.max(0.0), q.y.max(0.0)).lenght() - radius
+        });
+    }
+
+    /// Draws the text with [`TextLayoutBuilder`] defaults, `position` is the
+    /// top-left corner of the first line.
+    fn draw_text(&mut self, font: &Font, position: Vec2<f32>, text: &str, color: Vec4<f32>) {
+        let layout = TextLayoutBuilder::new().build(font, text);
+        self.draw_text_layout(font, position, &layout, color);
+    }
+
+    fn draw_text_layout(&mut self, font: &Font, position: Vec2<f32>, layout: &TextLayout, color: Vec4<f32>) {
+        for glyph in layout.glyphs() {
+            if let Some(glyph_render) = font.glyph_render(&glyph.char_info(), position + glyph.position()) {
+                self.fill_outlines(glyph_render.outlines(), glyph_render.fill_rule(), color);
+            }
+        }
+    }
+
+    /// Blends `color` into the buffer using `coverage` values as alpha,
+    /// `position` is the top-left corner of the coverage buffer.
+    fn draw_coverage(&mut self, position: Vec2<i32>, coverage: &Buffer2d<f32>, color: Vec4<f32>) {
+        for y in 0..coverage.height() {
+            for x in 0..coverage.width() {
+                let alpha = coverage.value(Vec2::new(x, y));
+                if alpha <= 0.0 {
+                    continue;
+                }
+
+                let offset: Vec2<i32> = Vec2::new(x, y).cast();
+                let point = position + offset;
+                if self.pixel(point).is_some() {
+                    unsafe {
+                        self.blend_point_unchecked(point, color, alpha);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Blends `color` into the pixel, `coverage` is the part of the pixel
+    /// covered by the drawn shape.
+    ///
+    /// # Safety
+    ///
+    /// `point` must be inside of the buffer
+    unsafe fn blend_point_unchecked(&mut self, point: Vec2<i32>, color: Vec4<f32>, coverage: f32) {
+        let bg = self.value(point.cast()).to_rgba();
+        let color = BlendMode::SrcOver.blend_in(self.color_space(), color, bg, coverage);
+        self.draw_point_unchecked(point, P::from_rgba(color));
+    }
 }
 
-impl Rasterizate for Buffer2d<Vec4<f32>> {}
+impl<P> Rasterizate<P> for Buffer2d<P>
+    where P: PixelFormat,
+{}
 
-// https://stackoverflow.com/questions/726549/algorithm-for-additive-color-mixing-for-rgb-values
-fn mix(fg: Vec4<f32>, bg: Vec4<f32>) -> Vec4<f32> {
-    let mut r = Vec4::default();
-    r.w = 1.0 - (1.0 - fg.w) * (1.0 - bg.w);
+impl<'a, B, P> Rasterizate<P> for Buffer2dBlend<'a, B>
+    where B: Buffer2dWrite<P>,
+          P: PixelFormat,
+{
+    unsafe fn blend_point_unchecked(&mut self, point: Vec2<i32>, color: Vec4<f32>, coverage: f32) {
+        self.blend_value::<P>(point.cast(), color, coverage);
+    }
+}
 
-    if r.w < 1.0e-6 {
-        return r; // Fully transparent -- R,G,B not important
+/// Calls `f(x, y)` for points of the second octant of the midpoint circle,
+/// where `y <= x`
+fn for_each_circle_octant<F>(radius: i32, mut f: F)
+    where F: FnMut(i32, i32),
+{
+    if radius < 0 {
+        return;
     }
 
-    r.x = fg.x * fg.w / r.w + bg.x * bg.w * (1.0 - fg.w) / r.w;
-    r.y = fg.y * fg.w / r.w + bg.y * bg.w * (1.0 - fg.w) / r.w;
-    r.z = fg.z * fg.w / r.w + bg.z * bg.w * (1.0 - fg.w) / r.w;
+    let (mut x, mut y) = (radius, 0);
+    let mut d = 1 - radius;
+
+    while y <= x {
+        f(x, y);
+        y += 1;
+
+        if d < 0 {
+            d += 2 * y + 1;
+        }
+        else {
+            x -= 1;
+            d += 2 * (y - x) + 1;
+        }
+    }
+}
+
+/// Points of all octants mirrored from `(x, y)`, the points on the axes and
+/// the diagonals are returned once
+fn circle_symmetric_points(x: i32, y: i32) -> impl Iterator<Item = Vec2<i32>> {
+    unique_points([(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)])
+}
 
-    r
+fn unique_points<const N: usize>(points: [(i32, i32); N]) -> impl Iterator<Item = Vec2<i32>> {
+    points.into_iter()
+        .enumerate()
+        .filter(move |&(i, point)| !points[..i].contains(&point))
+        .map(|(_, (x, y))| Vec2::new(x, y))
+}
+
+fn is_angle_in_arc(angle: f32, start_angle: f32, sweep_angle: f32) -> bool {
+    if sweep_angle.abs() >= TAU {
+        return true;
+    }
+
+    let offset = if sweep_angle >= 0.0 { angle - start_angle } else { start_angle - angle };
+    offset.rem_euclid(TAU) <= sweep_angle.abs()
+}
+
+/// Radii bigger than half of the rect are scaled down like in CSS
+// https://www.w3.org/TR/css-backgrounds-3/#corner-overlap
+fn clamp_corner_radii(radii: [f32; 4], size: Vec2<f32>) -> [f32; 4] {
+    let radii = radii.map(|val| val.max(0.0));
+    let sums = [radii[0] + radii[1], radii[1] + radii[2], radii[2] + radii[3], radii[3] + radii[0]];
+    let sides = [size.x, size.y, size.x, size.y];
+
+    let k = sums.iter().zip(sides)
+        .filter(|(&sum, _)| sum > 0.0)
+        .map(|(&sum, side)| side / sum)
+        .fold(1.0, f32::min);
+
+    radii.map(|val| val * k)
+}
+
+/// Approximate signed distance to the ellipse
+// https://iquilezles.org/articles/ellipsedist/
+fn ellipse_distance(point: Vec2<f32>, radii: Vec2<f32>) -> f32 {
+    if radii.x <= 0.0 || radii.y <= 0.0 {
+        return f32::MAX;
+    }
+
+    let k0 = Vec2::new(point.x / radii.x, point.y / radii.y).lenght();
+    let k1 = Vec2::new(point.x / (radii.x * radii.x), point.y / (radii.y * radii.y)).lenght();
+    if k1 == 0.0 {
+        return -radii.x.min(radii.y);
+    }
+
+    k0 * (k0 - 1.0) / k1
+}
+
+/// Blends `color` by the signed distance to the shape at pixel centers, the
+/// pixel is half covered on the border.
+fn fill_distance<R, P, F>(buffer: &mut R, bounds: Rect<f32>, color: Vec4<f32>, distance: F)
+    where R: Rasterizate<P> + ?Sized,
+          P: PixelFormat,
+          F: Fn(Vec2<f32>) -> f32,
+{
+    let size: Vec2<f32> = buffer.size().cast();
+    let x0 = bounds.p1.x.floor().max(0.0) as i32;
+    let y0 = bounds.p1.y.floor().max(0.0) as i32;
+    let x1 = bounds.p2.x.ceil().min(size.x) as i32;
+    let y1 = bounds.p2.y.ceil().min(size.y) as i32;
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let coverage = (0.5 - distance(Vec2::new(x as f32 + 0.5, y as f32 + 0.5))).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                unsafe {
+                    buffer.blend_point_unchecked(Vec2::new(x, y), color, coverage);
+                }
+            }
+        }
+    }
+}
+
+/// Rows of the buffer crossed by the edges
+fn scanline_rows(table: &EdgeTable, size: Vec2<usize>) -> Option<Range<usize>> {
+    let (y_min, y_max) = table.y_range()?;
+    let start = y_min.floor().max(0.0) as usize;
+    let end = (y_max.ceil().max(0.0) as usize).min(size.y);
+
+    (start < end && size.x > 0).then_some(start..end)
+}
+
+/// Twice the signed area of the triangle, positive if it's clockwise on the
+/// screen
+fn edge_function(a: Vec2<f32>, b: Vec2<f32>, point: Vec2<f32>) -> f32 {
+    (b.x - a.x) * (point.y - a.y) - (b.y - a.y) * (point.x - a.x)
 }
 
 fn anti_aliasing_curve_point(buffer: &mut Buffer2d<Vec4<f32>>, point: Vec2<f64>, color: Vec3<f32>) {
@@ -592,4 +1139,284 @@ fn anti_aliasing_curve_point(buffer: &mut Buffer2d<Vec4<f32>>, point: Vec2<f64>,
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::graphics::char_set::CharSet;
+    use crate::graphics::color_space::ColorSpace;
+    use crate::graphics::font_registry::{tests::test_font, FontFace};
+    use crate::graphics::snapshot::{self, assert_snapshot, Snapshot};
+
+    use super::*;
+
+    const BACKGROUND: Vec4<f32> = Vec4::new(0.0, 0.0, 0.0, 1.0);
+
+    #[test]
+    fn rasterizate_lines() {
+        let mut buffer = Buffer2d::new(Vec2::new(32, 32), BACKGROUND);
+
+        buffer.draw_line(Vec2::new(1, 1), Vec2::new(30, 12), Vec4::new(1.0, 0.0, 0.0, 1.0));
+        buffer.draw_line(Vec2::new(-10, 40), Vec2::new(40, -10), Vec4::new(0.0, 1.0, 0.0, 1.0));
+        buffer.draw_rect_border(Vec2::new(4, 20), Vec2::new(12, 28), Vec4::new(0.0, 0.0, 1.0, 1.0));
+
+        assert_snapshot("rasterizate_lines", &buffer);
+    }
+
+    #[test]
+    fn rasterizate_wu_lines() {
+        let mut buffer = Buffer2d::new(Vec2::new(32, 32), BACKGROUND);
+
+        buffer.draw_wu_line(Vec2::new(2, 2), Vec2::new(29, 11), Vec3::new(1.0, 1.0, 1.0));
+        buffer.draw_wu_line(Vec2::new(3, 29), Vec2::new(12, 4), Vec3::new(1.0, 0.5, 0.0));
+        buffer.draw_wu_line(Vec2::new(16, 16), Vec2::new(30, 30), Vec3::new(0.0, 0.5, 1.0));
+        buffer.draw_wu_line(Vec2::new(20, 2), Vec2::new(20, 12), Vec3::new(0.0, 1.0, 0.0));
+
+        Snapshot::new("rasterizate_wu_lines").with_tolerance(1).assert(&buffer);
+    }
+
+    #[test]
+    fn rasterizate_fill_outlines() {
+        let star: Vec<Vec2<f32>> = (0..5)
+            .map(|i| {
+                let angle = (i * 2) as f32 * std::f32::consts::TAU / 5.0;
+                Vec2::new(16.0 + 14.0 * angle.sin(), 16.0 - 14.0 * angle.cos())
+            })
+            .collect();
+
+        for (name, fill_rule) in [
+            ("rasterizate_fill_non_zero", FillRule::NonZero),
+            ("rasterizate_fill_even_odd", FillRule::EvenOdd),
+        ] {
+            let mut buffer = Buffer2d::new(Vec2::new(32, 32), BACKGROUND);
+            buffer.fill_outlines(&[&star], fill_rule, Vec4::new(1.0, 0.8, 0.2, 1.0));
+
+            Snapshot::new(name).with_tolerance(1).assert(&buffer);
+        }
+    }
+
+    #[test]
+    fn rasterizate_coverage_srgb() {
+        let mut buffer = Buffer2d::new(Vec2::new(24, 24), BACKGROUND).with_color_space(ColorSpace::Srgb);
+        let circle: Vec<Vec2<f32>> = (0..32)
+            .map(|i| {
+                let angle = i as f32 * std::f32::consts::TAU / 32.0;
+                Vec2::new(12.0 + 9.5 * angle.cos(), 12.0 + 9.5 * angle.sin())
+            })
+            .collect();
+
+        buffer.fill_outlines(&[circle], FillRule::NonZero, Vec4::new(1.0, 1.0, 1.0, 0.75));
+
+        Snapshot::new("rasterizate_coverage_srgb").with_tolerance(1).assert(&buffer);
+    }
+
+    /// Concave "C" shape and a square with a hole
+    fn test_polygons() -> [Vec<Vec2<f32>>; 3] {
+        [
+            vec![
+                Vec2::new(2.5, 2.0), Vec2::new(14.0, 2.0), Vec2::new(14.0, 6.3), Vec2::new(7.0, 6.3),
+                Vec2::new(7.0, 12.0), Vec2::new(14.0, 13.7), Vec2::new(14.0, 18.0), Vec2::new(2.5, 18.0),
+            ],
+            vec![Vec2::new(17.2, 3.0), Vec2::new(29.8, 3.0), Vec2::new(29.8, 17.0), Vec2::new(17.2, 17.0)],
+            vec![Vec2::new(20.0, 7.0), Vec2::new(27.0, 7.0), Vec2::new(23.5, 14.0)],
+        ]
+    }
+
+    #[test]
+    fn rasterizate_fill_polygon() {
+        let mut buffer = Buffer2d::new(Vec2::new(32, 20), BACKGROUND);
+        buffer.fill_polygons(&test_polygons(), FillRule::EvenOdd, Vec4::new(0.2, 0.6, 1.0, 1.0));
+        assert_snapshot("rasterizate_fill_polygon", &buffer);
+
+        // Pixel centers on the edges are inside only on the left and top
+        let mut buffer = Buffer2d::new(Vec2::new(4, 4), 0.0);
+        buffer.fill_polygon(&[Vec2::new(0.5, 0.5), Vec2::new(2.5, 0.5), Vec2::new(2.5, 2.5), Vec2::new(0.5, 2.5)], FillRule::NonZero, Vec4::from(1.0));
+        assert_eq!(buffer.iter().filter(|&&val| val == 1.0).count(), 4);
+        assert_eq!(buffer.value(Vec2::new(0, 0)), 1.0);
+        assert_eq!(buffer.value(Vec2::new(2, 2)), 0.0);
+    }
+
+    #[test]
+    fn rasterizate_fill_smooth_polygon() {
+        let size = Vec2::new(32, 20);
+        let color = Vec4::new(0.2, 0.6, 1.0, 1.0);
+
+        let mut buffer = Buffer2d::new(size, BACKGROUND);
+        buffer.fill_smooth_polygons(&test_polygons(), FillRule::EvenOdd, color);
+        Snapshot::new("rasterizate_fill_smooth_polygon").with_tolerance(1).assert(&buffer);
+
+        // Close to the exact area coverage
+        let mut exact = Buffer2d::new(size, BACKGROUND);
+        exact.fill_outlines(&test_polygons(), FillRule::EvenOdd, color);
+
+        let comparison = snapshot::compare(&buffer, &exact, 40).unwrap();
+        assert_eq!(comparison.mismatched, 0);
+        assert!(comparison.ssim > 0.98);
+    }
+
+    #[test]
+    fn rasterizate_fill_triangle() {
+        let mut buffer = Buffer2d::new(Vec2::new(32, 32), BACKGROUND);
+        buffer.fill_triangle(
+            [Vec2::new(16.0, 1.5), Vec2::new(30.5, 29.0), Vec2::new(1.0, 24.0)],
+            [Vec4::new(1.0, 0.0, 0.0, 1.0), Vec4::new(0.0, 1.0, 0.0, 1.0), Vec4::new(0.0, 0.0, 1.0, 1.0)],
+        );
+
+        Snapshot::new("rasterizate_fill_triangle").with_tolerance(1).assert(&buffer);
+    }
+
+    #[test]
+    fn rasterizate_fill_triangle_shared_edges() {
+        // Fan of triangles with both orientations, every pixel of the square
+        // must be blended exactly once
+        let mut buffer = Buffer2d::new(Vec2::new(16, 16), Vec4::new(0.0, 0.0, 0.0, 0.0));
+        let center = Vec2::new(7.3, 8.0);
+        let corners = [Vec2::new(2.0, 2.0), Vec2::new(14.0, 2.0), Vec2::new(14.0, 14.0), Vec2::new(2.0, 14.0)];
+        let color = Vec4::new(1.0, 1.0, 1.0, 0.5);
+
+        for i in 0..4 {
+            let (p0, p1) = (corners[i], corners[(i + 1) % 4]);
+            let vertices = if i % 2 == 0 { [center, p0, p1] } else { [p1, p0, center] };
+            buffer.fill_triangle(vertices, [color; 3]);
+        }
+
+        for y in 0..16 {
+            for x in 0..16 {
+                let expected = if (2..14).contains(&x) && (2..14).contains(&y) { 0.5 } else { 0.0 };
+                assert!((buffer.value(Vec2::new(x, y)).w - expected).abs() < 0.001, "{x} {y}");
+            }
+        }
+    }
+
+    #[test]
+    fn rasterizate_circles() {
+        let mut buffer = Buffer2d::new(Vec2::new(40, 40), BACKGROUND);
+
+        buffer.fill_circle(Vec2::new(10, 10), 7, Vec4::new(0.2, 0.6, 1.0, 1.0));
+        buffer.draw_circle(Vec2::new(10, 10), 8, Vec4::new(1.0, 1.0, 1.0, 1.0));
+        buffer.draw_ellipse(Vec2::new(28, 10), Vec2::new(9, 5), Vec4::new(1.0, 0.8, 0.0, 1.0));
+        buffer.draw_arc(Vec2::new(10, 29), 8, -std::f32::consts::FRAC_PI_2, 1.5 * std::f32::consts::PI, Vec4::new(0.0, 1.0, 0.3, 1.0));
+        buffer.fill_rounded_rect(Rect::new(Vec2::new(21, 21), Vec2::new(37, 36)), [6, 0, 3, 9], Vec4::new(1.0, 0.3, 0.3, 1.0));
+
+        // Clipped like points
+        buffer.draw_circle(Vec2::new(38, 38), 5, Vec4::new(1.0, 1.0, 1.0, 1.0));
+        buffer.fill_circle(Vec2::new(-2, 20), 3, Vec4::new(1.0, 1.0, 1.0, 1.0));
+
+        assert_snapshot("rasterizate_circles", &buffer);
+    }
+
+    /// Pixels composited twice with alpha 0.5 would get alpha 0.75
+    fn assert_blended_once<F>(draw: F)
+        where F: Fn(&mut Buffer2dBlend<'_, Buffer2d<Vec4<f32>>>, Vec4<f32>),
+    {
+        let mut buffer = Buffer2d::new(Vec2::new(25, 25), Vec4::from(0.0));
+        draw(&mut buffer.blended(BlendMode::SrcOver), Vec4::new(1.0, 1.0, 1.0, 0.5));
+
+        assert!(buffer.iter().any(|val| val.w != 0.0));
+        assert!(buffer.iter().all(|val| val.w == 0.0 || (val.w - 0.5).abs() < 0.001));
+    }
+
+    #[test]
+    fn rasterizate_circles_blended_once() {
+        let center = Vec2::new(12, 12);
+
+        assert_blended_once(|buffer, color| buffer.draw_circle(center, 9, color));
+        assert_blended_once(|buffer, color| buffer.draw_circle(center, 0, color));
+        assert_blended_once(|buffer, color| buffer.fill_circle(center, 9, color));
+        assert_blended_once(|buffer, color| buffer.fill_circle(center, 1, color));
+        assert_blended_once(|buffer, color| buffer.draw_ellipse(center, Vec2::new(10, 4), color));
+        assert_blended_once(|buffer, color| buffer.draw_ellipse(center, Vec2::new(0, 0), color));
+        assert_blended_once(|buffer, color| buffer.draw_arc(center, 9, 0.0, TAU, color));
+    }
+
+    #[test]
+    fn rasterizate_midpoint_shapes() {
+        // Radius 2: 12 points, symmetric by both axes
+        let mut buffer = Buffer2d::new(Vec2::new(5, 5), 0.0);
+        buffer.draw_circle(Vec2::new(2, 2), 2, Vec4::from(1.0));
+        assert_eq!(buffer.iter().filter(|&&val| val == 1.0).count(), 12);
+
+        let mut ellipse = Buffer2d::new(Vec2::new(5, 5), 0.0);
+        ellipse.draw_ellipse(Vec2::new(2, 2), Vec2::new(2, 2), Vec4::from(1.0));
+        assert_eq!(ellipse.iter().filter(|&&val| val == 1.0).count(), 12);
+
+        // Square corners are the same as draw_rect
+        let rect = Rect::new(Vec2::new(1, 2), Vec2::new(6, 4));
+        let mut rounded = Buffer2d::new(Vec2::new(8, 8), 0.0);
+        rounded.fill_rounded_rect(rect, [0; 4], Vec4::from(1.0));
+        let mut expected = Buffer2d::new(Vec2::new(8, 8), 0.0);
+        expected.draw_rect(rect, 1.0);
+        assert_eq!(rounded.buffer(), expected.buffer());
+
+        // Too big radii make a stadium
+        let mut stadium = Buffer2d::new(Vec2::new(12, 4), 0.0);
+        stadium.fill_rounded_rect(Rect::new(Vec2::new(0, 0), Vec2::new(11, 3)), [10; 4], Vec4::from(1.0));
+        assert_eq!(stadium.value(Vec2::new(0, 0)), 0.0);
+        assert_eq!(stadium.value(Vec2::new(0, 1)), 1.0);
+        assert_eq!(stadium.value(Vec2::new(6, 0)), 1.0);
+
+        // Huge rects are clipped
+        let mut huge = Buffer2d::new(Vec2::new(4, 4), 0.0);
+        huge.fill_rounded_rect(Rect::new(Vec2::new(-1_000_000_000, -1_000_000_000), Vec2::new(i32::MAX, i32::MAX)), [10; 4], Vec4::from(1.0));
+        assert!(huge.iter().all(|&val| val == 1.0));
+    }
+
+    #[test]
+    fn rasterizate_smooth_circles() {
+        let mut buffer = Buffer2d::new(Vec2::new(40, 40), BACKGROUND).with_color_space(ColorSpace::Srgb);
+
+        buffer.fill_smooth_circle(Vec2::new(10.0, 10.0), 6.5, Vec4::new(0.2, 0.6, 1.0, 1.0));
+        buffer.draw_smooth_circle(Vec2::new(10.0, 10.0), 8.0, Vec4::new(1.0, 1.0, 1.0, 1.0));
+        buffer.draw_smooth_ellipse(Vec2::new(28.0, 10.0), Vec2::new(9.0, 5.0), Vec4::new(1.0, 0.8, 0.0, 1.0));
+        buffer.draw_smooth_arc(Vec2::new(10.0, 29.0), 8.0, -std::f32::consts::FRAC_PI_2, 1.5 * std::f32::consts::PI, Vec4::new(0.0, 1.0, 0.3, 1.0));
+        buffer.fill_smooth_rounded_rect(
+            Rect::new(Vec2::new(21.0, 21.0), Vec2::new(38.0, 37.0)),
+            [6.0, 0.0, 3.0, 9.0],
+            Vec4::new(1.0, 0.3, 0.3, 1.0),
+        );
+
+        Snapshot::new("rasterizate_smooth_circles").with_tolerance(1).assert(&buffer);
+    }
+
+    #[test]
+    fn rasterizate_smooth_areas() {
+        let mut circle = Buffer2d::new(Vec2::new(20, 20), 0.0);
+        circle.fill_smooth_circle(Vec2::new(10.0, 10.0), 6.0, Vec4::from(1.0));
+
+        let area: f32 = circle.iter().sum();
+        assert!((area - std::f32::consts::PI * 36.0).abs() < 0.5);
+
+        let mut rect = Buffer2d::new(Vec2::new(20, 20), 0.0);
+        rect.fill_smooth_rounded_rect(Rect::new(Vec2::new(2.0, 3.0), Vec2::new(17.0, 13.0)), [4.0; 4], Vec4::from(1.0));
+
+        let area: f32 = rect.iter().sum();
+        let expected = 15.0 * 10.0 - (4.0 - std::f32::consts::PI) * 16.0;
+        assert!((area - expected).abs() < 0.5);
+    }
+
+    #[test]
+    fn rasterizate_text() {
+        let data = test_font("Test Sans", "Regular", &['H', 'i']);
+        let face = Arc::new(FontFace::new(Arc::new(data), 0).unwrap());
+        let font = Font::new("test".to_string(), vec![face], 7, &CharSet::Chars("Hi".to_string())).unwrap();
+
+        // Glyphs are 4x7 boxes with 0.5 bearing, advance 5 and ascender 8
+        let mut buffer = Buffer2d::new(Vec2::new(16, 12), 0.0);
+        buffer.draw_text(&font, Vec2::new(2.0, 2.0), "Hi", Vec4::from(1.0));
+
+        let area: f32 = buffer.iter().sum();
+        assert!((area - 2.0 * 28.0).abs() < 0.1);
+
+        assert!((buffer.value(Vec2::new(2, 5)) - 0.5).abs() < 0.01);
+        assert_eq!(buffer.value(Vec2::new(3, 3)), 1.0);
+        assert_eq!(buffer.value(Vec2::new(3, 2)), 0.0);
+        assert_eq!(buffer.value(Vec2::new(3, 9)), 1.0);
+        assert_eq!(buffer.value(Vec2::new(3, 10)), 0.0);
+        assert!((buffer.value(Vec2::new(6, 5)) - 0.5).abs() < 0.01);
+        assert!((buffer.value(Vec2::new(7, 5)) - 0.5).abs() < 0.01);
+        assert_eq!(buffer.value(Vec2::new(10, 5)), 1.0);
+        assert_eq!(buffer.value(Vec2::new(12, 5)), 0.0);
+    }
+}
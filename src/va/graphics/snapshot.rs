@@ -0,0 +1,263 @@
+//! Golden-image tests of rendered buffers.
+//!
+//! References are PNG files in `tests/snapshots`. Run the tests with
+//! `VA_BLESS=1` to regenerate them. On mismatch the actual, expected and diff
+//! images are written to `target/snapshots`.
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::utils::math::vector::vector2::Vec2;
+use crate::utils::math::vector::vector4::Vec4;
+
+use super::buffer::buffer2d::{Buffer2d, Buffer2dRead};
+use super::color_space::ColorSpace;
+use super::image::codec::{rgba8_bytes, ImageCodec, PngCodec};
+use super::pixel_format::PixelFormat;
+
+const BLESS_VAR: &str = "VA_BLESS";
+
+/// Side of the SSIM window
+const WINDOW_SIZE: usize = 8;
+
+/// Comparison of a rendered buffer with its reference image
+pub struct Snapshot {
+    name: String,
+    tolerance: u8,
+    max_mismatched: usize,
+    min_ssim: f32,
+}
+
+impl Snapshot {
+    /// Exact match by default, SSIM only matters with a tolerance or
+    /// mismatched pixels
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            tolerance: 0,
+            max_mismatched: 0,
+            min_ssim: 0.99,
+        }
+    }
+
+    /// Maximum difference of 8-bit channels of the same pixel
+    pub fn with_tolerance(mut self, tolerance: u8) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Number of pixels allowed to differ by more than the tolerance
+    pub fn with_max_mismatched(mut self, max_mismatched: usize) -> Self {
+        self.max_mismatched = max_mismatched;
+        self
+    }
+
+    /// Minimum mean structural similarity of the images
+    pub fn with_min_ssim(mut self, min_ssim: f32) -> Self {
+        self.min_ssim = min_ssim;
+        self
+    }
+
+    /// Panics if the buffer doesn't match the reference, blesses the
+    /// reference if `VA_BLESS` is set.
+    #[track_caller]
+    pub fn assert<P>(&self, buffer: &Buffer2d<P>)
+        where P: PixelFormat,
+    {
+        let actual: Buffer2d<Vec4<f32>> = buffer.convert();
+        let path = reference_dir().join(format!("{}.png", self.name));
+
+        if env::var_os(BLESS_VAR).is_some() {
+            write_png(&path, &actual).unwrap();
+            return;
+        }
+
+        let expected = match fs::read(&path).map_err(anyhow::Error::from).and_then(|data| PngCodec.decode(&data)) {
+            Ok(val) => val,
+            Err(_) => {
+                let actual_path = self.write_output("actual", &actual);
+                panic!(
+                    "snapshot {:?} has no reference {path:?}, run with {BLESS_VAR}=1 to create it, actual: {actual_path:?}",
+                    self.name,
+                );
+            }
+        };
+
+        let comparison = compare(&actual, &expected, self.tolerance);
+        if let Some(comparison) = &comparison {
+            if comparison.mismatched <= self.max_mismatched && comparison.ssim >= self.min_ssim {
+                return;
+            }
+        }
+
+        let actual_path = self.write_output("actual", &actual);
+        let expected_path = self.write_output("expected", &expected);
+
+        match comparison {
+            Some(comparison) => {
+                let diff_path = self.write_output("diff", &diff_image(&actual, &expected, self.tolerance));
+                panic!(
+                    "snapshot {:?} mismatch: {} pixels differ (max {}), SSIM {:.4} (min {:.4})\n  actual: {actual_path:?}\n  expected: {expected_path:?}\n  diff: {diff_path:?}",
+                    self.name, comparison.mismatched, self.max_mismatched, comparison.ssim, self.min_ssim,
+                );
+            }
+            None => panic!(
+                "snapshot {:?} size mismatch: {:?} != {:?}\n  actual: {actual_path:?}\n  expected: {expected_path:?}",
+                self.name, actual.size(), expected.size(),
+            ),
+        }
+    }
+
+    fn write_output(&self, kind: &str, buffer: &Buffer2d<Vec4<f32>>) -> PathBuf {
+        let path = output_dir().join(format!("{}.{kind}.png", self.name));
+        write_png(&path, buffer).unwrap();
+        path
+    }
+}
+
+/// Compares the buffer with `tests/snapshots/<name>.png` exactly
+#[track_caller]
+pub fn assert_snapshot<P>(name: &str, buffer: &Buffer2d<P>)
+    where P: PixelFormat,
+{
+    Snapshot::new(name).assert(buffer);
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Comparison {
+    pub mismatched: usize,
+    pub ssim: f32,
+}
+
+/// `None` if the sizes differ
+pub fn compare(a: &Buffer2d<Vec4<f32>>, b: &Buffer2d<Vec4<f32>>, tolerance: u8) -> Option<Comparison> {
+    if a.size() != b.size() {
+        return None;
+    }
+
+    let (a_bytes, b_bytes) = (rgba8_bytes(a), rgba8_bytes(b));
+    let mismatched = a_bytes.chunks_exact(4)
+        .zip(b_bytes.chunks_exact(4))
+        .filter(|(a, b)| a.iter().zip(b.iter()).any(|(a, b)| a.abs_diff(*b) > tolerance))
+        .count();
+
+    Some(Comparison {
+        mismatched,
+        ssim: ssim(&to_samples(&a_bytes), &to_samples(&b_bytes), a.size()),
+    })
+}
+
+/// Mean SSIM of all channels over windows of 8x8 pixels
+// https://en.wikipedia.org/wiki/Structural_similarity
+fn ssim(a: &[Vec4<f32>], b: &[Vec4<f32>], size: Vec2<usize>) -> f32 {
+    const C1: f32 = 0.01 * 0.01;
+    const C2: f32 = 0.03 * 0.03;
+
+    let mut sum = 0.0;
+    let mut count = 0;
+
+    for y0 in (0..size.y).step_by(WINDOW_SIZE) {
+        for x0 in (0..size.x).step_by(WINDOW_SIZE) {
+            let indices: Vec<usize> = (y0..(y0 + WINDOW_SIZE).min(size.y))
+                .flat_map(|y| (x0..(x0 + WINDOW_SIZE).min(size.x)).map(move |x| y * size.x + x))
+                .collect();
+            let n = indices.len() as f32;
+
+            for channel in 0..4 {
+                let mean_a = indices.iter().map(|&i| a[i][channel]).sum::<f32>() / n;
+                let mean_b = indices.iter().map(|&i| b[i][channel]).sum::<f32>() / n;
+
+                let (mut var_a, mut var_b, mut covar) = (0.0, 0.0, 0.0);
+                for &i in &indices {
+                    let (da, db) = (a[i][channel] - mean_a, b[i][channel] - mean_b);
+                    var_a += da * da / n;
+                    var_b += db * db / n;
+                    covar += da * db / n;
+                }
+
+                sum += ((2.0 * mean_a * mean_b + C1) * (2.0 * covar + C2))
+                    / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+                count += 1;
+            }
+        }
+    }
+
+    if count == 0 { 1.0 } else { sum / count as f32 }
+}
+
+/// Mismatched pixels are red, brighter for bigger differences, the rest is
+/// the dimmed expected image
+pub fn diff_image(actual: &Buffer2d<Vec4<f32>>, expected: &Buffer2d<Vec4<f32>>, tolerance: u8) -> Buffer2d<Vec4<f32>> {
+    let (a_bytes, b_bytes) = (rgba8_bytes(actual), rgba8_bytes(expected));
+
+    let values = a_bytes.chunks_exact(4).zip(b_bytes.chunks_exact(4)).map(|(a, b)| {
+        let diff = a.iter().zip(b.iter()).map(|(a, b)| a.abs_diff(*b)).max().unwrap_or(0);
+        if diff > tolerance {
+            Vec4::new(0.5 + 0.5 * f32::from(diff) / 255.0, 0.0, 0.0, 1.0)
+        }
+        else {
+            let luma = 0.2126 * f32::from(b[0]) + 0.7152 * f32::from(b[1]) + 0.0722 * f32::from(b[2]);
+            let gray = 0.25 * luma * f32::from(b[3]) / (255.0 * 255.0);
+            Vec4::new(gray, gray, gray, 1.0)
+        }
+    });
+
+    Buffer2d::from_iter(actual.size(), values).with_color_space(ColorSpace::Srgb)
+}
+
+fn to_samples(bytes: &[u8]) -> Vec<Vec4<f32>> {
+    bytes.chunks_exact(4)
+        .map(|val| {
+            let [r, g, b, a] = [val[0], val[1], val[2], val[3]].map(|val| f32::from(val) / 255.0);
+            Vec4::new(r, g, b, a)
+        })
+        .collect()
+}
+
+fn write_png(path: &PathBuf, buffer: &Buffer2d<Vec4<f32>>) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let data = PngCodec.encode(buffer)?;
+    fs::write(path, data).with_context(|| format!("failed to write {path:?}"))
+}
+
+fn reference_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("snapshots")
+}
+
+fn output_dir() -> PathBuf {
+    env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target"))
+        .join("snapshots")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_compare() {
+        let size = Vec2::new(10, 10);
+        let a = Buffer2d::from_iter(size, (0..100).map(|i| Vec4::from(i as f32 / 100.0)));
+
+        let same = compare(&a, &a.clone(), 0).unwrap();
+        assert_eq!(same.mismatched, 0);
+        assert!((same.ssim - 1.0).abs() < 0.0001);
+
+        let b = Buffer2d::from_iter(size, (0..100).map(|i| if i == 43 { Vec4::from(1.0) } else { Vec4::from(i as f32 / 100.0) }));
+        let comparison = compare(&a, &b, 2).unwrap();
+        assert_eq!(comparison.mismatched, 1);
+        assert!(comparison.ssim < 1.0);
+
+        let diff = diff_image(&a, &b, 2);
+        assert_eq!(diff.value(Vec2::new(3, 4)).y, 0.0);
+        assert!(diff.value(Vec2::new(3, 4)).x > 0.5);
+
+        assert!(compare(&a, &Buffer2d::new(Vec2::new(10, 9), Vec4::default()), 0).is_none());
+    }
+}
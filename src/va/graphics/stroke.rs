@@ -0,0 +1,514 @@
+use std::f32::consts::PI;
+
+use crate::utils::math::vector::vector2::Vec2;
+
+use super::path::Contour;
+
+/// Max distance between a round join or cap and its polygon in pixels
+const ROUND_TOLERANCE: f32 = 0.05;
+
+/// Max count of dashes of a contour, shorter dash patterns are stretched
+const MAX_DASHES: usize = 10_000;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Falls back to [`LineJoin::Bevel`] if the miter is longer than the
+    /// miter limit
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+
+    /// Extends the line end by half of the width
+    Square,
+}
+
+/// How a path is outlined by [`stroke_polygons`], defaults follow SVG.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrokeStyle {
+    width: f32,
+    join: LineJoin,
+    miter_limit: f32,
+    cap: LineCap,
+    dashes: Vec<f32>,
+    dash_offset: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl StrokeStyle {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            join: LineJoin::default(),
+            miter_limit: 4.0,
+            cap: LineCap::default(),
+            dashes: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    /// Max ratio of the miter length to the width
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// Alternating lengths of dashes and gaps, an odd count is repeated
+    /// twice. `offset` is the distance into the pattern at the start of
+    /// every contour. Invalid patterns draw a solid line.
+    pub fn with_dashes(mut self, dashes: Vec<f32>, offset: f32) -> Self {
+        self.dashes = dashes;
+        self.dash_offset = offset;
+        self
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn join(&self) -> LineJoin {
+        self.join
+    }
+
+    pub fn miter_limit(&self) -> f32 {
+        self.miter_limit
+    }
+
+    pub fn cap(&self) -> LineCap {
+        self.cap
+    }
+
+    pub fn dashes(&self) -> &[f32] {
+        &self.dashes
+    }
+
+    pub fn dash_offset(&self) -> f32 {
+        self.dash_offset
+    }
+
+    fn dash_pattern(&self) -> Option<Vec<f32>> {
+        let total: f32 = self.dashes.iter().sum();
+        if self.dashes.is_empty() || self.dashes.iter().any(|&dash| dash.is_nan() || dash < 0.0) || total.is_nan() || total <= 0.0 {
+            return None;
+        }
+
+        let mut pattern = self.dashes.clone();
+        if pattern.len() % 2 == 1 {
+            pattern.extend_from_slice(&self.dashes);
+        }
+
+        Some(pattern)
+    }
+}
+
+/// Returns convex polygons covering the stroke of the contours.
+///
+/// Every polygon is counter-clockwise, so overlapping parts don't cancel each
+/// other when filled with [`FillRule::NonZero`].
+///
+/// [`FillRule::NonZero`]: super::coverage_accumulator::FillRule::NonZero
+pub fn stroke_polygons(contours: &[Contour], style: &StrokeStyle) -> Vec<Vec<Vec2<f32>>> {
+    let mut stroker = Stroker {
+        style,
+        half_width: style.width * 0.5,
+        polygons: Vec::new(),
+    };
+
+    if stroker.half_width.is_nan() || stroker.half_width <= 0.0 {
+        return Vec::new();
+    }
+
+    let pattern = style.dash_pattern();
+    for contour in contours {
+        match &pattern {
+            Some(pattern) => {
+                for dash in dash_contour(contour, pattern, style.dash_offset) {
+                    stroker.stroke(&dash, false);
+                }
+            }
+            None => stroker.stroke(contour.points(), contour.is_closed()),
+        }
+    }
+
+    stroker.polygons
+}
+
+struct Stroker<'a> {
+    style: &'a StrokeStyle,
+    half_width: f32,
+    polygons: Vec<Vec<Vec2<f32>>>,
+}
+
+impl<'a> Stroker<'a> {
+    fn stroke(&mut self, points: &[Vec2<f32>], closed: bool) {
+        match points.len() {
+            0 => (),
+            1 => self.dot(points[0]),
+            _ => self.stroke_polyline(points, closed),
+        }
+    }
+
+    /// Zero length lines are drawn only with round and square caps
+    fn dot(&mut self, point: Vec2<f32>) {
+        match self.style.cap {
+            LineCap::Butt => (),
+            LineCap::Round => {
+                let polygon = self.arc(point, 0.0, 2.0 * PI);
+                self.push(polygon);
+            }
+            LineCap::Square => {
+                let hw = self.half_width;
+                self.push(vec![
+                    point + Vec2::new(-hw, -hw),
+                    point + Vec2::new(hw, -hw),
+                    point + Vec2::new(hw, hw),
+                    point + Vec2::new(-hw, hw),
+                ]);
+            }
+        }
+    }
+
+    fn stroke_polyline(&mut self, points: &[Vec2<f32>], closed: bool) {
+        let count = if closed { points.len() } else { points.len() - 1 };
+
+        for i in 0..count {
+            let p0 = points[i];
+            let p1 = points[(i + 1) % points.len()];
+            let normal = self.normal(p1 - p0);
+
+            self.push(vec![p0 + normal, p1 + normal, p1 - normal, p0 - normal]);
+        }
+
+        let joins = if closed { 0..points.len() } else { 1..points.len() - 1 };
+        for i in joins {
+            let prev = points[(i + points.len() - 1) % points.len()];
+            let next = points[(i + 1) % points.len()];
+            self.join(prev, points[i], next);
+        }
+
+        if !closed {
+            self.cap(points[1], points[0]);
+            self.cap(points[points.len() - 2], points[points.len() - 1]);
+        }
+    }
+
+    /// Fills the gap on the outer side of the corner at `point`
+    fn join(&mut self, prev: Vec2<f32>, point: Vec2<f32>, next: Vec2<f32>) {
+        let d0 = (point - prev).normalize();
+        let d1 = (next - point).normalize();
+
+        let cross = d0.x * d1.y - d0.y * d1.x;
+        if cross.abs() < 1.0e-6 && d0.dot(d1) > 0.0 {
+            return;
+        }
+
+        // The outer side is opposite to the turn
+        let side = if cross > 0.0 { -1.0 } else { 1.0 };
+        let n0 = self.normal(d0) * side;
+        let n1 = self.normal(d1) * side;
+
+        match self.style.join {
+            LineJoin::Bevel => self.push(vec![point, point + n0, point + n1]),
+            LineJoin::Miter => {
+                // Cosine of half of the turn angle, the miter ratio is its inverse
+                let cos_half = ((1.0 + d0.dot(d1)) * 0.5).max(0.0).sqrt();
+                if cos_half > 1.0e-6 && 1.0 / cos_half <= self.style.miter_limit {
+                    let tip = point + (n0 + n1).normalize() * (self.half_width / cos_half);
+                    self.push(vec![point, point + n0, tip, point + n1]);
+                }
+                else {
+                    self.push(vec![point, point + n0, point + n1]);
+                }
+            }
+            LineJoin::Round => {
+                let start = n0.y.atan2(n0.x);
+                let mut sweep = n1.y.atan2(n1.x) - start;
+                if sweep > PI {
+                    sweep -= 2.0 * PI;
+                }
+                else if sweep < -PI {
+                    sweep += 2.0 * PI;
+                }
+
+                let mut polygon = vec![point];
+                polygon.extend(self.arc(point, start, sweep));
+                self.push(polygon);
+            }
+        }
+    }
+
+    /// Cap at `end` of the line from `prev`
+    fn cap(&mut self, prev: Vec2<f32>, end: Vec2<f32>) {
+        let direction = (end - prev).normalize();
+        let normal = self.normal(direction);
+
+        match self.style.cap {
+            LineCap::Butt => (),
+            LineCap::Round => {
+                let start = normal.y.atan2(normal.x);
+                let polygon = self.arc(end, start, -PI);
+                self.push(polygon);
+            }
+            LineCap::Square => {
+                let extension = direction * self.half_width;
+                self.push(vec![end + normal, end + normal + extension, end - normal + extension, end - normal]);
+            }
+        }
+    }
+
+    /// Left normal with the length of half of the width
+    fn normal(&self, direction: Vec2<f32>) -> Vec2<f32> {
+        let direction = direction.normalize();
+        Vec2::new(-direction.y, direction.x) * self.half_width
+    }
+
+    /// Points of the arc with the radius of half of the width
+    fn arc(&self, center: Vec2<f32>, start: f32, sweep: f32) -> Vec<Vec2<f32>> {
+        let radius = self.half_width;
+
+        // Angle step keeping chords closer than the tolerance to the arc
+        let step = 2.0 * (1.0 - (ROUND_TOLERANCE / radius).min(1.0)).acos();
+        let count = (sweep.abs() / step.max(1.0e-3)).ceil().max(2.0) as usize;
+
+        (0..=count)
+            .map(|i| {
+                let angle = start + sweep * i as f32 / count as f32;
+                center + Vec2::new(angle.cos(), angle.sin()) * radius
+            })
+            .collect()
+    }
+
+    fn push(&mut self, mut polygon: Vec<Vec2<f32>>) {
+        let area = signed_area(&polygon);
+        if area.abs() < 1.0e-6 {
+            return;
+        }
+
+        if area < 0.0 {
+            polygon.reverse();
+        }
+
+        self.polygons.push(polygon);
+    }
+}
+
+// https://en.wikipedia.org/wiki/Shoelace_formula
+fn signed_area(polygon: &[Vec2<f32>]) -> f32 {
+    let mut area = 0.0;
+    for (i, p0) in polygon.iter().enumerate() {
+        let p1 = polygon[(i + 1) % polygon.len()];
+        area += p0.x * p1.y - p1.x * p0.y;
+    }
+
+    area * 0.5
+}
+
+/// Splits the contour into dashes, a closed contour is split including the
+/// closing line. `pattern` has an even length.
+fn dash_contour(contour: &Contour, pattern: &[f32], offset: f32) -> Vec<Vec<Vec2<f32>>> {
+    let points = contour.points();
+    if points.len() < 2 {
+        return vec![points.to_vec()];
+    }
+
+    let count = if contour.is_closed() { points.len() } else { points.len() - 1 };
+    let lenght: f32 = (0..count)
+        .map(|i| (points[(i + 1) % points.len()] - points[i]).lenght())
+        .sum();
+
+    // Tiny dashes wouldn't advance the position in f32
+    let total: f32 = pattern.iter().sum();
+    let min_total = lenght * (pattern.len() / 2) as f32 / MAX_DASHES as f32;
+    let scale = (min_total / total).max(1.0);
+    let pattern: Vec<f32> = pattern.iter().map(|&dash| dash * scale).collect();
+    let offset = offset * scale;
+    let total = total * scale;
+
+    // Position in the pattern
+    let mut index = 0;
+    let mut remaining = offset.rem_euclid(total);
+    while remaining > 0.0 && remaining >= pattern[index] {
+        remaining -= pattern[index];
+        index = (index + 1) % pattern.len();
+    }
+    remaining = pattern[index] - remaining;
+
+    let mut dashes = Vec::new();
+    let mut dash = Vec::new();
+    let starts_with_dash = index % 2 == 0;
+    if starts_with_dash {
+        dash.push(points[0]);
+    }
+
+    for i in 0..count {
+        let p0 = points[i];
+        let p1 = points[(i + 1) % points.len()];
+        let length = (p1 - p0).lenght();
+        let mut position = 0.0;
+
+        while length - position > remaining {
+            position += remaining;
+            let point = p0.mix(p1, position / length);
+
+            if index % 2 == 0 {
+                dash.push(point);
+                dashes.push(std::mem::take(&mut dash));
+            }
+            else {
+                dash.push(point);
+            }
+
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        }
+
+        remaining -= length - position;
+        if index % 2 == 0 {
+            dash.push(p1);
+        }
+    }
+
+    if index % 2 == 0 && !dash.is_empty() {
+        // The dash crossing the start of a closed contour is continuous
+        if contour.is_closed() && starts_with_dash && !dashes.is_empty() {
+            dash.extend(dashes.remove(0));
+        }
+
+        dashes.push(dash);
+    }
+
+    // Zero length dashes are drawn as dots by round and square caps
+    for dash in &mut dashes {
+        dash.dedup();
+    }
+
+    dashes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::path::Path;
+    use crate::utils::math::is_equal::IsCopyTypeEqual;
+
+    use super::*;
+
+    const EPSILON: f64 = 0.001;
+
+    fn area(polygons: &[Vec<Vec2<f32>>]) -> f64 {
+        polygons.iter().map(|polygon| f64::from(signed_area(polygon))).sum()
+    }
+
+    #[test]
+    fn stroke_line_caps() {
+        let contours = Path::builder()
+            .move_to(Vec2::new(0.0, 0.0))
+            .line_to(Vec2::new(10.0, 0.0))
+            .build()
+            .flatten();
+
+        let butt = stroke_polygons(&contours, &StrokeStyle::new(2.0));
+        assert!(area(&butt).is_equal(20.0, EPSILON));
+
+        let square = stroke_polygons(&contours, &StrokeStyle::new(2.0).with_cap(LineCap::Square));
+        assert!(area(&square).is_equal(24.0, EPSILON));
+
+        let round = stroke_polygons(&contours, &StrokeStyle::new(2.0).with_cap(LineCap::Round));
+        // Round caps are approximated by polygons inside of the circle
+        let round_area = area(&round) - 20.0;
+        assert!(round_area > std::f64::consts::PI * 0.9 && round_area < std::f64::consts::PI);
+
+        for polygon in butt.iter().chain(&square).chain(&round) {
+            assert!(signed_area(polygon) > 0.0);
+        }
+    }
+
+    #[test]
+    fn stroke_joins() {
+        let contours = Path::builder()
+            .move_to(Vec2::new(0.0, 0.0))
+            .line_to(Vec2::new(10.0, 0.0))
+            .line_to(Vec2::new(10.0, 10.0))
+            .build()
+            .flatten();
+
+        // Two segments and the join of the right angle corner
+        let miter = stroke_polygons(&contours, &StrokeStyle::new(2.0));
+        assert_eq!(miter.len(), 3);
+        assert!(f64::from(signed_area(&miter[2])).is_equal(1.0, EPSILON));
+
+        let bevel = stroke_polygons(&contours, &StrokeStyle::new(2.0).with_join(LineJoin::Bevel));
+        assert!(f64::from(signed_area(&bevel[2])).is_equal(0.5, EPSILON));
+
+        // The miter ratio of the right angle is sqrt(2)
+        let limited = stroke_polygons(&contours, &StrokeStyle::new(2.0).with_miter_limit(1.4));
+        assert!(f64::from(signed_area(&limited[2])).is_equal(0.5, EPSILON));
+    }
+
+    #[test]
+    fn stroke_dashes() {
+        let contour = Path::builder()
+            .move_to(Vec2::new(0.0, 0.0))
+            .line_to(Vec2::new(10.0, 0.0))
+            .build()
+            .flatten();
+
+        let dashes = dash_contour(&contour[0], &[3.0, 1.0], 0.0);
+        assert_eq!(dashes.len(), 3);
+        assert_eq!(dashes[0], vec![Vec2::new(0.0, 0.0), Vec2::new(3.0, 0.0)]);
+        assert_eq!(dashes[2], vec![Vec2::new(8.0, 0.0), Vec2::new(10.0, 0.0)]);
+
+        let dashes = dash_contour(&contour[0], &[3.0, 1.0], 2.0);
+        assert_eq!(dashes[0], vec![Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0)]);
+        assert_eq!(dashes[1], vec![Vec2::new(2.0, 0.0), Vec2::new(5.0, 0.0)]);
+
+        let style = StrokeStyle::new(2.0).with_dashes(vec![3.0, 1.0], 0.0);
+        let polygons = stroke_polygons(&contour, &style);
+        assert!(area(&polygons).is_equal(16.0, EPSILON));
+
+        // The last dash of the closed contour continues into the first one
+        let square = Path::builder()
+            .move_to(Vec2::new(0.0, 0.0))
+            .line_to(Vec2::new(10.0, 0.0))
+            .line_to(Vec2::new(10.0, 10.0))
+            .line_to(Vec2::new(0.0, 10.0))
+            .close()
+            .build()
+            .flatten();
+
+        let dashes = dash_contour(&square[0], &[6.0, 3.0], 0.0);
+        assert_eq!(dashes.len(), 4);
+        assert_eq!(dashes[0], vec![Vec2::new(9.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(10.0, 5.0)]);
+        let expected = [Vec2::new(0.0, 4.0), Vec2::new(0.0, 0.0), Vec2::new(6.0, 0.0)];
+        assert_eq!(dashes[3].len(), expected.len());
+        for (point, expected) in dashes[3].iter().zip(expected) {
+            assert!(point.is_equal(expected, EPSILON));
+        }
+
+        // Tiny dashes are stretched
+        let dashes = dash_contour(&contour[0], &[1.0e-9, 1.0e-9], 0.0);
+        assert_eq!(dashes.len(), MAX_DASHES);
+        assert!((dashes[1][0].x - 1.0e-3).abs() < 1.0e-6);
+    }
+}
@@ -0,0 +1,435 @@
+use crate::utils::cast::Cast;
+use crate::utils::math::geometry::rect::Rect;
+use crate::utils::math::vector::vector2::Vec2;
+
+use super::font::{CharInfo, Font};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+
+    /// Stretches whitespaces, so lines fill the max width. The last line of a
+    /// paragraph is aligned left.
+    Justify,
+}
+
+/// Glyph with its pen position on the baseline.
+#[derive(Clone, Copy, Debug)]
+pub struct PositionedGlyph {
+    char: char,
+    char_info: CharInfo,
+    position: Vec2<f32>,
+}
+
+impl PositionedGlyph {
+    pub fn char(&self) -> char {
+        self.char
+    }
+
+    pub fn char_info(&self) -> CharInfo {
+        self.char_info
+    }
+
+    pub fn position(&self) -> Vec2<f32> {
+        self.position
+    }
+
+    /// Quad of the glyph image, textured by `char_info().uv_rect()`
+    pub fn rect(&self) -> Rect<f32> {
+        let atlas_rect = self.char_info.atlas_rect();
+        let size: Vec2<f32> = (atlas_rect.p2 - atlas_rect.p1).cast();
+        let p1 = self.position + self.char_info.bearing();
+
+        Rect::new(p1, p1 + size)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TextLayoutBuilder {
+    max_width: Option<f32>,
+    align: TextAlign,
+}
+
+impl Default for TextLayoutBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextLayoutBuilder {
+    pub fn new() -> Self {
+        Self {
+            max_width: None,
+            align: TextAlign::default(),
+        }
+    }
+
+    /// Words are moved to the next line if the line is wider than `max_width`
+    pub fn with_max_width(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    pub fn with_align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// The origin is the top-left corner of the first line, Y axis is
+    /// directed down. Characters missing in the font are skipped.
+    pub fn build(&self, font: &Font, text: &str) -> TextLayout {
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            let mut line = Line::default();
+            let mut prev: Option<CharInfo> = None;
+
+            for char in paragraph.chars() {
+                let char_info = match font.chars_info.get(&char) {
+                    Some(val) => *val,
+                    None => continue,
+                };
+
+                let kerning = match prev {
+                    Some(prev) => font.kerning(&prev, &char_info),
+                    None => 0.0,
+                };
+
+                let mut x = line.pen + kerning;
+                let is_overflowed = self.max_width.is_some_and(|max_width| {
+                    x + char_info.advance_width() > max_width
+                });
+
+                if is_overflowed && !char.is_whitespace() && !line.glyphs.is_empty() {
+                    let next_line = line.break_line();
+                    lines.push(line);
+                    line = next_line;
+
+                    // The kerning with the last glyph of the previous line is
+                    // dropped, the moved word is kerned with the character
+                    x = match line.glyphs.last() {
+                        Some(last) => line.pen + font.kerning(&last.char_info, &char_info),
+                        None => 0.0,
+                    };
+                }
+
+                if char.is_whitespace() {
+                    line.break_index = Some(line.glyphs.len());
+                }
+
+                line.glyphs.push(PositionedGlyph {
+                    char,
+                    char_info,
+                    position: Vec2::new(x, 0.0),
+                });
+                line.pen = x + char_info.advance_width();
+                prev = Some(char_info);
+            }
+
+            line.is_paragraph_end = true;
+            lines.push(line);
+        }
+
+        self.align_lines(font, lines)
+    }
+
+    fn align_lines(&self, font: &Font, lines: Vec<Line>) -> TextLayout {
+        let line_count = lines.len();
+        let widths: Vec<f32> = lines.iter().map(Line::width).collect();
+
+        let max_width = self.max_width.unwrap_or_else(|| {
+            widths.iter().copied().fold(0.0, f32::max)
+        });
+
+        let mut glyphs = Vec::new();
+        let mut rect: Option<Rect<f32>> = None;
+
+        for (i, (line, width)) in lines.into_iter().zip(widths).enumerate() {
+            let baseline = font.ascender() + i as f32 * font.line_height();
+
+            let offset = match self.align {
+                TextAlign::Left | TextAlign::Justify => 0.0,
+                TextAlign::Center => (max_width - width) * 0.5,
+                TextAlign::Right => max_width - width,
+            };
+
+            let spaces = line.glyphs.iter()
+                .filter(|glyph| glyph.char.is_whitespace())
+                .count();
+
+            let space_stretch = if self.align == TextAlign::Justify && !line.is_paragraph_end && spaces > 0 {
+                (max_width - width).max(0.0) / spaces as f32
+            }
+            else {
+                0.0
+            };
+
+            let mut stretch = 0.0;
+            let mut right = offset;
+            for mut glyph in line.glyphs {
+                glyph.position = Vec2::new(glyph.position.x + offset + stretch, baseline);
+
+                if glyph.char.is_whitespace() {
+                    stretch += space_stretch;
+                }
+                else {
+                    right = right.max(glyph.position.x + glyph.char_info.advance_width());
+                }
+
+                if !glyph.char_info.is_empty() {
+                    glyphs.push(glyph);
+                }
+            }
+
+            let line_rect = Rect::new(
+                Vec2::new(offset, baseline - font.ascender()),
+                Vec2::new(right, baseline + font.descender()),
+            );
+
+            rect = Some(match rect {
+                Some(rect) => Rect::new(
+                    Vec2::new(rect.p1.x.min(line_rect.p1.x), rect.p1.y),
+                    Vec2::new(rect.p2.x.max(line_rect.p2.x), line_rect.p2.y),
+                ),
+                None => line_rect,
+            });
+        }
+
+        TextLayout {
+            glyphs,
+            rect: rect.unwrap_or_default(),
+            line_count,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Line {
+    glyphs: Vec<PositionedGlyph>,
+    pen: f32,
+
+    /// Index of the last whitespace, the line can be broken there
+    break_index: Option<usize>,
+    is_paragraph_end: bool,
+}
+
+impl Line {
+    /// Width without trailing whitespaces
+    fn width(&self) -> f32 {
+        self.glyphs.iter()
+            .rev()
+            .find(|glyph| !glyph.char.is_whitespace())
+            .map_or(0.0, |glyph| glyph.position.x + glyph.char_info.advance_width())
+    }
+
+    /// Moves the last word into a new line. Words longer than the line are
+    /// broken between characters.
+    fn break_line(&mut self) -> Line {
+        let glyphs = match self.break_index.take() {
+            Some(i) => {
+                let glyphs = self.glyphs.split_off(i + 1);
+
+                // Whitespaces at the line end aren't visible
+                while self.glyphs.last().is_some_and(|glyph| glyph.char.is_whitespace()) {
+                    self.glyphs.pop();
+                }
+
+                glyphs
+            }
+            None => Vec::new(),
+        };
+
+        let shift = glyphs.first().map_or(0.0, |glyph| glyph.position.x);
+        let glyphs: Vec<PositionedGlyph> = glyphs.into_iter()
+            .map(|mut glyph| {
+                glyph.position.x -= shift;
+                glyph
+            })
+            .collect();
+
+        let pen = if glyphs.is_empty() {
+            0.0
+        }
+        else {
+            self.pen - shift
+        };
+
+        Line {
+            glyphs,
+            pen,
+            break_index: None,
+            is_paragraph_end: false,
+        }
+    }
+}
+
+/// Text positioned by [`TextLayoutBuilder`].
+#[derive(Clone, Debug)]
+pub struct TextLayout {
+    glyphs: Vec<PositionedGlyph>,
+    rect: Rect<f32>,
+    line_count: usize,
+}
+
+impl TextLayout {
+    /// Glyphs with images, whitespaces are skipped
+    pub fn glyphs(&self) -> &[PositionedGlyph] {
+        &self.glyphs
+    }
+
+    /// Bounds of all lines, from the ascender of the first line to the
+    /// descender of the last one
+    pub fn rect(&self) -> Rect<f32> {
+        self.rect
+    }
+
+    pub fn size(&self) -> Vec2<f32> {
+        self.rect.p2 - self.rect.p1
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::graphics::char_set::CharSet;
+    use crate::graphics::font_registry::tests::{test_font, test_font_with_kerning};
+    use crate::graphics::font_registry::FontFace;
+
+    use super::*;
+
+    /// Glyphs have the advance of 5 px, the line height is 10 px with the
+    /// ascender of 8 px
+    fn font() -> Font {
+        let data = test_font("Test Sans", "Regular", &[' ', '\t', 'H', 'a', 'b']);
+        let face = Arc::new(FontFace::new(Arc::new(data), 0).unwrap());
+        Font::new("test".to_string(), vec![face], 7, &CharSet::Chars(" \tHab".to_string())).unwrap()
+    }
+
+    /// Text of every line with the X position of its first glyph
+    fn lines(layout: &TextLayout) -> Vec<(String, f32)> {
+        let mut lines: Vec<(String, f32, f32)> = Vec::new();
+        for glyph in layout.glyphs() {
+            match lines.last_mut() {
+                Some((text, _, y)) if *y == glyph.position().y => text.push(glyph.char()),
+                _ => lines.push((glyph.char().to_string(), glyph.position().x, glyph.position().y)),
+            }
+        }
+
+        lines.into_iter().map(|(text, x, _)| (text, x)).collect()
+    }
+
+    #[test]
+    fn text_layout_word_wrap() {
+        let font = font();
+        assert_eq!(font.chars_info[&'a'].advance_width(), 5.0);
+        assert_eq!(font.line_height(), 10.0);
+
+        let layout = TextLayoutBuilder::new().with_max_width(22.0).build(&font, "ab ab aab");
+        assert_eq!(layout.line_count(), 3);
+        assert_eq!(lines(&layout), vec![("ab".to_string(), 0.0), ("ab".to_string(), 0.0), ("aab".to_string(), 0.0)]);
+
+        // Baselines of the lines
+        let y: Vec<f32> = layout.glyphs().iter().map(|glyph| glyph.position().y).collect();
+        assert_eq!((y[0], y[2], y[6]), (8.0, 18.0, 28.0));
+
+        // Words longer than the line are broken between characters
+        let layout = TextLayoutBuilder::new().with_max_width(12.0).build(&font, "aaaaa");
+        assert_eq!(lines(&layout), vec![("aa".to_string(), 0.0), ("aa".to_string(), 0.0), ("a".to_string(), 0.0)]);
+
+        // Paragraphs are never joined
+        let layout = TextLayoutBuilder::new().build(&font, "ab\n\nb");
+        assert_eq!(layout.line_count(), 3);
+        assert_eq!(layout.glyphs()[2].position().y, 28.0);
+    }
+
+    #[test]
+    fn text_layout_align() {
+        let font = font();
+        let layout = |align| TextLayoutBuilder::new().with_align(align).build(&font, "abab\nab");
+
+        assert_eq!(lines(&layout(TextAlign::Left))[1].1, 0.0);
+        assert_eq!(lines(&layout(TextAlign::Center))[1].1, 5.0);
+        assert_eq!(lines(&layout(TextAlign::Right))[1].1, 10.0);
+
+        let layout = layout(TextAlign::Right);
+        assert_eq!(layout.rect(), Rect::new(Vec2::new(0.0, 0.0), Vec2::new(20.0, 20.0)));
+        assert_eq!(layout.size(), Vec2::new(20.0, 20.0));
+
+        // Spaces are stretched except in the last line of the paragraph
+        let layout = TextLayoutBuilder::new()
+            .with_max_width(27.0)
+            .with_align(TextAlign::Justify)
+            .build(&font, "ab ab ab");
+        let x: Vec<f32> = layout.glyphs().iter().map(|glyph| glyph.position().x).collect();
+        assert_eq!(x, vec![0.0, 5.0, 10.0, 17.0, 22.0, 0.0, 5.0]);
+        assert_eq!(layout.rect().p2.x, 27.0);
+
+        // Any whitespace which wraps lines is stretched
+        let layout = TextLayoutBuilder::new()
+            .with_max_width(42.0)
+            .with_align(TextAlign::Justify)
+            .build(&font, "ab\tab ab ab");
+        let x: Vec<f32> = layout.glyphs().iter().map(|glyph| glyph.position().x).take(9).collect();
+        assert_eq!(x, vec![0.0, 5.0, 10.0, 16.0, 21.0, 26.0, 32.0, 37.0, 0.0]);
+    }
+
+    #[test]
+    fn text_layout_kerning() {
+        // -1 px between "a" and "b"
+        let data = test_font_with_kerning("Test Sans", "Regular", &[' ', 'H', 'a', 'b'], &[('a', 'b', -100)]);
+        let face = Arc::new(FontFace::new(Arc::new(data), 0).unwrap());
+        let font = Font::new("test".to_string(), vec![face], 7, &CharSet::Chars(" Hab".to_string())).unwrap();
+
+        let (a, b) = (font.chars_info[&'a'], font.chars_info[&'b']);
+        assert_eq!(font.kerning(&a, &b), -1.0);
+        assert_eq!(font.kerning(&b, &a), 0.0);
+
+        let layout = TextLayoutBuilder::new().build(&font, "abab");
+        let x: Vec<f32> = layout.glyphs().iter().map(|glyph| glyph.position().x).collect();
+        assert_eq!(x, vec![0.0, 4.0, 9.0, 13.0]);
+        assert_eq!(layout.size().x, 18.0);
+
+        // The moved word keeps its kerning
+        let layout = TextLayoutBuilder::new().with_max_width(12.0).build(&font, "ab ab");
+        assert_eq!(lines(&layout), vec![("ab".to_string(), 0.0), ("ab".to_string(), 0.0)]);
+        assert_eq!(layout.glyphs()[3].position().x, 4.0);
+    }
+
+    #[test]
+    fn text_layout_break_line() {
+        let font = font();
+        let glyph = |char, x| PositionedGlyph {
+            char,
+            char_info: font.chars_info[&char],
+            position: Vec2::new(x, 0.0),
+        };
+
+        let mut line = Line {
+            glyphs: vec![glyph('a', 0.0), glyph(' ', 5.0), glyph(' ', 10.0), glyph('a', 15.0), glyph('b', 20.0)],
+            pen: 25.0,
+            break_index: Some(2),
+            is_paragraph_end: false,
+        };
+        assert_eq!(line.width(), 25.0);
+
+        // Trailing whitespaces are removed and the word is moved to the start
+        let next = line.break_line();
+        assert_eq!(line.glyphs.len(), 1);
+        assert_eq!(line.width(), 5.0);
+        assert_eq!(next.glyphs.iter().map(|glyph| glyph.position.x).collect::<Vec<f32>>(), vec![0.0, 5.0]);
+        assert_eq!((next.pen, next.width()), (10.0, 10.0));
+
+        // Without a whitespace the next line is empty
+        let mut line = Line { break_index: None, ..next };
+        let next = line.break_line();
+        assert!(next.glyphs.is_empty());
+        assert_eq!((next.pen, line.glyphs.len()), (0.0, 2));
+    }
+}
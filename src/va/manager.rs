@@ -3,30 +3,32 @@ use std::any::TypeId;
 use std::cell::{RefCell, Ref};
 use std::collections::HashMap;
 use std::fs;
-use std::io::{self, Cursor};
+use std::io;
 use std::path::Path;
 use std::rc::Rc;
 use std::sync::Arc;
 
 use anyhow::{bail, Context};
-use log::info;
-use png::{BitDepth, ColorType};
+use log::warn;
+use png::ColorType;
 use thiserror::Error;
 
 use vulkano::device::Device;
-use vulkano::format::Format;
 use vulkano::image::view::ImageView;
 use vulkano::image::{ImageDimensions, ImmutableImage, MipmapsCount};
 use vulkano::pipeline::GraphicsPipeline;
 use vulkano::shader::{ShaderCreationError, ShaderModule};
 
-use crate::graphics::buffer::buffer2d::{Buffer2d, Buffer2dRead, save_buffer};
+use crate::graphics::buffer::buffer2d::{Buffer2d, Buffer2dRead};
+use crate::graphics::char_set::CharSet;
+use crate::graphics::color_space::ColorSpace;
 use crate::graphics::font::{self, Font};
-use crate::graphics::image::save_image;
-use crate::utils::cast::Cast;
-use crate::utils::math::vector::vector2::Vec2;
+use crate::graphics::font_debug::FontDebugOptions;
+use crate::graphics::font_registry::{FontFace, FontRegistry};
+use crate::graphics::image::codec::{ImageCodec, ImageCodecs};
+use crate::graphics::image::{decode_png, save_image, PngColorMetadata};
+use crate::graphics::pixel_format::{PixelFormat, Rgba16F, Rgba8};
 use crate::utils::math::vector::vector4::Vec4;
-use crate::utils::number::{Number, Float};
 
 use super::graphics::mesh::Mesh;
 use super::graphics::Graphics;
@@ -37,6 +39,9 @@ pub struct Manager {
 
     shader_modules: HashMap<String, Arc<ShaderModule>>,
     graphics_pipelines: RefCell<HashMap<TypeId, Arc<GraphicsPipeline>>>,
+    font_registry: RefCell<FontRegistry>,
+    font_debug_options: RefCell<Option<FontDebugOptions>>,
+    image_codecs: RefCell<ImageCodecs>,
 }
 
 #[derive(Debug, Error)]
@@ -63,6 +68,9 @@ impl Manager {
 
             shader_modules: HashMap::new(),
             graphics_pipelines: RefCell::default(),
+            font_registry: RefCell::default(),
+            font_debug_options: RefCell::default(),
+            image_codecs: RefCell::default(),
         })
     }
 
@@ -151,92 +159,66 @@ impl Manager {
         }
     }
 
-    fn parse_png(data: &[u8]) -> anyhow::Result<(ImageDimensions, Vec<u8>)> {
-        let cursor = Cursor::new(data);
+    /// See [`decode_png`]
+    pub fn parse_png(data: &[u8]) -> anyhow::Result<Buffer2d<Vec4<f32>>> {
+        decode_png(data).context("failed to decode PNG image")
+    }
 
-        let decoder = png::Decoder::new(cursor);
-        let mut reader = decoder.read_info()?;
+    /// Codecs added later are checked first, so built-in ones can be replaced
+    pub fn add_image_codec(&self, codec: Box<dyn ImageCodec>) {
+        self.image_codecs.borrow_mut().add(codec);
+    }
 
-        let info = reader.info();
-        let dimensions = ImageDimensions::Dim2d {
-            width: info.width,
-            height: info.height,
-            array_layers: 1,
-        };
-        let image_bit_depth = info.bit_depth;
-        let image_color_type = info.color_type;
-
-        let mut image_data = vec![0; reader.output_buffer_size()];
-        let output_info = reader.next_frame(&mut image_data)?;
-        image_data.resize(output_info.buffer_size(), 0);
-
-        Ok((dimensions, match (image_bit_depth, image_color_type) {
-            (BitDepth::Eight, ColorType::Rgb) => {
-                info!("Using R8G8B8 format texture");
-
-                let pixel_count = image_data.len() / 3;
-                let mut new_image = Vec::with_capacity(pixel_count * 4);
-                for i in 0..pixel_count {
-                    new_image.push(image_data[3 * i]);
-                    new_image.push(image_data[3 * i + 1]);
-                    new_image.push(image_data[3 * i + 2]);
-                    new_image.push(u8::MAX);
-                }
-
-                new_image
-            }
-            (BitDepth::Eight, ColorType::Rgba) => image_data,
-            (bit_depth, color_type) => bail!(
-                "unsupporting image type (BitDepth::{:?}, ColorType::{:?})",
-                bit_depth,
-                color_type
-            ),
-        }))
+    /// The format is detected by magic bytes
+    pub fn decode_image(&self, data: &[u8]) -> anyhow::Result<Buffer2d<Vec4<f32>>> {
+        self.image_codecs.borrow().decode(data)
     }
 
-    /// Loads PNG transparent images(use only R8G8B8A8 format!) and creates the image view
+    pub fn load_image_buffer(&self, filename: &str) -> anyhow::Result<Buffer2d<Vec4<f32>>> {
+        let data = self.load_binary_relative(filename)
+            .with_context(|| format!("failed to load {filename} image binary"))?;
+
+        self.decode_image(&data).with_context(|| format!("failed to load {filename} image"))
+    }
+
+    /// Loads an image of any registered format and creates the image view. The
+    /// image is uploaded in 8-bit RGBA format, images with more precise values
+    /// (e.g. 16-bit PNG) in half float RGBA format. Use
+    /// [`Manager::load_image_buffer`] with
+    /// [`Manager::load_image_from_memory_as`] for other formats.
     /// # Panics
     ///
     /// Function panics if not setup queue
     pub fn load_image(&self, filename: &str) -> anyhow::Result<Arc<ImageView<ImmutableImage>>> {
-        todo!();
-        // let (dimensions, image_data) = Self::parse_png(
-        //     &self.load_binary_relative(filename).context("failed to load image binary")?
-        // )?;
-
-        // const FORMAT: Format = Format::R8G8B8A8_SRGB;
-        // let (image, future) = match ImmutableImage::from_iter(
-        //     image_data.into_iter(),
-        //     dimensions,
-        //     MipmapsCount::One,
-        //     FORMAT, // TODO choose srgb or default
-        //     self.graphics.queue().expect("no available queue"),
-        // ) {
-        //     Ok(val) => val,
-        //     Err(err) => bail!(format!("{} ({:?})", err, FORMAT)),
-        // };
-        // dbg!();
-
-        // self.graphics.new_future(Box::new(future));
-
-        // Ok(ImageView::new(image)?)
+        let buffer = self.load_image_buffer(filename)?;
+        if buffer.fits_rgba8() {
+            self.load_image_from_memory_as::<Rgba8, _>(&buffer, buffer.color_space())
+        }
+        else {
+            self.load_image_from_memory_as::<Rgba16F, _>(&buffer, buffer.color_space())
+        }
     }
 
-    pub fn load_image_from_memory(&self, buffer: &Buffer2d<Vec4<f32>>) -> anyhow::Result<Arc<ImageView<ImmutableImage>>> {
-        const FORMAT: Format = Format::R8G8B8A8_SRGB;
+    /// The image format is picked by the pixel format and the color space of
+    /// the buffer, see [`Buffer2d::to_image_data`]. Untagged
+    /// `Buffer2d<Vec4<f32>>` buffers are uploaded as `R8G8B8A8_SRGB`.
+    pub fn load_image_from_memory<P>(&self, buffer: &Buffer2d<P>) -> anyhow::Result<Arc<ImageView<ImmutableImage>>>
+        where P: PixelFormat,
+    {
+        let (format, data) = buffer.to_image_data();
         let (image, future) = match ImmutableImage::from_iter(
-            buffer.to_r8g8b8a8(),
+            data,
             ImageDimensions::Dim2d {
                 width: buffer.width() as u32, 
                 height: buffer.height() as u32, 
                 array_layers: 1
             },
             MipmapsCount::One,
-            FORMAT, // TODO choose srgb or default
+            format,
             self.graphics.queue().expect("no available queue"),
         ) {
             Ok(val) => val,
-            Err(err) => bail!(format!("{} ({:?})", err, FORMAT)),
+            Err(err) => bail!(format!("{} ({:?})", err, format)),
         };
         
         self.graphics.new_future(Box::new(future));
@@ -244,49 +226,114 @@ impl Manager {
         Ok(ImageView::new_default(image)?)
     }
 
-    pub fn load_font<T>(&self, font_name: T, px_size: u32) -> anyhow::Result<Arc<ImageView<ImmutableImage>>>
+    /// Uploads the buffer converted into `Q` pixels encoded in `color_space`,
+    /// e.g. [`Rgba8`](crate::graphics::pixel_format::Rgba8) with
+    /// [`ColorSpace::Srgb`] for a compact color image
+    pub fn load_image_from_memory_as<Q, P>(&self, buffer: &Buffer2d<P>, color_space: ColorSpace)
+        -> anyhow::Result<Arc<ImageView<ImmutableImage>>>
+        where Q: PixelFormat,
+              P: PixelFormat,
+    {
+        self.load_image_from_memory(&buffer.to_color_space(color_space).convert::<Q>())
+    }
+
+    /// Loads all faces of the font file (or `.ttc` collection) from the
+    /// `fonts` directory. Files are loaded once.
+    pub fn load_font_collection(&self, file_name: &str) -> anyhow::Result<Vec<Arc<FontFace>>> {
+        self.font_registry.borrow_mut().add(file_name, || {
+            self.load_binary_relative(format!("fonts/{file_name}"))
+                .with_context(|| format!("failed to load {file_name} font"))
+        })
+    }
+
+    pub fn font_face(&self, file_name: &str, index: u32) -> anyhow::Result<Arc<FontFace>> {
+        self.load_font_collection(file_name)?;
+
+        self.font_registry.borrow()
+            .face(file_name, index)
+            .with_context(|| format!("no face {index} in {file_name} font"))
+    }
+
+    /// Searches loaded fonts by family and style (e.g. "Bold"), see
+    /// [`FontRegistry::find`]
+    pub fn find_font_face(&self, family: &str, style: &str) -> Option<Arc<FontFace>> {
+        self.font_registry.borrow().find(family, style)
+    }
+
+    /// Faces used for characters missing in the font face, in order
+    pub fn set_font_fallbacks(&self, fallbacks: Vec<Arc<FontFace>>) {
+        self.font_registry.borrow_mut().set_fallbacks(fallbacks);
+    }
+
+    pub fn push_font_fallback(&self, face: Arc<FontFace>) {
+        self.font_registry.borrow_mut().push_fallback(face);
+    }
+
+    /// Enables dumping of atlases of created fonts, disabled by default.
+    /// Failed dumps are only logged.
+    pub fn set_font_debug_options(&self, options: Option<FontDebugOptions>) {
+        *self.font_debug_options.borrow_mut() = options;
+    }
+
+    pub fn load_font<T>(&self, font_name: T, px_size: u32, char_set: &CharSet) 
+        -> anyhow::Result<(Rc<Font>, Arc<ImageView<ImmutableImage>>)>
         where T: ToString,
     {
         let font_name = font_name.to_string();
+        let face = self.font_face(&font_name, 0)?;
 
-        let data = self.load_binary_relative(format!("fonts/{font_name}"))?;
-        let (font, future) = Font::new(
-            font_name, 
-            &data,
-            px_size,
-            self.graphics.queue().expect("no available queue"),
-        ).context("failed to create font")?;
+        self.create_font(font_name, face, px_size, char_set)
+    }
 
-        let Font { buffer2d, image, .. } = font;
+    /// Creates the font atlas from the face and the fallback faces.
+    pub fn create_font<T>(&self, font_name: T, face: Arc<FontFace>, px_size: u32, char_set: &CharSet)
+        -> anyhow::Result<(Rc<Font>, Arc<ImageView<ImmutableImage>>)>
+        where T: ToString,
+    {
+        let faces: Vec<Arc<FontFace>> = std::iter::once(Arc::clone(&face))
+            .chain(
+                self.font_registry.borrow()
+                    .fallbacks()
+                    .iter()
+                    .filter(|fallback| !Arc::ptr_eq(fallback, &face))
+                    .cloned()
+            )
+            .collect();
+
+        let font = Font::new(font_name.to_string(), faces, px_size, char_set).context("failed to create font")?;
+        // Diagnostics don't break font loading
+        if let Some(options) = &*self.font_debug_options.borrow() {
+            if let Err(err) = options.dump_font(&font) {
+                warn!("failed to dump font debug images: {:?}", err);
+            }
+        }
 
-        let size: Vec2<u32> = buffer2d.size().cast();
-        let (width, height) = size.into();
-        let data = buffer2d.to_r8g8b8a8();
+        let (image, future) = font.create_image(self.graphics.queue().expect("no available queue"))?;
 
-        self.save_image(
-            "C:/Users/dpyta/Desktop/output.png", 
-            &data, 
-            ColorType::Rgba,
-            width, 
-            height,
-        )?;
-        
         self.graphics.new_future(Box::new(future));
 
-        Ok(ImageView::new_default(image)?)
+        Ok((Rc::new(font), ImageView::new_default(image)?))
     }
 
-    pub fn save_image<T>(&self, path: T, data: &[u8], color_type: ColorType, width: u32, height: u32) -> anyhow::Result<()>
+    pub fn save_image<T>(
+        &self,
+        path: T,
+        data: &[u8],
+        color_type: ColorType,
+        width: u32,
+        height: u32,
+        metadata: &PngColorMetadata,
+    ) -> anyhow::Result<()>
         where T: AsRef<Path>,
     {
-        save_image(path, data, color_type, width, height)
+        save_image(path, data, color_type, width, height, metadata)
     }
 
-    pub fn save_buffer<T, U>(&self, path: T, buffer: &Buffer2d<Vec4<U>>) -> anyhow::Result<()>
+    /// The encoder is picked by the file extension from registered codecs
+    pub fn save_buffer<T, P>(&self, path: T, buffer: &Buffer2d<P>) -> anyhow::Result<()>
         where T: AsRef<Path>,
-              U: Number + Float,
-              <U as std::ops::Mul>::Output: Float + Cast<u8>,
+              P: PixelFormat,
     {
-        save_buffer(path, buffer)
+        self.image_codecs.borrow().save(path, &buffer.convert())
     }
 }
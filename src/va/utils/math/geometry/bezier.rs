@@ -0,0 +1,764 @@
+use crate::utils::math::vector::vector2::Vec2;
+use crate::utils::number::{Float, Number};
+
+use super::line::Line2;
+use super::rect::Rect;
+
+/// Default tolerance of lengths, intersections and approximations
+const TOLERANCE: f64 = 0.001;
+
+/// Subdivision limit of recursive algorithms
+const MAX_DEPTH: usize = 48;
+
+const MAX_QUADS: f64 = 1024.0;
+
+/// Limit of raw intersection hits, stops the subdivision of nearly tangent
+/// curves
+const MAX_HITS: usize = 256;
+
+/// Bézier curve of degree `N - 1`, the parameter `t` is in `[0, 1]`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bezier<T, const N: usize>
+    where T: Number,
+{
+    pub points: [Vec2<T>; N],
+}
+
+pub type QuadBezier<T> = Bezier<T, 3>;
+pub type CubicBezier<T> = Bezier<T, 4>;
+
+impl<T, const N: usize> Bezier<T, N>
+    where T: Number + Float,
+{
+    pub fn start(&self) -> Vec2<T> {
+        self.points[0]
+    }
+
+    pub fn end(&self) -> Vec2<T> {
+        self.points[N - 1]
+    }
+
+    pub fn eval(&self, t: T) -> Vec2<T> {
+        let mut points = self.points;
+        de_casteljau(&mut points, t)
+    }
+
+    /// Velocity at `t`
+    pub fn derivative(&self, t: T) -> Vec2<T> {
+        let mut points = self.points;
+        let degree = differentiate(&mut points, N);
+        de_casteljau(&mut points[..N - 1], t) * degree
+    }
+
+    fn second_derivative(&self, t: T) -> Vec2<T> {
+        if N < 3 {
+            return Vec2::ZERO;
+        }
+
+        let mut points = self.points;
+        let degree = differentiate(&mut points, N) * differentiate(&mut points, N - 1);
+        de_casteljau(&mut points[..N - 2], t) * degree
+    }
+
+    /// Splits at `t` with de Casteljau's algorithm
+    // https://pomax.github.io/bezierinfo/#splitting
+    pub fn split(&self, t: T) -> (Self, Self) {
+        let mut points = self.points;
+        let mut left = self.points;
+        let mut right = self.points;
+
+        for level in 1..N {
+            for i in 0..N - level {
+                points[i] = points[i].mix(points[i + 1], t);
+            }
+
+            left[level] = points[0];
+            right[N - 1 - level] = points[N - 1 - level];
+        }
+
+        (Self { points: left }, Self { points: right })
+    }
+
+    /// Part of the curve between `t0` and `t1`, the arguments are swapped if
+    /// `t0 > t1`
+    pub fn subsegment(&self, t0: T, t1: T) -> Self {
+        let (t0, t1) = if t0 > t1 { (t1, t0) } else { (t0, t1) };
+        let (left, _) = self.split(t1);
+        if t1 == T::ZERO {
+            return left;
+        }
+
+        let (_, right) = left.split(t0 / t1);
+        right
+    }
+
+    /// Tight bounds, the curve extrema are roots of the derivative
+    pub fn bounds(&self) -> Rect<T> {
+        let coefficients = self.coefficients();
+        let mut min = min(self.start(), self.end());
+        let mut max = max(self.start(), self.end());
+
+        for axis in [0, 1] {
+            let derivative: Vec<T> = (1..N)
+                .map(|i| coefficients[i][axis] * T::new(i as f64))
+                .collect();
+
+            for t in unit_roots(&derivative) {
+                let point = self.eval(t);
+                min = self::min(min, point);
+                max = self::max(max, point);
+            }
+        }
+
+        Rect::new(min, max)
+    }
+
+    /// Bounds of the control points, they contain the curve
+    fn hull(&self) -> Rect<T> {
+        let p1 = self.points.iter().fold(self.start(), |p1, &point| min(p1, point));
+        let p2 = self.points.iter().fold(self.start(), |p2, &point| max(p2, point));
+
+        Rect::new(p1, p2)
+    }
+
+    /// Control points are not farther than `tolerance` from the chord and
+    /// don't go beyond its ends, so the curve is nearly a line segment
+    fn is_flat(&self, tolerance: T) -> bool {
+        let chord = self.end() - self.start();
+        let length = chord.sqr_length();
+        if length == T::ZERO {
+            return false;
+        }
+
+        self.points.iter().all(|&point| {
+            let offset = point - self.start();
+            let projection = offset.dot(chord) / length;
+            cross(offset, chord).abs() <= tolerance * length.sqrt() && projection >= T::ZERO && projection <= T::ONE
+        })
+    }
+
+    pub fn length(&self) -> T {
+        self.length_with_tolerance(T::new(TOLERANCE))
+    }
+
+    /// Arc length, the curve is split until its control polygon is longer
+    /// than the chord by no more than `tolerance`
+    // Gravesen, "Adaptive subdivision and the length and energy of Bézier curves"
+    pub fn length_with_tolerance(&self, tolerance: T) -> T {
+        self.length_recursive(tolerance, 0)
+    }
+
+    fn length_recursive(&self, tolerance: T, depth: usize) -> T {
+        let chord = (self.end() - self.start()).lenght();
+        let polygon = self.points
+            .windows(2)
+            .map(|points| (points[1] - points[0]).lenght())
+            .fold(T::ZERO, |sum, length| sum + length);
+
+        if polygon - chord <= tolerance || depth >= MAX_DEPTH {
+            let degree = T::new((N - 1) as f64);
+            return (T::TWO * chord + (degree - T::ONE) * polygon) / (degree + T::ONE);
+        }
+
+        let (left, right) = self.split(T::new(0.5));
+        let tolerance = tolerance / T::TWO;
+
+        left.length_recursive(tolerance, depth + 1) + right.length_recursive(tolerance, depth + 1)
+    }
+
+    /// Parameter of the curve point nearest to `point`. The curve is sampled
+    /// and the best sample is refined with Newton's method.
+    pub fn nearest(&self, point: Vec2<T>) -> T {
+        const ITERATIONS: usize = 8;
+
+        let samples = 8 * N;
+        let distance = |t: T| (self.eval(t) - point).sqr_length();
+
+        let mut best = T::ZERO;
+        for i in 1..=samples {
+            let t = T::new(i as f64 / samples as f64);
+            if distance(t) < distance(best) {
+                best = t;
+            }
+        }
+
+        // Minimum of the squared distance, its derivative is `2 * d · B'`
+        let mut t = best;
+        for _ in 0..ITERATIONS {
+            let offset = self.eval(t) - point;
+            let derivative = self.derivative(t);
+
+            let denominator = derivative.dot(derivative) + offset.dot(self.second_derivative(t));
+            if denominator <= T::ZERO {
+                break;
+            }
+
+            t = (t - offset.dot(derivative) / denominator).clamp(T::ZERO, T::ONE);
+        }
+
+        if distance(t) < distance(best) { t } else { best }
+    }
+
+    /// Sorted curve parameters of intersections with the line segment.
+    /// Touching points without crossing may be missed.
+    pub fn intersect_line(&self, line: Line2<T>) -> Vec<T> {
+        let direction = line.p2 - line.p1;
+        let length = direction.sqr_length();
+        if length == T::ZERO {
+            return Vec::new();
+        }
+
+        // The signed distance to the line is a polynomial of `t`
+        let mut coefficients = self.coefficients();
+        coefficients[0] -= line.p1;
+
+        let distance: Vec<T> = coefficients.iter()
+            .map(|coefficient| cross(*coefficient, direction))
+            .collect();
+
+        unit_roots(&distance)
+            .into_iter()
+            .filter(|&t| {
+                let s = (self.eval(t) - line.p1).dot(direction) / length;
+                s >= T::ZERO && s <= T::ONE
+            })
+            .collect()
+    }
+
+    pub fn intersect<const M: usize>(&self, curve: &Bezier<T, M>) -> Vec<(T, T)> {
+        self.intersect_with_tolerance(curve, T::new(TOLERANCE))
+    }
+
+    /// Parameter pairs of intersections sorted by the parameter of `self`.
+    /// Pieces are split until their bounds are smaller than `tolerance` and
+    /// the result is refined with Newton's method. Overlapping curves give
+    /// only the ends of every overlap.
+    // https://pomax.github.io/bezierinfo/#curveintersection
+    pub fn intersect_with_tolerance<const M: usize>(&self, curve: &Bezier<T, M>, tolerance: T) -> Vec<(T, T)> {
+        let mut hits = Vec::new();
+        let mut overlaps = Vec::new();
+        intersect_recursive(
+            (self, T::ZERO, T::ONE),
+            (curve, T::ZERO, T::ONE),
+            tolerance,
+            0,
+            &mut hits,
+            &mut overlaps,
+        );
+
+        let mut hits: Vec<(T, T)> = hits.into_iter()
+            .map(|(s, t)| refine_intersection(self, curve, s, t))
+            .collect();
+
+        // Overlaps of neighbour pieces are joined into one
+        overlaps.sort_by(|a, b| a.0.0.partial_cmp(&b.0.0).unwrap_or(std::cmp::Ordering::Equal));
+        let mut joined: Vec<Overlap<T>> = Vec::new();
+        for (start, end) in overlaps {
+            match joined.last_mut() {
+                Some(last) if start.0 <= last.1.0 || (self.eval(start.0) - self.eval(last.1.0)).lenght() <= T::TWO * tolerance => {
+                    if end.0 > last.1.0 {
+                        last.1 = end;
+                    }
+                }
+                _ => joined.push((start, end)),
+            }
+        }
+
+        hits.extend(joined.into_iter().flat_map(|(start, end)| [start, end]));
+        hits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut result: Vec<(T, T)> = Vec::with_capacity(hits.len());
+        for (s, t) in hits {
+            // The curve between the hits is compared, not only the points,
+            // so both hits of a loop crossing itself are kept
+            let is_same = result.last()
+                .is_some_and(|&(last, _)| max_side(self.subsegment(last, s).hull()) <= T::TWO * tolerance);
+
+            if !is_same {
+                result.push((s, t));
+            }
+        }
+
+        result
+    }
+
+    /// Coefficients of powers of `t`, starting from `t⁰`
+    fn coefficients(&self) -> [Vec2<T>; N] {
+        let degree = N - 1;
+        let mut coefficients = [Vec2::ZERO; N];
+
+        for (j, coefficient) in coefficients.iter_mut().enumerate() {
+            let mut sum = Vec2::ZERO;
+            for i in 0..=j {
+                let sign = if (j - i) % 2 == 0 { 1.0 } else { -1.0 };
+                sum += self.points[i] * T::new(sign * binomial(j, i));
+            }
+
+            *coefficient = sum * T::new(binomial(degree, j));
+        }
+
+        coefficients
+    }
+}
+
+impl<T> QuadBezier<T>
+    where T: Number + Float,
+{
+    pub fn new(p0: Vec2<T>, p1: Vec2<T>, p2: Vec2<T>) -> Self {
+        Self {
+            points: [p0, p1, p2],
+        }
+    }
+
+    /// The same curve as a cubic one
+    pub fn elevate(&self) -> CubicBezier<T> {
+        let [p0, p1, p2] = self.points;
+        let k = T::new(2.0 / 3.0);
+
+        CubicBezier::new(p0, p0.mix(p1, k), p2.mix(p1, k), p2)
+    }
+}
+
+impl<T> CubicBezier<T>
+    where T: Number + Float,
+{
+    pub fn new(p0: Vec2<T>, p1: Vec2<T>, p2: Vec2<T>, p3: Vec2<T>) -> Self {
+        Self {
+            points: [p0, p1, p2, p3],
+        }
+    }
+
+    pub fn to_quads(&self) -> Vec<QuadBezier<T>> {
+        self.to_quads_with_tolerance(T::new(TOLERANCE))
+    }
+
+    /// Quadratic curves deviating from the curve by no more than
+    /// `tolerance`, the curve is split evenly
+    // https://caffeineowl.com/graphics/2d/vectorial/cubic2quad01.html
+    pub fn to_quads_with_tolerance(&self, tolerance: T) -> Vec<QuadBezier<T>> {
+        let [p0, p1, p2, p3] = self.points;
+
+        // Error of the single quadratic curve is `√3 / 36 * |p3 - 3p2 + 3p1 - p0|`
+        // and decreases as the cube of the number of pieces
+        let error = (p3 - p2 * T::new(3.0) + p1 * T::new(3.0) - p0).lenght() * T::new(3.0f64.sqrt() / 36.0);
+        let count = (error / tolerance).powf(T::new(1.0 / 3.0)).ceil();
+        let count = if count >= T::ONE { count.clamp(T::ONE, T::new(MAX_QUADS)) } else { T::ONE };
+
+        let mut quads = Vec::new();
+        let mut i = T::ZERO;
+        while i < count {
+            let [p0, p1, p2, p3] = self.subsegment(i / count, (i + T::ONE) / count).points;
+            let control = (p1 * T::new(3.0) - p0 + p2 * T::new(3.0) - p3) * T::new(0.25);
+
+            quads.push(QuadBezier::new(p0, control, p3));
+            i += T::ONE;
+        }
+
+        quads
+    }
+}
+
+impl<T, const N: usize> From<[Vec2<T>; N]> for Bezier<T, N>
+    where T: Number,
+{
+    fn from(value: [Vec2<T>; N]) -> Self {
+        Self {
+            points: value,
+        }
+    }
+}
+
+fn de_casteljau<T>(points: &mut [Vec2<T>], t: T) -> Vec2<T>
+    where T: Number + Float,
+{
+    for level in 1..points.len() {
+        for i in 0..points.len() - level {
+            points[i] = points[i].mix(points[i + 1], t);
+        }
+    }
+
+    points[0]
+}
+
+/// Replaces the first `len` points with `len - 1` differences, returns the
+/// degree they are multiplied by
+fn differentiate<T>(points: &mut [Vec2<T>], len: usize) -> T
+    where T: Number + Float,
+{
+    for i in 0..len - 1 {
+        points[i] = points[i + 1] - points[i];
+    }
+
+    T::new((len - 1) as f64)
+}
+
+fn min<T>(a: Vec2<T>, b: Vec2<T>) -> Vec2<T>
+    where T: Number,
+{
+    Vec2::new(if a.x < b.x { a.x } else { b.x }, if a.y < b.y { a.y } else { b.y })
+}
+
+fn max<T>(a: Vec2<T>, b: Vec2<T>) -> Vec2<T>
+    where T: Number,
+{
+    Vec2::new(if a.x > b.x { a.x } else { b.x }, if a.y > b.y { a.y } else { b.y })
+}
+
+fn max_side<T>(rect: Rect<T>) -> T
+    where T: Number,
+{
+    let size = rect.p2 - rect.p1;
+    if size.x > size.y { size.x } else { size.y }
+}
+
+fn cross<T>(a: Vec2<T>, b: Vec2<T>) -> T
+    where T: Number,
+{
+    a.x * b.y - a.y * b.x
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |result, i| result * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// Evaluates the polynomial with coefficients of increasing powers
+fn polynomial<T>(coefficients: &[T], t: T) -> T
+    where T: Number,
+{
+    coefficients.iter().rev().fold(T::ZERO, |sum, &coefficient| sum * t + coefficient)
+}
+
+/// Sorted roots in `[0, 1]` of the polynomial with coefficients of
+/// increasing powers. Roots of the derivative split the interval into
+/// monotone parts, which are bisected.
+fn unit_roots<T>(coefficients: &[T]) -> Vec<T>
+    where T: Number + Float,
+{
+    let degree = match coefficients.iter().rposition(|&coefficient| coefficient != T::ZERO) {
+        Some(val) if val > 0 => val,
+        _ => return Vec::new(),
+    };
+    let coefficients = &coefficients[..=degree];
+
+    let derivative: Vec<T> = (1..=degree)
+        .map(|i| coefficients[i] * T::new(i as f64))
+        .collect();
+
+    let mut bounds = vec![T::ZERO];
+    bounds.extend(unit_roots(&derivative).into_iter().filter(|&t| t > T::ZERO && t < T::ONE));
+    bounds.push(T::ONE);
+
+    let mut roots = Vec::new();
+    for interval in bounds.windows(2) {
+        let (mut a, mut b) = (interval[0], interval[1]);
+        let (fa, fb) = (polynomial(coefficients, a), polynomial(coefficients, b));
+
+        if fa == T::ZERO {
+            if roots.last() != Some(&a) {
+                roots.push(a);
+            }
+
+            continue;
+        }
+
+        if fb == T::ZERO || (fa < T::ZERO) == (fb < T::ZERO) {
+            continue;
+        }
+
+        // Until the middle can't be represented
+        loop {
+            let middle = (a + b) / T::TWO;
+            if middle <= a || middle >= b {
+                break;
+            }
+
+            if (polynomial(coefficients, middle) < T::ZERO) == (fa < T::ZERO) {
+                a = middle;
+            }
+            else {
+                b = middle;
+            }
+        }
+
+        roots.push(a);
+    }
+
+    if polynomial(coefficients, T::ONE) == T::ZERO && roots.last() != Some(&T::ONE) {
+        roots.push(T::ONE);
+    }
+
+    roots
+}
+
+type Piece<'a, T, const N: usize> = (&'a Bezier<T, N>, T, T);
+
+/// Parameter pairs of the start and the end of a common part
+type Overlap<T> = ((T, T), (T, T));
+
+fn intersect_recursive<T, const N: usize, const M: usize>(
+    (a, a0, a1): Piece<T, N>,
+    (b, b0, b1): Piece<T, M>,
+    tolerance: T,
+    depth: usize,
+    hits: &mut Vec<(T, T)>,
+    overlaps: &mut Vec<Overlap<T>>,
+)
+    where T: Number + Float,
+{
+    if hits.len() >= MAX_HITS {
+        return;
+    }
+
+    let (a_hull, b_hull) = (a.hull(), b.hull());
+    let is_separated = a_hull.p2.x < b_hull.p1.x || b_hull.p2.x < a_hull.p1.x
+        || a_hull.p2.y < b_hull.p1.y || b_hull.p2.y < a_hull.p1.y;
+    if is_separated {
+        return;
+    }
+
+    let (a_size, b_size) = (max_side(a_hull), max_side(b_hull));
+
+    if (a_size <= tolerance && b_size <= tolerance) || depth >= MAX_DEPTH {
+        hits.push(((a0 + a1) / T::TWO, (b0 + b1) / T::TWO));
+        return;
+    }
+
+    if let Some(overlap) = collinear_overlap((a, a0, a1), (b, b0, b1), tolerance) {
+        overlaps.push(overlap);
+        return;
+    }
+
+    let half = T::new(0.5);
+    if a_size >= b_size {
+        let (left, right) = a.split(half);
+        let middle = (a0 + a1) / T::TWO;
+
+        intersect_recursive((&left, a0, middle), (b, b0, b1), tolerance, depth + 1, hits, overlaps);
+        intersect_recursive((&right, middle, a1), (b, b0, b1), tolerance, depth + 1, hits, overlaps);
+    }
+    else {
+        let (left, right) = b.split(half);
+        let middle = (b0 + b1) / T::TWO;
+
+        intersect_recursive((a, a0, a1), (&left, b0, middle), tolerance, depth + 1, hits, overlaps);
+        intersect_recursive((a, a0, a1), (&right, middle, b1), tolerance, depth + 1, hits, overlaps);
+    }
+}
+
+/// Common part of nearly straight pieces lying on one line, `None` if the
+/// pieces are curved, cross or are apart. Pieces touching by their ends give
+/// a single point.
+fn collinear_overlap<T, const N: usize, const M: usize>(
+    (a, a0, a1): Piece<T, N>,
+    (b, b0, b1): Piece<T, M>,
+    tolerance: T,
+) -> Option<Overlap<T>>
+    where T: Number + Float,
+{
+    if !a.is_flat(tolerance) || !b.is_flat(tolerance) {
+        return None;
+    }
+
+    let direction = a.end() - a.start();
+    let length = direction.sqr_length();
+    let distance = |point: Vec2<T>| cross(point - a.start(), direction).abs() / length.sqrt();
+    if distance(b.start()) > tolerance || distance(b.end()) > tolerance {
+        return None;
+    }
+
+    // Ends of the common part along the chord of `a`
+    let project = |point: Vec2<T>| (point - a.start()).dot(direction) / length;
+    let (u, v) = (project(b.start()), project(b.end()));
+    let (mut low, mut high) = if u < v { (u, v) } else { (v, u) };
+    if low < T::ZERO {
+        low = T::ZERO;
+    }
+    if high > T::ONE {
+        high = T::ONE;
+    }
+    if (low - high) * length.sqrt() > tolerance {
+        return None;
+    }
+    if high < low {
+        low = (low + high) / T::TWO;
+        high = low;
+    }
+
+    let parameters = |point: Vec2<T>| (a0 + (a1 - a0) * a.nearest(point), b0 + (b1 - b0) * b.nearest(point));
+    Some((parameters(a.start() + direction * low), parameters(a.start() + direction * high)))
+}
+
+/// Newton's method for `a(s) - b(t) = 0`, keeps the parameters if it doesn't
+/// get closer
+fn refine_intersection<T, const N: usize, const M: usize>(a: &Bezier<T, N>, b: &Bezier<T, M>, s: T, t: T) -> (T, T)
+    where T: Number + Float,
+{
+    const ITERATIONS: usize = 4;
+
+    let distance = |s: T, t: T| (a.eval(s) - b.eval(t)).sqr_length();
+    let (mut next_s, mut next_t) = (s, t);
+
+    for _ in 0..ITERATIONS {
+        let offset = b.eval(next_t) - a.eval(next_s);
+        let (da, db) = (a.derivative(next_s), b.derivative(next_t));
+
+        let determinant = cross(db, da);
+        if determinant == T::ZERO {
+            break;
+        }
+
+        next_s = (next_s + cross(db, offset) / determinant).clamp(T::ZERO, T::ONE);
+        next_t = (next_t + cross(da, offset) / determinant).clamp(T::ZERO, T::ONE);
+    }
+
+    if distance(next_s, next_t) < distance(s, t) { (next_s, next_t) } else { (s, t) }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::math::geometry::line::Line;
+    use crate::utils::math::is_equal::IsCopyTypeEqual;
+
+    use super::*;
+
+    const EPSILON: f64 = 0.001;
+
+    #[test]
+    fn bezier_eval_and_split() {
+        let curve = CubicBezier::new(Vec2::new(0.0, 0.0), Vec2::new(0.0, 1.0), Vec2::new(1.0, 1.0), Vec2::new(1.0, 0.0));
+        assert!(curve.eval(0.5).is_equal(Vec2::new(0.5, 0.75), EPSILON));
+        assert!(curve.derivative(0.0).is_equal(Vec2::new(0.0, 3.0), EPSILON));
+        assert!(curve.derivative(0.5).is_equal(Vec2::new(1.5, 0.0), EPSILON));
+        assert!(curve.second_derivative(0.0).is_equal(Vec2::new(6.0, -6.0), EPSILON));
+
+        let (left, right) = curve.split(0.25);
+        assert!(left.end().is_equal(curve.eval(0.25), EPSILON));
+        assert!(left.eval(0.5).is_equal(curve.eval(0.125), EPSILON));
+        assert!(right.eval(0.5).is_equal(curve.eval(0.625), EPSILON));
+        assert!(curve.subsegment(0.25, 0.75).eval(0.5).is_equal(curve.eval(0.5), EPSILON));
+
+        let quad = QuadBezier::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 2.0), Vec2::new(2.0, 0.0));
+        let cubic = quad.elevate();
+        for t in [0.0, 0.3, 0.5, 0.9] {
+            assert!(cubic.eval(t).is_equal(quad.eval(t), EPSILON));
+        }
+    }
+
+    #[test]
+    fn bezier_bounds_and_length() {
+        let quad = QuadBezier::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 2.0), Vec2::new(2.0, 0.0));
+        let bounds = quad.bounds();
+        assert!(bounds.p1.is_equal(Vec2::new(0.0, 0.0), EPSILON));
+        assert!(bounds.p2.is_equal(Vec2::new(2.0, 1.0), EPSILON));
+
+        let cubic = CubicBezier::new(Vec2::new(0.0, 0.0), Vec2::new(-1.0, 1.0), Vec2::new(2.0, 1.0), Vec2::new(1.0, 0.0));
+        let bounds = cubic.bounds();
+        assert!(bounds.p1.is_equal(Vec2::new(-0.2071, 0.0), EPSILON));
+        assert!(bounds.p2.is_equal(Vec2::new(1.2071, 0.75), EPSILON));
+
+        // Straight line and a quarter of a circle
+        let line = CubicBezier::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(2.0, 0.0), Vec2::new(3.0, 0.0));
+        assert!(line.length().is_equal(3.0, EPSILON));
+
+        let k = 0.552284749831;
+        let arc = CubicBezier::new(Vec2::new(1.0, 0.0), Vec2::new(1.0, k), Vec2::new(k, 1.0), Vec2::new(0.0, 1.0));
+        assert!(arc.length_with_tolerance(0.00001).is_equal(std::f64::consts::FRAC_PI_2, EPSILON));
+    }
+
+    #[test]
+    fn bezier_nearest() {
+        let quad = QuadBezier::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 2.0), Vec2::new(2.0, 0.0));
+        assert!(quad.nearest(Vec2::new(1.0, 5.0)).is_equal(0.5, EPSILON));
+        assert!(quad.nearest(Vec2::new(-1.0, -1.0)).is_equal(0.0, EPSILON));
+        assert!(quad.nearest(Vec2::new(3.0, -0.5)).is_equal(1.0, EPSILON));
+
+        // The nearest point is where the offset is perpendicular to the curve
+        let point = Vec2::new(0.3, 0.9);
+        let t = quad.nearest(point);
+        assert!((quad.eval(t) - point).dot(quad.derivative(t)).is_equal(0.0, EPSILON));
+    }
+
+    #[test]
+    fn bezier_intersections() {
+        let quad = QuadBezier::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 2.0), Vec2::new(2.0, 0.0));
+
+        let hits = quad.intersect_line(Line::new(Vec2::new(-1.0, 0.5), Vec2::new(3.0, 0.5)));
+        assert_eq!(hits.len(), 2);
+        for t in hits {
+            assert!(quad.eval(t).y.is_equal(0.5, EPSILON));
+        }
+
+        assert!(quad.intersect_line(Line::new(Vec2::new(-1.0, 0.5), Vec2::new(0.0, 0.5))).is_empty());
+        assert!(quad.intersect_line(Line::new(Vec2::new(0.0, 2.0), Vec2::new(2.0, 2.0))).is_empty());
+
+        // Mirrored arch crosses twice
+        let cubic = CubicBezier::new(Vec2::new(0.0, 1.0), Vec2::new(0.5, -1.0), Vec2::new(1.5, -1.0), Vec2::new(2.0, 1.0));
+        let hits = quad.intersect(&cubic);
+        assert_eq!(hits.len(), 2);
+        for (s, t) in hits {
+            assert!(quad.eval(s).is_equal(cubic.eval(t), EPSILON));
+        }
+
+        let far = CubicBezier::new(Vec2::new(5.0, 5.0), Vec2::new(6.0, 6.0), Vec2::new(7.0, 5.0), Vec2::new(8.0, 6.0));
+        assert!(quad.intersect(&far).is_empty());
+    }
+
+    #[test]
+    fn bezier_overlaps() {
+        // Overlaps are reported once by their ends
+        let quad = QuadBezier::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 2.0), Vec2::new(2.0, 0.0));
+        let hits = quad.intersect(&quad);
+        assert_eq!(hits.len(), 2);
+        assert!(Vec2::from(hits[0]).is_equal(Vec2::new(0.0, 0.0), EPSILON));
+        assert!(Vec2::from(hits[1]).is_equal(Vec2::new(1.0, 1.0), EPSILON));
+
+        let part = quad.subsegment(0.75, 0.25);
+        let hits = quad.intersect(&part);
+        assert_eq!(hits.len(), 2);
+        assert!(Vec2::from(hits[0]).is_equal(Vec2::new(0.25, 0.0), EPSILON));
+        assert!(Vec2::from(hits[1]).is_equal(Vec2::new(0.75, 1.0), EPSILON));
+
+        // Opposite directions along one line
+        let line = CubicBezier::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(2.0, 0.0), Vec2::new(3.0, 0.0));
+        let back = QuadBezier::new(Vec2::new(4.0, 0.0), Vec2::new(2.5, 0.0), Vec2::new(1.0, 0.0));
+        let hits = line.intersect(&back);
+        assert_eq!(hits.len(), 2);
+        assert!(line.eval(hits[0].0).is_equal(Vec2::new(1.0, 0.0), EPSILON));
+        assert!(back.eval(hits[1].1).is_equal(Vec2::new(3.0, 0.0), EPSILON));
+
+        // The loop of a self-intersecting curve crosses itself besides the overlap
+        let looped = CubicBezier::new(Vec2::new(0.0, 0.0), Vec2::new(3.0, 2.0), Vec2::new(-1.0, 2.0), Vec2::new(2.0, 0.0));
+        let hits = looped.intersect(&looped);
+        assert_eq!(hits.len(), 4);
+        let crossings: Vec<(f64, f64)> = hits.into_iter().filter(|&(s, t)| (s - t).abs() > 0.1).collect();
+        assert_eq!(crossings.len(), 2);
+        for (s, t) in crossings {
+            assert!(looped.eval(s).is_equal(looped.eval(t), EPSILON));
+        }
+    }
+
+    #[test]
+    fn bezier_to_quads() {
+        let cubic = CubicBezier::new(Vec2::new(0.0, 0.0), Vec2::new(0.0, 100.0), Vec2::new(100.0, 100.0), Vec2::new(100.0, 0.0));
+
+        for tolerance in [10.0, 0.1] {
+            let quads = cubic.to_quads_with_tolerance(tolerance);
+            assert!(quads.first().unwrap().start().is_equal(cubic.start(), EPSILON));
+            assert!(quads.last().unwrap().end().is_equal(cubic.end(), EPSILON));
+
+            let count = quads.len() as f64;
+            for (i, quad) in quads.iter().enumerate() {
+                for k in [0.25, 0.5, 0.75] {
+                    let point = cubic.eval((i as f64 + k) / count);
+                    assert!((quad.eval(k) - point).lenght() <= tolerance);
+                }
+            }
+        }
+
+        // Elevated quadratic curve is exact
+        let quad = QuadBezier::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 2.0), Vec2::new(2.0, 0.0));
+        let quads = quad.elevate().to_quads();
+        assert_eq!(quads.len(), 1);
+        assert!(quads[0].points[1].is_equal(quad.points[1], EPSILON));
+    }
+}
@@ -5,6 +5,9 @@ use roots::Roots;
 use crate::utils::math::vector::vector2::Vec2;
 use super::axis::Axis2d;
 
+/// Subdivision limit of huge curves, e.g. of malformed outlines
+const MAX_SEGMENTS: f64 = 4096.0;
+
 pub fn get_quad_curve_point(
     p0: Vec2<f64>, 
     p1: Vec2<f64>, 
@@ -242,6 +245,63 @@ fn approx_inv_myint(x: f64) -> f64 {
     x * (1.0 - B + (B * B + 0.25 * x * x).sqrt())
 }
 
+pub fn get_cube_curve_point(
+    p0: Vec2<f64>,
+    p1: Vec2<f64>,
+    p2: Vec2<f64>,
+    p3: Vec2<f64>,
+    t: f64
+) -> Vec2<f64>
+{
+    (1.0 - t).powi(3) * p0
+        + 3.0 * (1.0 - t).powi(2) * t * p1
+        + 3.0 * (1.0 - t) * t.powi(2) * p2
+        + t.powi(3) * p3
+}
+
+/// Returns `t` values splitting the curve into line segments which deviate
+/// from the curve by no more than `tolerance`.
+// Wang's formula: https://www.cs.ucdavis.edu/~amenta/s04/wang.pdf
+pub fn subdivide_cube_curve_with_tolerance(
+    p0: Vec2<f64>,
+    p1: Vec2<f64>,
+    p2: Vec2<f64>,
+    p3: Vec2<f64>,
+    tolerance: f64,
+) -> Vec<f64>
+{
+    let dd1 = (p0 - 2.0 * p1 + p2).lenght();
+    let dd2 = (p1 - 2.0 * p2 + p3).lenght();
+    let dd = dd1.max(dd2);
+
+    let count = (0.75 * dd / tolerance).sqrt().ceil();
+    let n = if count.is_finite() {
+        count.clamp(1.0, MAX_SEGMENTS) as u64
+    }
+    else {
+        1
+    };
+
+    let mut result = Vec::with_capacity(n as usize + 1);
+    for i in 0..n {
+        result.push(i as f64 / n as f64);
+    }
+
+    result.push(1.0);
+    result
+}
+
+pub fn subdivide_cube_curve(
+    p0: Vec2<f64>,
+    p1: Vec2<f64>,
+    p2: Vec2<f64>,
+    p3: Vec2<f64>,
+) -> Vec<f64>
+{
+    const THRESHOLD: f64 = 0.1;
+    subdivide_cube_curve_with_tolerance(p0, p1, p2, p3, THRESHOLD)
+}
+
 // // https://raphlinus.github.io/graphics/curves/2019/12/23/flatten-quadbez.html
 // #[derive(Debug)]
 // pub struct QuadCurvePointsIter {
@@ -477,4 +537,57 @@ impl Iterator for CubeCurveIntPointsIter {
     fn next(&mut self) -> Option<Self::Item> {
         self.inner.next()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::math::is_equal::IsCopyTypeEqual;
+
+    use super::*;
+
+    const EPSILON: f64 = 0.001;
+
+    #[test]
+    fn cube_curve_point() {
+        let p0 = Vec2::new(0.0, 0.0);
+        let p1 = Vec2::new(0.0, 1.0);
+        let p2 = Vec2::new(1.0, 1.0);
+        let p3 = Vec2::new(1.0, 0.0);
+
+        assert!(get_cube_curve_point(p0, p1, p2, p3, 0.0).is_equal(p0, EPSILON));
+        assert!(get_cube_curve_point(p0, p1, p2, p3, 0.5).is_equal(Vec2::new(0.5, 0.75), EPSILON));
+        assert!(get_cube_curve_point(p0, p1, p2, p3, 1.0).is_equal(p3, EPSILON));
+    }
+
+    #[test]
+    fn subdivide_cube_curve_tolerance() {
+        let p0 = Vec2::new(0.0, 0.0);
+        let p1 = Vec2::new(0.0, 100.0);
+        let p2 = Vec2::new(100.0, 100.0);
+        let p3 = Vec2::new(100.0, 0.0);
+
+        let t_values = subdivide_cube_curve_with_tolerance(p0, p1, p2, p3, 0.1);
+        assert_eq!(*t_values.first().unwrap(), 0.0);
+        assert_eq!(*t_values.last().unwrap(), 1.0);
+
+        // Every segment middle is close to the curve
+        for t in t_values.windows(2) {
+            let a = get_cube_curve_point(p0, p1, p2, p3, t[0]);
+            let b = get_cube_curve_point(p0, p1, p2, p3, t[1]);
+            let middle = get_cube_curve_point(p0, p1, p2, p3, 0.5 * (t[0] + t[1]));
+
+            assert!(((a + b) * 0.5 - middle).lenght() <= 0.1);
+        }
+
+        let coarse = subdivide_cube_curve_with_tolerance(p0, p1, p2, p3, 10.0);
+        assert!(coarse.len() < t_values.len());
+
+        // A straight line is one segment
+        let line = subdivide_cube_curve(p0, Vec2::new(1.0, 0.0), Vec2::new(2.0, 0.0), Vec2::new(3.0, 0.0));
+        assert_eq!(line, vec![0.0, 1.0]);
+
+        let huge = Vec2::new(1.0e30, -1.0e30);
+        let t_values = subdivide_cube_curve(p0, huge, -huge, p3);
+        assert_eq!(t_values.len(), MAX_SEGMENTS as usize + 1);
+    }
+}
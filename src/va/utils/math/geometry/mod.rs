@@ -1,4 +1,5 @@
 pub mod axis;
+pub mod bezier;
 pub mod curve;
 pub mod line;
 pub mod point;
@@ -1,4 +1,4 @@
-use std::ops::{Add, Index, IndexMut, Mul, Sub};
+use std::ops::{Add, Div, Index, IndexMut, Mul, Neg, Sub};
 
 use crate::utils::math::vector::vector3::Vec3;
 use crate::utils::number::{Number, Float};
@@ -66,8 +66,158 @@ impl<T> Mat3x3<T>
         self[5] = vector.y;
     }
 
-    pub fn view() -> Self {
-        todo!(); // TODO
+    pub fn transpose(self) -> Self {
+        let mut matrix = self;
+
+        for row in 0..ROW_COUNT {
+            for column in 0..COLUMN_COUNT {
+                *matrix.get_mut(column, row) = self.get(row, column);
+            }
+        }
+
+        matrix
+    }
+}
+
+impl<T> Mat3x3<T>
+    where T: Number + Float + Neg<Output = T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+{
+    /// Counterclockwise rotation by `angle` radians when Y is directed up,
+    /// clockwise when it's directed down
+    pub fn with_rotation(angle: T) -> Self {
+        let (sin, cos) = (angle.sin(), angle.cos());
+
+        Self::new([
+            cos, -sin, T::ZERO,
+            sin, cos, T::ZERO,
+            T::ZERO, T::ZERO, T::ONE,
+        ])
+    }
+
+    /// Skew by `angles.x` radians along X and `angles.y` radians along Y,
+    /// like CSS `skew()`
+    pub fn with_skew(angles: Vec2<T>) -> Self {
+        Self::new([
+            T::ONE, angles.x.tan(), T::ZERO,
+            angles.y.tan(), T::ONE, T::ZERO,
+            T::ZERO, T::ZERO, T::ONE,
+        ])
+    }
+
+    /// 2D camera centered at `position`, rotated by `angle` radians and
+    /// zoomed by `zoom`. Maps the world coordinates into the view ones.
+    pub fn view(position: Vec2<T>, angle: T, zoom: T) -> Self {
+        let (sin, cos) = (angle.sin() * zoom, angle.cos() * zoom);
+
+        Self::new([
+            cos, sin, -(cos * position.x + sin * position.y),
+            -sin, cos, sin * position.x - cos * position.y,
+            T::ZERO, T::ZERO, T::ONE,
+        ])
+    }
+
+    pub fn determinant(&self) -> T {
+        let a = |row: usize, column: usize| self.get(column, row);
+
+        a(0, 0) * (a(1, 1) * a(2, 2) - a(1, 2) * a(2, 1))
+            - a(0, 1) * (a(1, 0) * a(2, 2) - a(1, 2) * a(2, 0))
+            + a(0, 2) * (a(1, 0) * a(2, 1) - a(1, 1) * a(2, 0))
+    }
+
+    /// `None` if the matrix is singular
+    // https://en.wikipedia.org/wiki/Adjugate_matrix
+    pub fn inverse(&self) -> Option<Self> {
+        let determinant = self.determinant();
+        if determinant == T::ZERO {
+            return None;
+        }
+
+        let a = |row: usize, column: usize| self.get(column, row);
+        let minor = |r0: usize, r1: usize, c0: usize, c1: usize| a(r0, c0) * a(r1, c1) - a(r0, c1) * a(r1, c0);
+
+        let matrix = Self::new([
+            minor(1, 2, 1, 2), -minor(0, 2, 1, 2), minor(0, 1, 1, 2),
+            -minor(1, 2, 0, 2), minor(0, 2, 0, 2), -minor(0, 1, 0, 2),
+            minor(1, 2, 0, 1), -minor(0, 2, 0, 1), minor(0, 1, 0, 1),
+        ]);
+
+        Some(matrix * (T::ONE / determinant))
+    }
+
+    /// Splits the affine part into `translate * rotation * skew * scale`,
+    /// the skew is only along X. `None` if the matrix is singular.
+    ///
+    /// A reflection is a negative `scale.y`.
+    // https://frederic-wang.fr/decomposition-of-2d-transform-matrices.html
+    pub fn decompose(&self) -> Option<Decomposition<T>> {
+        let (a, b) = (self.get(0, 0), self.get(0, 1));
+        let (c, d) = (self.get(1, 0), self.get(1, 1));
+
+        let scale_x = (a * a + b * b).sqrt();
+        let determinant = a * d - b * c;
+        if scale_x == T::ZERO || determinant == T::ZERO {
+            return None;
+        }
+
+        let scale_y = determinant / scale_x;
+        let shear = (a * c + b * d) / (scale_x * scale_y);
+
+        Some(Decomposition {
+            translate: Vec2::new(self.get(2, 0), self.get(2, 1)),
+            rotation: b.atan2(a),
+            skew: Vec2::new(shear.atan2(T::ONE), T::ZERO),
+            scale: Vec2::new(scale_x, scale_y),
+        })
+    }
+
+    /// Applies the transform to a point, dividing by the projective
+    /// coordinate
+    pub fn transform_point(&self, point: Vec2<T>) -> Vec2<T> {
+        let a = |row: usize, column: usize| self.get(column, row);
+
+        let x = a(0, 0) * point.x + a(0, 1) * point.y + a(0, 2);
+        let y = a(1, 0) * point.x + a(1, 1) * point.y + a(1, 2);
+        let w = a(2, 0) * point.x + a(2, 1) * point.y + a(2, 2);
+
+        if w == T::ONE {
+            Vec2::new(x, y)
+        }
+        else {
+            Vec2::new(x / w, y / w)
+        }
+    }
+
+    /// Applies the linear part of the transform to a direction, the
+    /// translation is ignored
+    pub fn transform_vector(&self, vector: Vec2<T>) -> Vec2<T> {
+        let a = |row: usize, column: usize| self.get(column, row);
+
+        Vec2::new(
+            a(0, 0) * vector.x + a(0, 1) * vector.y,
+            a(1, 0) * vector.x + a(1, 1) * vector.y,
+        )
+    }
+}
+
+/// 2D affine transform split by [`Mat3x3::decompose`], angles are in radians
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Decomposition<T>
+    where T: Number,
+{
+    pub translate: Vec2<T>,
+    pub rotation: T,
+    pub skew: Vec2<T>,
+    pub scale: Vec2<T>,
+}
+
+impl<T> From<Decomposition<T>> for Mat3x3<T>
+    where T: Number + Float + Neg<Output = T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+{
+    fn from(value: Decomposition<T>) -> Self {
+        Mat3x3::with_translate(value.translate)
+            * Mat3x3::with_rotation(value.rotation)
+            * Mat3x3::with_skew(value.skew)
+            * Mat3x3::with_scale(value.scale)
     }
 }
 
@@ -328,4 +478,84 @@ mod tests {
             28, 34, 15,
         ]));
     }
+
+    #[test]
+    fn matrix3x3_view() {
+        let view = Mat3x3::view(Vec2::new(10.0, 5.0), std::f64::consts::FRAC_PI_2, 2.0);
+
+        let point = view * Vec3::new(10.0, 5.0, 1.0);
+        assert!(point.is_equal(Vec3::new(0.0, 0.0, 1.0), EPSILON));
+
+        // The camera is rotated, so the world is rotated backwards
+        let point = view * Vec3::new(10.0, 6.0, 1.0);
+        assert!(point.is_equal(Vec3::new(2.0, 0.0, 1.0), EPSILON));
+    }
+
+    #[test]
+    fn matrix3x3_inverse() {
+        let matrix = Mat3x3::new([
+            2.0, 0.0, 1.0,
+            1.0, 3.0, 2.0,
+            1.0, 1.0, 2.0,
+        ]);
+        assert!(matrix.determinant().is_equal(6.0, EPSILON));
+        assert!(matrix.transpose().determinant().is_equal(6.0, EPSILON));
+        assert!(matrix.transpose().get(1, 0).is_equal(1.0, EPSILON));
+
+        let inverse = matrix.inverse().unwrap();
+        assert!((matrix * inverse).is_equal(Mat3x3::default(), EPSILON));
+        assert!((inverse * matrix).is_equal(Mat3x3::default(), EPSILON));
+
+        assert_eq!(Mat3x3::with_scale(Vec2::new(1.0, 0.0)).inverse(), None);
+
+        // Small scales aren't singular
+        let small = Mat3x3::with_scale(Vec2::new(1.0e-8, 1.0e-8));
+        assert!((small.inverse().unwrap() * small).is_equal(Mat3x3::default(), EPSILON));
+        assert!(Mat3x3::with_scale(Vec2::new(1.0, 1.0e-17)).inverse().is_some());
+    }
+
+    #[test]
+    fn matrix3x3_transform() {
+        let matrix = Mat3x3::with_translate(Vec2::new(10.0, 5.0)) * Mat3x3::with_rotation(std::f64::consts::FRAC_PI_2);
+        assert!(matrix.transform_point(Vec2::new(1.0, 0.0)).is_equal(Vec2::new(10.0, 6.0), EPSILON));
+        assert!(matrix.transform_vector(Vec2::new(1.0, 0.0)).is_equal(Vec2::new(0.0, 1.0), EPSILON));
+
+        // Screen coordinates back into the local ones
+        let inverse = matrix.inverse().unwrap();
+        assert!(inverse.transform_point(Vec2::new(10.0, 6.0)).is_equal(Vec2::new(1.0, 0.0), EPSILON));
+
+        let skew = Mat3x3::with_skew(Vec2::new(std::f64::consts::FRAC_PI_4, 0.0));
+        assert!(skew.transform_point(Vec2::new(0.0, 2.0)).is_equal(Vec2::new(2.0, 2.0), EPSILON));
+
+        let projective = Mat3x3::new([
+            1.0, 0.0, 0.0,
+            0.0, 1.0, 0.0,
+            0.0, 0.5, 1.0,
+        ]);
+        assert!(projective.transform_point(Vec2::new(4.0, 2.0)).is_equal(Vec2::new(2.0, 1.0), EPSILON));
+    }
+
+    #[test]
+    fn matrix3x3_decompose() {
+        let decomposition = Decomposition {
+            translate: Vec2::new(3.0, -2.0),
+            rotation: 0.7,
+            skew: Vec2::new(0.3, 0.0),
+            scale: Vec2::new(2.0, -0.5),
+        };
+        let matrix = Mat3x3::from(decomposition);
+
+        let result = matrix.decompose().unwrap();
+        assert!(result.translate.is_equal(decomposition.translate, EPSILON));
+        assert!(result.rotation.is_equal(decomposition.rotation, EPSILON));
+        assert!(result.skew.is_equal(decomposition.skew, EPSILON));
+        assert!(result.scale.is_equal(decomposition.scale, EPSILON));
+        assert!(Mat3x3::from(result).is_equal(matrix, EPSILON));
+
+        // Skew along Y is folded into the rotation, scale and skew along X
+        let matrix = Mat3x3::with_skew(Vec2::new(0.2, -0.4)) * Mat3x3::with_scale(Vec2::new(3.0, 1.5));
+        assert!(Mat3x3::from(matrix.decompose().unwrap()).is_equal(matrix, EPSILON));
+
+        assert!(Mat3x3::with_scale(Vec2::new(0.0, 1.0)).decompose().is_none());
+    }
 }
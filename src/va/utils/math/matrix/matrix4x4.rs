@@ -1,4 +1,4 @@
-use std::ops::{Add, Index, IndexMut, Mul, Sub};
+use std::ops::{Add, Div, Index, IndexMut, Mul, Neg, Sub};
 
 use crate::utils::number::{Number, Float};
 use crate::utils::math::vector::vector3::Vec3;
@@ -34,19 +34,236 @@ impl<T> Mat4x4<T>
         &mut self.data[DIMENSION * row + column]
     }
 
+    pub fn with_translate(vector: Vec3<T>) -> Self {
+        Self {
+            data: [
+                T::ONE, T::ZERO, T::ZERO, vector.x,
+                T::ZERO, T::ONE, T::ZERO, vector.y,
+                T::ZERO, T::ZERO, T::ONE, vector.z,
+                T::ZERO, T::ZERO, T::ZERO, T::ONE,
+            ],
+        }
+    }
+
+    pub fn with_scale(vector: Vec3<T>) -> Self {
+        Self {
+            data: [
+                vector.x, T::ZERO, T::ZERO, T::ZERO,
+                T::ZERO, vector.y, T::ZERO, T::ZERO,
+                T::ZERO, T::ZERO, vector.z, T::ZERO,
+                T::ZERO, T::ZERO, T::ZERO, T::ONE,
+            ],
+        }
+    }
+
+    /// Translation is applied before the transformations of the matrix
     pub fn translate(self, vector: Vec3<T>) -> Self {
-        let translate = Mat4x4::new([
-            T::ONE, T::ZERO, T::ZERO, vector.x,
-            T::ZERO, T::ONE, T::ZERO, vector.y,
-            T::ZERO, T::ZERO, T::ONE, vector.z,
+        self * Self::with_translate(vector)
+    }
+
+    pub fn scale(self, vector: Vec3<T>) -> Self {
+        self * Self::with_scale(vector)
+    }
+
+    pub fn transpose(self) -> Self {
+        let mut matrix = self;
+        for row in 0..DIMENSION {
+            for column in 0..DIMENSION {
+                *matrix.get_mut(column, row) = self.get(row, column);
+            }
+        }
+
+        matrix
+    }
+}
+
+/// Right-handed coordinates: the camera looks along -Z with +Y up.
+/// Projections map into Vulkan clip space, where +Y is down and depth is in
+/// `[0, 1]`, so +Y stays up on the screen.
+impl<T> Mat4x4<T>
+    where T: Number + Float + Neg<Output = T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+          Vec3<T>: FromIterator<T>,
+{
+    /// Counterclockwise rotation by `angle` radians when looking from the
+    /// positive end of the axis
+    pub fn with_rotation_x(angle: T) -> Self {
+        let (sin, cos) = (angle.sin(), angle.cos());
+
+        Self::new([
+            T::ONE, T::ZERO, T::ZERO, T::ZERO,
+            T::ZERO, cos, -sin, T::ZERO,
+            T::ZERO, sin, cos, T::ZERO,
+            T::ZERO, T::ZERO, T::ZERO, T::ONE,
+        ])
+    }
+
+    pub fn with_rotation_y(angle: T) -> Self {
+        let (sin, cos) = (angle.sin(), angle.cos());
+
+        Self::new([
+            cos, T::ZERO, sin, T::ZERO,
+            T::ZERO, T::ONE, T::ZERO, T::ZERO,
+            -sin, T::ZERO, cos, T::ZERO,
+            T::ZERO, T::ZERO, T::ZERO, T::ONE,
+        ])
+    }
+
+    pub fn with_rotation_z(angle: T) -> Self {
+        let (sin, cos) = (angle.sin(), angle.cos());
+
+        Self::new([
+            cos, -sin, T::ZERO, T::ZERO,
+            sin, cos, T::ZERO, T::ZERO,
+            T::ZERO, T::ZERO, T::ONE, T::ZERO,
+            T::ZERO, T::ZERO, T::ZERO, T::ONE,
+        ])
+    }
+
+    /// Rotation about an arbitrary axis, which doesn't have to be normalized
+    // https://en.wikipedia.org/wiki/Rotation_matrix#Rotation_matrix_from_axis_and_angle
+    pub fn with_rotation(axis: Vec3<T>, angle: T) -> Self {
+        let Vec3 { x, y, z } = axis.normalize();
+        let (sin, cos) = (angle.sin(), angle.cos());
+        let k = T::ONE - cos;
+
+        Self::new([
+            cos + x * x * k, x * y * k - z * sin, x * z * k + y * sin, T::ZERO,
+            y * x * k + z * sin, cos + y * y * k, y * z * k - x * sin, T::ZERO,
+            z * x * k - y * sin, z * y * k + x * sin, cos + z * z * k, T::ZERO,
+            T::ZERO, T::ZERO, T::ZERO, T::ONE,
+        ])
+    }
+
+    pub fn rotate_x(self, angle: T) -> Self {
+        self * Self::with_rotation_x(angle)
+    }
+
+    pub fn rotate_y(self, angle: T) -> Self {
+        self * Self::with_rotation_y(angle)
+    }
+
+    pub fn rotate_z(self, angle: T) -> Self {
+        self * Self::with_rotation_z(angle)
+    }
+
+    pub fn rotate(self, axis: Vec3<T>, angle: T) -> Self {
+        self * Self::with_rotation(axis, angle)
+    }
+
+    /// View matrix of the camera at `eye` looking at `target`
+    // https://github.com/g-truc/glm/blob/master/glm/ext/matrix_transform.inl
+    pub fn look_at(eye: Vec3<T>, target: Vec3<T>, up: Vec3<T>) -> Self {
+        let forward = Vec3::new(target.x - eye.x, target.y - eye.y, target.z - eye.z).normalize();
+        let side = forward.cross(up).normalize();
+        let up = side.cross(forward);
+
+        Self::new([
+            side.x, side.y, side.z, -side.dot(eye),
+            up.x, up.y, up.z, -up.dot(eye),
+            -forward.x, -forward.y, -forward.z, forward.dot(eye),
+            T::ZERO, T::ZERO, T::ZERO, T::ONE,
+        ])
+    }
+
+    /// `fov_y` is the vertical field of view in radians, the near plane is
+    /// mapped to depth 0 and the far plane to 1
+    // https://vincent-p.github.io/posts/vulkan_perspective_matrix/
+    pub fn perspective(fov_y: T, aspect: T, near: T, far: T) -> Self {
+        let focal = T::ONE / (fov_y / T::TWO).tan();
+
+        Self::new([
+            focal / aspect, T::ZERO, T::ZERO, T::ZERO,
+            T::ZERO, -focal, T::ZERO, T::ZERO,
+            T::ZERO, T::ZERO, far / (near - far), near * far / (near - far),
+            T::ZERO, T::ZERO, -T::ONE, T::ZERO,
+        ])
+    }
+
+    /// [`Mat4x4::perspective`] with the near plane at depth 1 and the far
+    /// plane at 0, which spreads float precision evenly over the distance
+    pub fn perspective_reversed_z(fov_y: T, aspect: T, near: T, far: T) -> Self {
+        let focal = T::ONE / (fov_y / T::TWO).tan();
+
+        Self::new([
+            focal / aspect, T::ZERO, T::ZERO, T::ZERO,
+            T::ZERO, -focal, T::ZERO, T::ZERO,
+            T::ZERO, T::ZERO, near / (far - near), near * far / (far - near),
+            T::ZERO, T::ZERO, -T::ONE, T::ZERO,
+        ])
+    }
+
+    /// Maps the box to the clip space, the near plane is mapped to depth 0
+    /// and the far plane to 1. With `top < bottom` the Y axis is down, like
+    /// in pixel coordinates.
+    pub fn orthographic(left: T, right: T, bottom: T, top: T, near: T, far: T) -> Self {
+        Self::new([
+            T::TWO / (right - left), T::ZERO, T::ZERO, -(right + left) / (right - left),
+            T::ZERO, -T::TWO / (top - bottom), T::ZERO, (top + bottom) / (top - bottom),
+            T::ZERO, T::ZERO, -T::ONE / (far - near), -near / (far - near),
+            T::ZERO, T::ZERO, T::ZERO, T::ONE,
+        ])
+    }
+
+    /// [`Mat4x4::orthographic`] with the near plane at depth 1 and the far
+    /// plane at 0
+    pub fn orthographic_reversed_z(left: T, right: T, bottom: T, top: T, near: T, far: T) -> Self {
+        Self::new([
+            T::TWO / (right - left), T::ZERO, T::ZERO, -(right + left) / (right - left),
+            T::ZERO, -T::TWO / (top - bottom), T::ZERO, (top + bottom) / (top - bottom),
+            T::ZERO, T::ZERO, T::ONE / (far - near), far / (far - near),
             T::ZERO, T::ZERO, T::ZERO, T::ONE,
+        ])
+    }
+
+    pub fn determinant(&self) -> T {
+        let (s, c) = self.sub_determinants();
+        s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+    }
+
+    /// `None` if the matrix is singular
+    // https://www.geometrictools.com/Documentation/LaplaceExpansionTheorem.pdf
+    pub fn inverse(&self) -> Option<Self> {
+        let (s, c) = self.sub_determinants();
+        let determinant = s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0];
+        if determinant == T::ZERO {
+            return None;
+        }
+
+        let a = |row: usize, column: usize| self.get(column, row);
+        let matrix = Self::new([
+            a(1, 1) * c[5] - a(1, 2) * c[4] + a(1, 3) * c[3],
+            -a(0, 1) * c[5] + a(0, 2) * c[4] - a(0, 3) * c[3],
+            a(3, 1) * s[5] - a(3, 2) * s[4] + a(3, 3) * s[3],
+            -a(2, 1) * s[5] + a(2, 2) * s[4] - a(2, 3) * s[3],
+
+            -a(1, 0) * c[5] + a(1, 2) * c[2] - a(1, 3) * c[1],
+            a(0, 0) * c[5] - a(0, 2) * c[2] + a(0, 3) * c[1],
+            -a(3, 0) * s[5] + a(3, 2) * s[2] - a(3, 3) * s[1],
+            a(2, 0) * s[5] - a(2, 2) * s[2] + a(2, 3) * s[1],
+
+            a(1, 0) * c[4] - a(1, 1) * c[2] + a(1, 3) * c[0],
+            -a(0, 0) * c[4] + a(0, 1) * c[2] - a(0, 3) * c[0],
+            a(3, 0) * s[4] - a(3, 1) * s[2] + a(3, 3) * s[0],
+            -a(2, 0) * s[4] + a(2, 1) * s[2] - a(2, 3) * s[0],
+
+            -a(1, 0) * c[3] + a(1, 1) * c[1] - a(1, 2) * c[0],
+            a(0, 0) * c[3] - a(0, 1) * c[1] + a(0, 2) * c[0],
+            -a(3, 0) * s[3] + a(3, 1) * s[1] - a(3, 2) * s[0],
+            a(2, 0) * s[3] - a(2, 1) * s[1] + a(2, 2) * s[0],
         ]);
 
-        self * translate
+        Some(matrix * (T::ONE / determinant))
     }
 
-    pub fn view() -> Self {
-        todo!(); // TODO
+    /// 2x2 determinants of the top two rows and the bottom two rows
+    fn sub_determinants(&self) -> ([T; 6], [T; 6]) {
+        let a = |row: usize, column: usize| self.get(column, row);
+        let det = |r0: usize, r1: usize, c0: usize, c1: usize| a(r0, c0) * a(r1, c1) - a(r1, c0) * a(r0, c1);
+
+        (
+            [det(0, 1, 0, 1), det(0, 1, 0, 2), det(0, 1, 0, 3), det(0, 1, 1, 2), det(0, 1, 1, 3), det(0, 1, 2, 3)],
+            [det(2, 3, 0, 1), det(2, 3, 0, 2), det(2, 3, 0, 3), det(2, 3, 1, 2), det(2, 3, 1, 3), det(2, 3, 2, 3)],
+        )
     }
 }
 
@@ -160,40 +377,25 @@ impl<T> Mul<T> for Mat4x4<T>
 }
 
 impl<T> Mul<Vec4<T>> for Mat4x4<T>
-    where T: Number + std::ops::AddAssign<<T as std::ops::Mul>::Output>,
+    where T: Number,
 {
     type Output = Vec4<T>;
 
-    fn mul(self, rhs: Vec4<T>) -> Self::Output {
-        let mut vector = Vec4::default();
-
-        for i in 0..DIMENSION {
-            for j in 0..DIMENSION {
-                vector[i] += rhs[j] * self[DIMENSION * i + j];
-            }
-        }
-
-        vector
+    fn mul(self, rhs: Self::Output) -> Self::Output {
+        Vec4::from(T::mul_mat4_vec4(&self.data, rhs.into()))
     }
 }
 
+/// `data` is row-major like the arrays of [`Lanes`]
 impl<T> Mul for Mat4x4<T>
-    where T: Number + Mul<Output = T>,
+    where T: Number,
 {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        let mut matrix = Self::from(T::ZERO);
-
-        for y in 0..DIMENSION {
-            for x in 0..DIMENSION {
-                for i in 0..DIMENSION {
-                    *matrix.get_mut(x, y) += self.get(i, y) * rhs.get(x, i);
-                }
-            }
+        Self {
+            data: T::mul_mat4(&self.data, &rhs.data),
         }
-
-        matrix
     }
 }
 
@@ -319,4 +521,115 @@ mod tests {
             112, 108, 48, 84,
         ]));
     }
+
+    fn transform_point(matrix: Mat4x4<f64>, point: Vec3<f64>) -> Vec3<f64> {
+        let vector = matrix * Vec4::new(point.x, point.y, point.z, 1.0);
+        Vec3::new(vector.x / vector.w, vector.y / vector.w, vector.z / vector.w)
+    }
+
+    #[test]
+    fn matrix4x4_transforms() {
+        use std::f64::consts::FRAC_PI_2;
+
+        let matrix = Mat4x4::default()
+            .translate(Vec3::new(1.0, 2.0, 3.0))
+            .scale(Vec3::new(2.0, 3.0, 4.0));
+        let point = transform_point(matrix, Vec3::new(1.0, 1.0, 1.0));
+        assert!(point.is_equal(Vec3::new(3.0, 5.0, 7.0), EPSILON));
+
+        let point = transform_point(Mat4x4::with_rotation_z(FRAC_PI_2), Vec3::X);
+        assert!(point.is_equal(Vec3::Y, EPSILON));
+        let point = transform_point(Mat4x4::with_rotation_x(FRAC_PI_2), Vec3::Y);
+        assert!(point.is_equal(Vec3::Z, EPSILON));
+        let point = transform_point(Mat4x4::with_rotation_y(FRAC_PI_2), Vec3::Z);
+        assert!(point.is_equal(Vec3::X, EPSILON));
+
+        let axis = Vec3::new(0.0, 0.0, 2.0);
+        assert!(Mat4x4::with_rotation(axis, 0.7).is_equal(Mat4x4::with_rotation_z(0.7), EPSILON));
+        assert!(Mat4x4::with_rotation(Vec3::Y, 0.3).is_equal(Mat4x4::default().rotate_y(0.3), EPSILON));
+
+        // Rotation about the diagonal swaps the axes
+        let matrix = Mat4x4::with_rotation(Vec3::from(1.0), 2.0 * std::f64::consts::PI / 3.0);
+        assert!(transform_point(matrix, Vec3::X).is_equal(Vec3::Y, EPSILON));
+    }
+
+    #[test]
+    fn matrix4x4_inverse() {
+        let matrix = Mat4x4::new([
+            3.0, 4.0, 1.0, 4.0,
+            2.0, 4.0, 3.0, 1.0,
+            2.0, 1.0, 7.0, 7.0,
+            9.0, 6.0, 4.0, 5.0,
+        ]);
+        assert!(matrix.determinant().is_equal(-640.0, EPSILON));
+        assert_eq!(matrix.transpose().get(0, 3), 4.0);
+        assert_eq!(matrix.transpose().transpose(), matrix);
+
+        let inverse = matrix.inverse().unwrap();
+        assert!((matrix * inverse).is_equal(Mat4x4::default(), EPSILON));
+        assert!((inverse * matrix).is_equal(Mat4x4::default(), EPSILON));
+
+        let transform = Mat4x4::with_translate(Vec3::new(1.0, -2.0, 5.0)).rotate_x(0.4).scale(Vec3::new(2.0, 1.0, 0.5));
+        assert!((transform.inverse().unwrap() * transform).is_equal(Mat4x4::default(), EPSILON));
+
+        assert!(Mat4x4::<f64>::from(1.0).inverse().is_none());
+        assert_eq!(Mat4x4::<f64>::from(1.0).determinant(), 0.0);
+    }
+
+    #[test]
+    fn matrix4x4_lanes() {
+        // Products of the `simd` feature are the same as the scalar ones
+        let a = Mat4x4::perspective(1.0f32, 1.5, 0.1, 100.0);
+        let b = Mat4x4::look_at(Vec3::new(1.0, 2.0, 3.0), Vec3::ZERO, Vec3::Y).rotate_x(0.3);
+        let vector = Vec4::new(0.1, -7.0, 3.3, 1.0);
+
+        let mut product = Mat4x4::from(0.0);
+        for y in 0..DIMENSION {
+            for x in 0..DIMENSION {
+                for i in 0..DIMENSION {
+                    *product.get_mut(x, y) += a.get(i, y) * b.get(x, i);
+                }
+            }
+        }
+
+        let mut transformed = Vec4::from(0.0);
+        for y in 0..DIMENSION {
+            for i in 0..DIMENSION {
+                transformed[y] += a.get(i, y) * vector[i];
+            }
+        }
+
+        assert_eq!((a * b).data.map(f32::to_bits), product.data.map(f32::to_bits));
+        assert_eq!(<[f32; 4]>::from(a * vector).map(f32::to_bits), <[f32; 4]>::from(transformed).map(f32::to_bits));
+    }
+
+    #[test]
+    fn matrix4x4_camera() {
+        let view = Mat4x4::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+        assert!(transform_point(view, Vec3::ZERO).is_equal(Vec3::new(0.0, 0.0, -5.0), EPSILON));
+        assert!(transform_point(view, Vec3::new(1.0, 2.0, 0.0)).is_equal(Vec3::new(1.0, 2.0, -5.0), EPSILON));
+
+        let view = Mat4x4::look_at(Vec3::new(3.0, 0.0, 0.0), Vec3::ZERO, Vec3::Y);
+        assert!(transform_point(view, Vec3::new(0.0, 0.0, -1.0)).is_equal(Vec3::new(1.0, 0.0, -3.0), EPSILON));
+
+        let projection = Mat4x4::perspective(std::f64::consts::FRAC_PI_2, 2.0, 0.1, 100.0);
+        assert!(transform_point(projection, Vec3::new(0.0, 0.0, -0.1)).is_equal(Vec3::ZERO, EPSILON));
+        assert!(transform_point(projection, Vec3::new(0.0, 0.0, -100.0)).is_equal(Vec3::Z, EPSILON));
+
+        // Up in the view space is up on the screen
+        assert!(transform_point(projection, Vec3::new(2.0, 1.0, -1.0)).is_equal(Vec3::new(1.0, -1.0, 0.9009), EPSILON));
+
+        let projection = Mat4x4::perspective_reversed_z(std::f64::consts::FRAC_PI_2, 2.0, 0.1, 100.0);
+        assert!(transform_point(projection, Vec3::new(0.0, 0.0, -0.1)).is_equal(Vec3::Z, EPSILON));
+        assert!(transform_point(projection, Vec3::new(0.0, 0.0, -100.0)).is_equal(Vec3::ZERO, EPSILON));
+
+        // Pixel coordinates
+        let projection = Mat4x4::orthographic(0.0, 800.0, 600.0, 0.0, 0.0, 1.0);
+        assert!(transform_point(projection, Vec3::ZERO).is_equal(Vec3::new(-1.0, -1.0, 0.0), EPSILON));
+        assert!(transform_point(projection, Vec3::new(800.0, 600.0, -1.0)).is_equal(Vec3::new(1.0, 1.0, 1.0), EPSILON));
+
+        let projection = Mat4x4::orthographic_reversed_z(-1.0, 1.0, -1.0, 1.0, 1.0, 3.0);
+        assert!(transform_point(projection, Vec3::new(0.0, 1.0, -1.0)).is_equal(Vec3::new(0.0, -1.0, 1.0), EPSILON));
+        assert!(transform_point(projection, Vec3::new(0.0, 0.0, -3.0)).is_equal(Vec3::ZERO, EPSILON));
+    }
 }
@@ -1,5 +1,7 @@
 pub mod geometry;
 pub mod matrix;
+pub mod quaternion;
+pub mod simd;
 pub mod vector;
 pub mod constants;
 pub mod is_equal;
\ No newline at end of file
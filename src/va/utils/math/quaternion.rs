@@ -0,0 +1,444 @@
+use std::fmt::Display;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::utils::math::is_equal::IsCopyTypeEqual;
+use crate::utils::math::matrix::matrix3x3::Mat3x3;
+use crate::utils::math::matrix::matrix4x4::Mat4x4;
+use crate::utils::math::vector::utils::{impl_vector_cast, impl_vector_from};
+use crate::utils::math::vector::vector3::Vec3;
+use crate::utils::number::{Number, Float};
+
+/// Rotation quaternion `w + xi + yj + zk`, rotations follow the right-hand
+/// rule like [`Mat4x4::with_rotation`].
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Quat<T>
+    where T: Number + Float,
+{
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T,
+}
+
+impl<T> Quat<T>
+    where T: Number + Float,
+{
+    pub const IDENTITY: Quat<T> = Quat::new(T::ZERO, T::ZERO, T::ZERO, T::ONE);
+
+    pub const fn new(x: T, y: T, z: T, w: T) -> Self {
+        Self {
+            x, y, z, w
+        }
+    }
+
+    pub fn xyz(self) -> Vec3<T> {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+impl<T> Quat<T>
+    where T: Number + Float + Neg<Output = T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+          Vec3<T>: FromIterator<T>,
+{
+    /// Rotation by `angle` radians about the axis, which doesn't have to be
+    /// normalized
+    pub fn from_axis_angle(axis: Vec3<T>, angle: T) -> Self {
+        let axis = axis.normalize();
+        let half = angle / T::TWO;
+        let sin = half.sin();
+
+        Self::new(axis.x * sin, axis.y * sin, axis.z * sin, half.cos())
+    }
+
+    /// Rotation about X, then about Y and then about Z, the same as
+    /// `with_rotation_z(z) * with_rotation_y(y) * with_rotation_x(x)`
+    pub fn from_euler(x: T, y: T, z: T) -> Self {
+        Self::from_axis_angle(Vec3::Z, z) * Self::from_axis_angle(Vec3::Y, y) * Self::from_axis_angle(Vec3::X, x)
+    }
+
+    /// Normalized axis and the angle in `[0, 2π]`, the axis is X for the
+    /// identity
+    pub fn to_axis_angle(self) -> (Vec3<T>, T) {
+        let quat = self.normalize();
+        let sin = quat.xyz().lenght();
+        let angle = T::TWO * sin.atan2(quat.w);
+
+        if sin == T::ZERO {
+            (Vec3::X, angle)
+        }
+        else {
+            (Vec3::new(quat.x / sin, quat.y / sin, quat.z / sin), angle)
+        }
+    }
+
+    pub fn dot(self, quat: Self) -> T {
+        self.x * quat.x + self.y * quat.y + self.z * quat.z + self.w * quat.w
+    }
+
+    pub fn lenght(self) -> T {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(self) -> Self {
+        let length = self.lenght();
+        Self::new(self.x / length, self.y / length, self.z / length, self.w / length)
+    }
+
+    /// Inverse rotation of a normalized quaternion
+    pub fn conjugate(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    pub fn inverse(self) -> Self {
+        let k = T::ONE / self.dot(self);
+        self.conjugate() * k
+    }
+
+    /// Normalized linear interpolation by the shortest path, `k` is in
+    /// `[0, 1]`
+    pub fn nlerp(self, quat: Self, k: T) -> Self {
+        let quat = if self.dot(quat) < T::ZERO { -quat } else { quat };
+        (self * (T::ONE - k) + quat * k).normalize()
+    }
+
+    /// Spherical linear interpolation by the shortest path with the constant
+    /// angular velocity
+    // https://en.wikipedia.org/wiki/Slerp
+    pub fn slerp(self, quat: Self, k: T) -> Self {
+        let (quat, dot) = if self.dot(quat) < T::ZERO { (-quat, -self.dot(quat)) } else { (quat, self.dot(quat)) };
+
+        // Almost the same rotations, the angle is too small for division
+        if dot > T::new(0.9995) {
+            return self.nlerp(quat, k);
+        }
+
+        let angle = dot.acos();
+        let sin = angle.sin();
+
+        self * (((T::ONE - k) * angle).sin() / sin) + quat * ((k * angle).sin() / sin)
+    }
+
+    /// Rotates the vector by a normalized quaternion
+    // https://fgiesen.wordpress.com/2019/02/09/rotating-a-single-vector-using-a-quaternion/
+    pub fn rotate(self, vector: Vec3<T>) -> Vec3<T> {
+        let t = self.xyz().cross(vector);
+        let t = Vec3::new(t.x * T::TWO, t.y * T::TWO, t.z * T::TWO);
+        let u = self.xyz().cross(t);
+
+        Vec3::new(
+            vector.x + self.w * t.x + u.x,
+            vector.y + self.w * t.y + u.y,
+            vector.z + self.w * t.z + u.z,
+        )
+    }
+
+    /// Rows of the rotation matrix of a normalized quaternion
+    fn to_rows(self) -> [[T; 3]; 3] {
+        let Self { x, y, z, w } = self;
+        let two = T::TWO;
+
+        [
+            [T::ONE - two * (y * y + z * z), two * (x * y - z * w), two * (x * z + y * w)],
+            [two * (x * y + z * w), T::ONE - two * (x * x + z * z), two * (y * z - x * w)],
+            [two * (x * z - y * w), two * (y * z + x * w), T::ONE - two * (x * x + y * y)],
+        ]
+    }
+
+    /// Quaternion of a rotation matrix, `m(row, column)` is its element
+    // https://www.euclideanspace.com/maths/geometry/rotations/conversions/matrixToQuaternion/
+    fn from_rows<F>(m: F) -> Self
+        where F: Fn(usize, usize) -> T,
+    {
+        let trace = m(0, 0) + m(1, 1) + m(2, 2);
+        let quarter = T::new(0.25);
+
+        let quat = if trace > T::ZERO {
+            let s = (trace + T::ONE).sqrt() * T::TWO;
+            Self::new((m(2, 1) - m(1, 2)) / s, (m(0, 2) - m(2, 0)) / s, (m(1, 0) - m(0, 1)) / s, quarter * s)
+        }
+        else if m(0, 0) > m(1, 1) && m(0, 0) > m(2, 2) {
+            let s = (T::ONE + m(0, 0) - m(1, 1) - m(2, 2)).sqrt() * T::TWO;
+            Self::new(quarter * s, (m(0, 1) + m(1, 0)) / s, (m(0, 2) + m(2, 0)) / s, (m(2, 1) - m(1, 2)) / s)
+        }
+        else if m(1, 1) > m(2, 2) {
+            let s = (T::ONE + m(1, 1) - m(0, 0) - m(2, 2)).sqrt() * T::TWO;
+            Self::new((m(0, 1) + m(1, 0)) / s, quarter * s, (m(1, 2) + m(2, 1)) / s, (m(0, 2) - m(2, 0)) / s)
+        }
+        else {
+            let s = (T::ONE + m(2, 2) - m(0, 0) - m(1, 1)).sqrt() * T::TWO;
+            Self::new((m(0, 2) + m(2, 0)) / s, (m(1, 2) + m(2, 1)) / s, quarter * s, (m(1, 0) - m(0, 1)) / s)
+        };
+
+        quat.normalize()
+    }
+}
+
+impl<T> Default for Quat<T>
+    where T: Number + Float,
+{
+    /// Returns identity quaternion
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl<T> Add for Quat<T>
+    where T: Number + Float + Add<Output = T>,
+{
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z, self.w + rhs.w)
+    }
+}
+
+impl<T> Neg for Quat<T>
+    where T: Number + Float + Neg<Output = T>,
+{
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y, -self.z, -self.w)
+    }
+}
+
+impl<T> Mul<T> for Quat<T>
+    where T: Number + Float + Mul<Output = T>,
+{
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs, self.w * rhs)
+    }
+}
+
+impl<T> Mul for Quat<T>
+    where T: Number + Float + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    type Output = Self;
+
+    /// Rotation by `rhs` and then by `self`
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        )
+    }
+}
+
+impl<T> Mul<Vec3<T>> for Quat<T>
+    where T: Number + Float + Neg<Output = T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+          Vec3<T>: FromIterator<T>,
+{
+    type Output = Vec3<T>;
+
+    fn mul(self, rhs: Vec3<T>) -> Self::Output {
+        self.rotate(rhs)
+    }
+}
+
+impl<T> From<Quat<T>> for Mat3x3<T>
+    where T: Number + Float + Neg<Output = T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+          Vec3<T>: FromIterator<T>,
+{
+    fn from(value: Quat<T>) -> Self {
+        let [r0, r1, r2] = value.normalize().to_rows();
+
+        Mat3x3::new([
+            r0[0], r0[1], r0[2],
+            r1[0], r1[1], r1[2],
+            r2[0], r2[1], r2[2],
+        ])
+    }
+}
+
+impl<T> From<Quat<T>> for Mat4x4<T>
+    where T: Number + Float + Neg<Output = T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+          Vec3<T>: FromIterator<T>,
+{
+    fn from(value: Quat<T>) -> Self {
+        let [r0, r1, r2] = value.normalize().to_rows();
+
+        Mat4x4::new([
+            r0[0], r0[1], r0[2], T::ZERO,
+            r1[0], r1[1], r1[2], T::ZERO,
+            r2[0], r2[1], r2[2], T::ZERO,
+            T::ZERO, T::ZERO, T::ZERO, T::ONE,
+        ])
+    }
+}
+
+impl<T> From<Mat3x3<T>> for Quat<T>
+    where T: Number + Float + Neg<Output = T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+          Vec3<T>: FromIterator<T>,
+{
+    /// The matrix must be a rotation without scale
+    fn from(value: Mat3x3<T>) -> Self {
+        Self::from_rows(|row, column| value.get(column, row))
+    }
+}
+
+impl<T> From<Mat4x4<T>> for Quat<T>
+    where T: Number + Float + Neg<Output = T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+          Vec3<T>: FromIterator<T>,
+{
+    /// Rotation of the upper-left 3x3 part, which must have no scale
+    fn from(value: Mat4x4<T>) -> Self {
+        Self::from_rows(|row, column| value.get(column, row))
+    }
+}
+
+impl<T> From<(T, T, T, T)> for Quat<T>
+    where T: Number + Float,
+{
+    fn from(value: (T, T, T, T)) -> Self {
+        Self::new(value.0, value.1, value.2, value.3)
+    }
+}
+
+impl<T> IsCopyTypeEqual for Quat<T>
+    where T: Number + Float + Into<f64>,
+{
+    fn is_equal(self, other: Self, epsilon: f64) -> bool {
+        [(self.x, other.x), (self.y, other.y), (self.z, other.z), (self.w, other.w)]
+            .into_iter()
+            .all(|(a, b)| a.into().is_equal(b.into(), epsilon))
+    }
+}
+
+impl<T> Display for Quat<T>
+    where T: Number + Float,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Quat({}; {}; {}; {})", self.x, self.y, self.z, self.w)
+    }
+}
+
+impl<T> IntoIterator for Quat<T>
+    where T: Number + Float,
+{
+    type Item = T;
+    type IntoIter = std::array::IntoIter<T, 4>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        [self.x, self.y, self.z, self.w].into_iter()
+    }
+}
+
+impl<T> FromIterator<T> for Quat<T>
+    where T: Number + Float,
+{
+    /// # Panics
+    ///
+    /// Panics if iterator is invalid
+    fn from_iter<Iter>(iter: Iter) -> Self
+        where Iter: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
+        Self {
+            x: iter.next().expect("invalid iterator"),
+            y: iter.next().expect("invalid iterator"),
+            z: iter.next().expect("invalid iterator"),
+            w: iter.next().expect("invalid iterator"),
+        }
+    }
+}
+
+// Components are floats only
+impl_vector_from!(Quat, f64, f32);
+
+impl_vector_cast!(Quat, f32, f32, f64);
+impl_vector_cast!(Quat, f64, f32, f64);
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    use crate::utils::cast::Cast;
+    use crate::utils::math::vector::vector4::Vec4;
+
+    use super::*;
+
+    const EPSILON: f64 = 0.001;
+
+    /// Quaternions `q` and `-q` are the same rotation
+    fn is_same_rotation(a: Quat<f64>, b: Quat<f64>) -> bool {
+        a.is_equal(b, EPSILON) || a.is_equal(-b, EPSILON)
+    }
+
+    #[test]
+    fn quaternion_rotation() {
+        let quat = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 3.0), FRAC_PI_2);
+        assert!((quat * Vec3::X).is_equal(Vec3::Y, EPSILON));
+        assert!(quat.lenght().is_equal(1.0, EPSILON));
+
+        let (axis, angle) = quat.to_axis_angle();
+        assert!(axis.is_equal(Vec3::Z, EPSILON));
+        assert!(angle.is_equal(FRAC_PI_2, EPSILON));
+        assert_eq!(Quat::<f64>::default().to_axis_angle(), (Vec3::X, 0.0));
+
+        // Composition applies the right rotation first
+        let x = Quat::from_axis_angle(Vec3::X, FRAC_PI_2);
+        assert!(((quat * x) * Vec3::Y).is_equal(Vec3::Z, EPSILON));
+        assert!(((x * quat) * Vec3::X).is_equal(Vec3::Z, EPSILON));
+
+        assert!(is_same_rotation(quat * quat.conjugate(), Quat::IDENTITY));
+        assert!(is_same_rotation((quat * 2.0).inverse() * 2.0, quat.conjugate()));
+        assert!((quat * 3.0).normalize().is_equal(quat, EPSILON));
+    }
+
+    #[test]
+    fn quaternion_euler_and_matrices() {
+        let (x, y, z) = (0.3, -1.2, 2.5);
+        let quat = Quat::from_euler(x, y, z);
+        let matrix = Mat4x4::with_rotation_z(z) * Mat4x4::with_rotation_y(y) * Mat4x4::with_rotation_x(x);
+        assert!(Mat4x4::from(quat).is_equal(matrix, EPSILON));
+
+        let vector = Vec3::new(1.0, -2.0, 0.5);
+        let rotated = matrix * Vec4::new(vector.x, vector.y, vector.z, 1.0);
+        assert!((quat * vector).is_equal(rotated.xyz(), EPSILON));
+
+        // Every branch of the matrix conversion
+        for quat in [
+            quat,
+            Quat::from_axis_angle(Vec3::X, PI),
+            Quat::from_axis_angle(Vec3::Y, 0.9 * PI),
+            Quat::from_axis_angle(Vec3::new(0.1, 0.2, 1.0), 3.0),
+        ] {
+            assert!(is_same_rotation(Quat::from(Mat4x4::from(quat)), quat));
+            assert!(is_same_rotation(Quat::from(Mat3x3::from(quat)), quat));
+        }
+    }
+
+    #[test]
+    fn quaternion_interpolation() {
+        let a = Quat::from_axis_angle(Vec3::Z, 0.0);
+        let b = Quat::from_axis_angle(Vec3::Z, FRAC_PI_2);
+
+        assert!(a.slerp(b, 0.0).is_equal(a, EPSILON));
+        assert!(a.slerp(b, 1.0).is_equal(b, EPSILON));
+        assert!(a.slerp(b, 0.25).is_equal(Quat::from_axis_angle(Vec3::Z, FRAC_PI_2 * 0.25), EPSILON));
+        assert!(is_same_rotation(a.nlerp(b, 0.5), Quat::from_axis_angle(Vec3::Z, FRAC_PI_2 * 0.5)));
+
+        // The shortest path for the opposite sign
+        assert!(is_same_rotation(a.slerp(-b, 0.5), Quat::from_axis_angle(Vec3::Z, FRAC_PI_2 * 0.5)));
+        assert!(a.slerp(a * 1.0, 0.5).is_equal(a, EPSILON));
+    }
+
+    #[test]
+    fn quaternion_casts() {
+        let quat = Quat::new(0.5f32, 0.5, 0.5, 0.5);
+        let cast: Quat<f64> = quat.cast();
+        assert_eq!(cast, Quat::from(quat));
+        assert_eq!(Quat::from((0.5, 0.5, 0.5, 0.5)), cast);
+
+        let quat: Quat<f64> = [1.0, 2.0, 3.0, 4.0].into_iter().collect();
+        assert_eq!(quat, Quat::new(1.0, 2.0, 3.0, 4.0));
+        assert_eq!(quat.into_iter().sum::<f64>(), 10.0);
+        let cast: Quat<f32> = quat.cast();
+        assert_eq!(cast, Quat::new(1.0, 2.0, 3.0, 4.0));
+    }
+}
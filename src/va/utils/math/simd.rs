@@ -0,0 +1,378 @@
+//! Lane-wise arithmetic behind `Vec4` and `Mat4x4`.
+//!
+//! Every number type implements [`Lanes`] with the scalar code of [`scalar`].
+//! With the `simd` feature `f32` uses SSE2 on x86_64, and AVX for matrix
+//! products if it's enabled at compile time (`-C target-feature=+avx`). Other
+//! targets keep the portable scalar code. Results are bit-identical to the
+//! scalar ones, operations are done in the same order and without FMA.
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::utils::cast::Cast;
+use crate::utils::number::{Float, Number};
+
+pub trait Lanes:
+    Copy
+    + Default
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    fn add4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+        scalar::add4(a, b)
+    }
+
+    fn sub4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+        scalar::sub4(a, b)
+    }
+
+    fn mul4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+        scalar::mul4(a, b)
+    }
+
+    fn div4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+        scalar::div4(a, b)
+    }
+
+    /// Product of row-major 4x4 matrices
+    fn mul_mat4(a: &[Self; 16], b: &[Self; 16]) -> [Self; 16] {
+        scalar::mul_mat4(a, b)
+    }
+
+    /// Product of a row-major 4x4 matrix and a column vector
+    fn mul_mat4_vec4(a: &[Self; 16], vector: [Self; 4]) -> [Self; 4] {
+        scalar::mul_mat4_vec4(a, vector)
+    }
+}
+
+pub trait FloatLanes: Number + Float + Cast<u8> {
+    /// Clamps to `[0, 1]` and rounds to 8-bit values, NaN is 0
+    fn to_unorm8(a: [Self; 4]) -> [u8; 4] {
+        scalar::to_unorm8(a)
+    }
+}
+
+macro_rules! impl_lanes {
+    ($($n:ty),*) => {
+        $(
+            impl Lanes for $n {}
+        )*
+    };
+}
+
+impl_lanes!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f64);
+
+impl FloatLanes for f64 {}
+
+impl Lanes for f32 {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[inline]
+    fn add4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+        sse::add4(a, b)
+    }
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[inline]
+    fn sub4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+        sse::sub4(a, b)
+    }
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[inline]
+    fn mul4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+        sse::mul4(a, b)
+    }
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[inline]
+    fn div4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+        sse::div4(a, b)
+    }
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[inline]
+    fn mul_mat4(a: &[f32; 16], b: &[f32; 16]) -> [f32; 16] {
+        sse::mul_mat4(a, b)
+    }
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[inline]
+    fn mul_mat4_vec4(a: &[f32; 16], vector: [f32; 4]) -> [f32; 4] {
+        sse::mul_mat4_vec4(a, vector)
+    }
+}
+
+impl FloatLanes for f32 {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[inline]
+    fn to_unorm8(a: [f32; 4]) -> [u8; 4] {
+        sse::to_unorm8(a)
+    }
+}
+
+/// Portable implementation, the reference for the SIMD one
+pub mod scalar {
+    use super::*;
+
+    #[inline]
+    pub fn add4<T>(a: [T; 4], b: [T; 4]) -> [T; 4]
+        where T: Copy + Add<Output = T>,
+    {
+        [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]]
+    }
+
+    #[inline]
+    pub fn sub4<T>(a: [T; 4], b: [T; 4]) -> [T; 4]
+        where T: Copy + Sub<Output = T>,
+    {
+        [a[0] - b[0], a[1] - b[1], a[2] - b[2], a[3] - b[3]]
+    }
+
+    #[inline]
+    pub fn mul4<T>(a: [T; 4], b: [T; 4]) -> [T; 4]
+        where T: Copy + Mul<Output = T>,
+    {
+        [a[0] * b[0], a[1] * b[1], a[2] * b[2], a[3] * b[3]]
+    }
+
+    #[inline]
+    pub fn div4<T>(a: [T; 4], b: [T; 4]) -> [T; 4]
+        where T: Copy + Div<Output = T>,
+    {
+        [a[0] / b[0], a[1] / b[1], a[2] / b[2], a[3] / b[3]]
+    }
+
+    #[inline]
+    pub fn mul_mat4<T>(a: &[T; 16], b: &[T; 16]) -> [T; 16]
+        where T: Copy + Default + Add<Output = T> + Mul<Output = T>,
+    {
+        let mut matrix = [T::default(); 16];
+
+        for row in 0..4 {
+            for column in 0..4 {
+                for i in 0..4 {
+                    matrix[4 * row + column] = matrix[4 * row + column] + a[4 * row + i] * b[4 * i + column];
+                }
+            }
+        }
+
+        matrix
+    }
+
+    #[inline]
+    pub fn mul_mat4_vec4<T>(a: &[T; 16], vector: [T; 4]) -> [T; 4]
+        where T: Copy + Default + Add<Output = T> + Mul<Output = T>,
+    {
+        let mut result = [T::default(); 4];
+
+        for row in 0..4 {
+            for i in 0..4 {
+                result[row] = result[row] + vector[i] * a[4 * row + i];
+            }
+        }
+
+        result
+    }
+
+    #[inline]
+    pub fn to_unorm8<T>(a: [T; 4]) -> [u8; 4]
+        where T: Number + Float + Cast<u8>,
+    {
+        a.map(|val| (val.clamp(Float::new(0.0), Float::new(1.0)) * Float::new(255.0)).round().cast())
+    }
+}
+
+// SAFETY: SSE2 is a part of the x86_64 baseline and AVX functions are only
+// compiled with AVX enabled. Loads and stores are unaligned and stay inside
+// of the arrays.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod sse {
+    use std::arch::x86_64::*;
+
+    #[inline]
+    unsafe fn load(a: &[f32; 4]) -> __m128 {
+        _mm_loadu_ps(a.as_ptr())
+    }
+
+    #[inline]
+    unsafe fn store(a: __m128) -> [f32; 4] {
+        let mut result = [0.0; 4];
+        _mm_storeu_ps(result.as_mut_ptr(), a);
+        result
+    }
+
+    #[inline]
+    unsafe fn rows(a: &[f32; 16]) -> [__m128; 4] {
+        let rows: &[[f32; 4]; 4] = bytemuck::cast_ref(a);
+        [load(&rows[0]), load(&rows[1]), load(&rows[2]), load(&rows[3])]
+    }
+
+    #[inline]
+    pub fn add4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+        unsafe { store(_mm_add_ps(load(&a), load(&b))) }
+    }
+
+    #[inline]
+    pub fn sub4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+        unsafe { store(_mm_sub_ps(load(&a), load(&b))) }
+    }
+
+    #[inline]
+    pub fn mul4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+        unsafe { store(_mm_mul_ps(load(&a), load(&b))) }
+    }
+
+    #[inline]
+    pub fn div4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+        unsafe { store(_mm_div_ps(load(&a), load(&b))) }
+    }
+
+    /// Every row of the result is a sum of rows of `b` scaled by the row of
+    /// `a`
+    #[cfg(not(target_feature = "avx"))]
+    #[inline]
+    pub fn mul_mat4(a: &[f32; 16], b: &[f32; 16]) -> [f32; 16] {
+        let a: &[[f32; 4]; 4] = bytemuck::cast_ref(a);
+        let mut result = [[0.0; 4]; 4];
+
+        unsafe {
+            let b = rows(b);
+
+            for (row, a) in result.iter_mut().zip(a) {
+                let mut sum = _mm_setzero_ps();
+                for i in 0..4 {
+                    sum = _mm_add_ps(sum, _mm_mul_ps(_mm_set1_ps(a[i]), b[i]));
+                }
+
+                *row = store(sum);
+            }
+        }
+
+        bytemuck::cast(result)
+    }
+
+    /// Two rows of the result at once
+    #[cfg(target_feature = "avx")]
+    #[inline]
+    pub fn mul_mat4(a: &[f32; 16], b: &[f32; 16]) -> [f32; 16] {
+        let a: &[[f32; 8]; 2] = bytemuck::cast_ref(a);
+        let mut result = [[0.0; 8]; 2];
+
+        unsafe {
+            let b = rows(b).map(|row| _mm256_set_m128(row, row));
+
+            for (rows, a) in result.iter_mut().zip(a) {
+                let a = _mm256_loadu_ps(a.as_ptr());
+
+                let mut sum = _mm256_setzero_ps();
+                sum = _mm256_add_ps(sum, _mm256_mul_ps(_mm256_shuffle_ps::<0x00>(a, a), b[0]));
+                sum = _mm256_add_ps(sum, _mm256_mul_ps(_mm256_shuffle_ps::<0x55>(a, a), b[1]));
+                sum = _mm256_add_ps(sum, _mm256_mul_ps(_mm256_shuffle_ps::<0xaa>(a, a), b[2]));
+                sum = _mm256_add_ps(sum, _mm256_mul_ps(_mm256_shuffle_ps::<0xff>(a, a), b[3]));
+
+                _mm256_storeu_ps(rows.as_mut_ptr(), sum);
+            }
+        }
+
+        bytemuck::cast(result)
+    }
+
+    /// Sum of columns scaled by the vector
+    #[inline]
+    pub fn mul_mat4_vec4(a: &[f32; 16], vector: [f32; 4]) -> [f32; 4] {
+        unsafe {
+            let [r0, r1, r2, r3] = rows(a);
+
+            // Transpose
+            let (t0, t1) = (_mm_unpacklo_ps(r0, r1), _mm_unpacklo_ps(r2, r3));
+            let (t2, t3) = (_mm_unpackhi_ps(r0, r1), _mm_unpackhi_ps(r2, r3));
+            let columns = [_mm_movelh_ps(t0, t1), _mm_movehl_ps(t1, t0), _mm_movelh_ps(t2, t3), _mm_movehl_ps(t3, t2)];
+
+            let mut sum = _mm_setzero_ps();
+            for (column, value) in columns.into_iter().zip(vector) {
+                sum = _mm_add_ps(sum, _mm_mul_ps(_mm_set1_ps(value), column));
+            }
+
+            store(sum)
+        }
+    }
+
+    /// `f32::round` rounds half away from zero, SSE2 has no rounding, so the
+    /// fraction after truncation is compared with 0.5
+    #[inline]
+    pub fn to_unorm8(a: [f32; 4]) -> [u8; 4] {
+        unsafe {
+            // `max` returns the second operand for NaN
+            let value = _mm_min_ps(_mm_max_ps(load(&a), _mm_setzero_ps()), _mm_set1_ps(1.0));
+            let value = _mm_mul_ps(value, _mm_set1_ps(255.0));
+
+            let truncated = _mm_cvtepi32_ps(_mm_cvttps_epi32(value));
+            let is_half = _mm_cmpge_ps(_mm_sub_ps(value, truncated), _mm_set1_ps(0.5));
+            let rounded = _mm_add_ps(truncated, _mm_and_ps(is_half, _mm_set1_ps(1.0)));
+
+            let integers = _mm_cvttps_epi32(rounded);
+            let bytes = _mm_packus_epi16(_mm_packs_epi32(integers, integers), _mm_setzero_si128());
+
+            (_mm_cvtsi128_si32(bytes) as u32).to_le_bytes()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// NaNs are equal, zeros of different signs aren't
+    fn bits<const N: usize>(a: [f32; N]) -> [u32; N] {
+        a.map(|val| if val.is_nan() { f32::NAN.to_bits() } else { val.to_bits() })
+    }
+
+    fn float() -> impl Strategy<Value = f32> {
+        prop_oneof![
+            8 => -1000.0f32..1000.0,
+            1 => any::<f32>(),
+            1 => prop::sample::select(vec![0.0, -0.0, 0.5, 1.0, f32::INFINITY, f32::NEG_INFINITY, f32::NAN, f32::MIN_POSITIVE / 2.0]),
+        ]
+    }
+
+    fn vector() -> impl Strategy<Value = [f32; 4]> {
+        prop::array::uniform4(float())
+    }
+
+    fn matrix() -> impl Strategy<Value = [f32; 16]> {
+        prop::collection::vec(float(), 16).prop_map(|data| data.try_into().unwrap())
+    }
+
+    proptest! {
+        #[test]
+        fn simd_vector_arithmetic(a in vector(), b in vector()) {
+            prop_assert_eq!(bits(f32::add4(a, b)), bits(scalar::add4(a, b)));
+            prop_assert_eq!(bits(f32::sub4(a, b)), bits(scalar::sub4(a, b)));
+            prop_assert_eq!(bits(f32::mul4(a, b)), bits(scalar::mul4(a, b)));
+            prop_assert_eq!(bits(f32::div4(a, b)), bits(scalar::div4(a, b)));
+        }
+
+        #[test]
+        fn simd_matrix_products(a in matrix(), b in matrix(), vector in vector()) {
+            prop_assert_eq!(bits(f32::mul_mat4(&a, &b)), bits(scalar::mul_mat4(&a, &b)));
+            prop_assert_eq!(bits(f32::mul_mat4_vec4(&a, vector)), bits(scalar::mul_mat4_vec4(&a, vector)));
+        }
+
+        #[test]
+        fn simd_to_unorm8(a in prop::array::uniform4(prop_oneof![-0.5f32..1.5, float()])) {
+            prop_assert_eq!(f32::to_unorm8(a), scalar::to_unorm8(a));
+        }
+    }
+
+    #[test]
+    fn simd_to_unorm8_rounding() {
+        // Halves round up, the largest float below a half rounds down
+        let below_half = f32::from_bits(0.5f32.to_bits() - 1);
+        let values = [0.5 / 255.0, 2.5 / 255.0, below_half / 255.0, 254.5 / 255.0];
+
+        assert_eq!(f32::to_unorm8(values), scalar::to_unorm8(values));
+        assert_eq!(f32::to_unorm8([f32::NAN, -0.0, f32::INFINITY, 0.2]), [0, 0, 255, 51]);
+    }
+}
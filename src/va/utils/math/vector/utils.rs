@@ -446,31 +446,6 @@ macro_rules! impl_common_vector_methods {
             }
         }
 
-        impl<T> Add<T> for $vector<T>
-            where T: Number,
-                  $vector<T>: FromIterator<<T as Add>::Output>,
-        {
-            type Output = Self;
-
-            fn add(self, rhs: T) -> Self::Output {
-                self.into_iter().map(|val| val + rhs).collect()
-            }
-        }
-
-        impl<T> Add for $vector<T>
-            where T: Number,
-                  $vector<T>: FromIterator<<T as Add>::Output>,
-        {
-            type Output = Self;
-
-            fn add(self, rhs: Self) -> Self::Output {
-                self.into_iter()
-                    .zip(rhs)
-                    .map(|(left, right)| left + right)
-                    .collect()
-            }
-        }
-
         impl<T> AddAssign<T> for $vector<T>
             where T: Number + Add<Output = T>,
         {
@@ -487,31 +462,6 @@ macro_rules! impl_common_vector_methods {
             }
         }
 
-        impl<T> Sub<T> for $vector<T>
-            where T: Number,
-                  $vector<T>: FromIterator<<T as Sub>::Output>,
-        {
-            type Output = Self;
-
-            fn sub(self, rhs: T) -> Self::Output {
-                self.into_iter().map(|val| val - rhs).collect()
-            }
-        }
-
-        impl<T> Sub for $vector<T>
-            where T: Number,
-                  $vector<T>: FromIterator<<T as Sub>::Output>,
-        {
-            type Output = Self;
-
-            fn sub(self, rhs: Self) -> Self::Output {
-                self.into_iter()
-                    .zip(rhs)
-                    .map(|(left, right)| left - right)
-                    .collect()
-            }
-        }
-
         impl<T> SubAssign<T> for $vector<T>
             where T: Number + Sub<Output = T>,
         {
@@ -539,17 +489,6 @@ macro_rules! impl_common_vector_methods {
             }
         }
 
-        impl<T> Mul<T> for $vector<T>
-            where T: Number,
-                  $vector<T>: FromIterator<<T as Mul>::Output>,
-        {
-            type Output = Self;
-
-            fn mul(self, rhs: T) -> Self::Output {
-                self.into_iter().map(|val| val * rhs).collect()
-            }
-        }
-
         impl<T> MulAssign<T> for $vector<T>
             where T: Number + Mul<Output = T>,
         {
@@ -558,17 +497,6 @@ macro_rules! impl_common_vector_methods {
             }
         }
 
-        impl<T> Div<T> for $vector<T>
-            where T: Number,
-                  $vector<T>: FromIterator<<T as Div>::Output>,
-        {
-            type Output = Self;
-
-            fn div(self, rhs: T) -> Self::Output {
-                self.into_iter().map(|val| val / rhs).collect()
-            }
-        }
-
         impl<T> DivAssign<T> for $vector<T>
             where T: Number + Div<Output = T>,
         {
@@ -645,4 +573,82 @@ macro_rules! impl_common_vector_methods {
         }
     };
 }
-pub(crate) use impl_common_vector_methods;
\ No newline at end of file
+pub(crate) use impl_common_vector_methods;
+
+/// Operators with vectors and scalars, `Vec4` has its own lane-wise ones
+macro_rules! impl_arithmetic_operators {
+    ($vector:ident) => {
+        impl<T> Add<T> for $vector<T>
+            where T: Number,
+                  $vector<T>: FromIterator<<T as Add>::Output>,
+        {
+            type Output = Self;
+
+            fn add(self, rhs: T) -> Self::Output {
+                self.into_iter().map(|val| val + rhs).collect()
+            }
+        }
+
+        impl<T> Add for $vector<T>
+            where T: Number,
+                  $vector<T>: FromIterator<<T as Add>::Output>,
+        {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self::Output {
+                self.into_iter()
+                    .zip(rhs)
+                    .map(|(left, right)| left + right)
+                    .collect()
+            }
+        }
+
+        impl<T> Sub<T> for $vector<T>
+            where T: Number,
+                  $vector<T>: FromIterator<<T as Sub>::Output>,
+        {
+            type Output = Self;
+
+            fn sub(self, rhs: T) -> Self::Output {
+                self.into_iter().map(|val| val - rhs).collect()
+            }
+        }
+
+        impl<T> Sub for $vector<T>
+            where T: Number,
+                  $vector<T>: FromIterator<<T as Sub>::Output>,
+        {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                self.into_iter()
+                    .zip(rhs)
+                    .map(|(left, right)| left - right)
+                    .collect()
+            }
+        }
+
+        impl<T> Mul<T> for $vector<T>
+            where T: Number,
+                  $vector<T>: FromIterator<<T as Mul>::Output>,
+        {
+            type Output = Self;
+
+            fn mul(self, rhs: T) -> Self::Output {
+                self.into_iter().map(|val| val * rhs).collect()
+            }
+        }
+
+        impl<T> Div<T> for $vector<T>
+            where T: Number,
+                  $vector<T>: FromIterator<<T as Div>::Output>,
+        {
+            type Output = Self;
+
+            fn div(self, rhs: T) -> Self::Output {
+                self.into_iter().map(|val| val / rhs).collect()
+            }
+        }
+    };
+}
+pub(crate) use impl_arithmetic_operators;
\ No newline at end of file
@@ -5,6 +5,7 @@ use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign,
 use bytemuck::{Pod, Zeroable};
 
 use crate::va::utils::math::is_equal::IsCopyTypeEqual;
+use crate::va::utils::math::matrix::matrix3x3::Mat3x3;
 use crate::va::utils::cast::Cast;
 use crate::va::utils::number::{Number, Float, Integer, Pow};
 
@@ -22,6 +23,7 @@ pub struct Vec2<T>
 }
 
 impl_common_vector_methods!(Vec2);
+impl_arithmetic_operators!(Vec2);
 
 impl<T> Vec2<T>
     where T: Number,
@@ -44,6 +46,20 @@ impl<T> Vec2<T>
     }
 }
 
+impl<T> Vec2<T>
+    where T: Number + Float + Neg<Output = T> + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+{
+    /// Same as [`Mat3x3::transform_point`]
+    pub fn transform_point(self, matrix: &Mat3x3<T>) -> Self {
+        matrix.transform_point(self)
+    }
+
+    /// Same as [`Mat3x3::transform_vector`]
+    pub fn transform_vector(self, matrix: &Mat3x3<T>) -> Self {
+        matrix.transform_vector(self)
+    }
+}
+
 impl<T> From<T> for Vec2<T>
     where T: Number,
 {
@@ -302,6 +318,15 @@ mod tests {
         assert!(vector.is_equal(Vec2::new(0.0, 1.0), EPSILON));
     }
 
+    #[test]
+    fn vector2d_transform() {
+        let matrix = Mat3x3::with_translate(Vec2::new(10.0, 5.0)) * Mat3x3::with_rotation(std::f64::consts::FRAC_PI_2);
+        let point = Vec2::new(1.0, 0.0);
+        assert!(point.transform_point(&matrix).is_equal(matrix.transform_point(point), EPSILON));
+        assert!(point.transform_point(&matrix).is_equal(Vec2::new(10.0, 6.0), EPSILON));
+        assert!(point.transform_vector(&matrix).is_equal(Vec2::new(0.0, 1.0), EPSILON));
+    }
+
     #[test]
     fn vector2d_display() {
         let vector = Vec2::new(1, 2);
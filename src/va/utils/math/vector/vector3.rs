@@ -24,6 +24,7 @@ pub struct Vec3<T>
 }
 
 impl_common_vector_methods!(Vec3);
+impl_arithmetic_operators!(Vec3);
 
 impl<T> Vec3<T>
     where T: Number,
@@ -69,7 +70,7 @@ impl<T> Vec3<T>
     pub fn cross(self, vector: Self) -> Self {
         Vec3::new(
             self.y * vector.z - self.z * vector.y,
-            self.x * vector.z - self.z * vector.x,
+            self.z * vector.x - self.x * vector.z,
             self.x * vector.y - self.y * vector.x,
         )
     }
@@ -338,7 +339,9 @@ mod tests {
         assert!(vector.is_equal(Vec3::new(1.0, 3.0, 3.0), EPSILON));
 
         let vector = Vec3::new(12, 34, 53).cross(Vec3::new(75, 24, 12));
-        assert_eq!(vector, Vec3::new(-864, -3831, -2262));
+        assert_eq!(vector, Vec3::new(-864, 3831, -2262));
+        assert_eq!(Vec3::<i32>::X.cross(Vec3::Y), Vec3::Z);
+        assert_eq!(Vec3::<i32>::Z.cross(Vec3::X), Vec3::Y);
 
         let vector = Vec3::new(1.0, 2.0, 3.0);
         assert!(vector.sqr_length().is_equal(14.0, EPSILON));
@@ -167,6 +167,85 @@ impl<T> IndexMut<usize> for Vec4<T>
     }
 }
 
+impl<T> From<Vec4<T>> for [T; LENGTH]
+    where T: Number,
+{
+    fn from(value: Vec4<T>) -> Self {
+        [value.x, value.y, value.z, value.w]
+    }
+}
+
+impl<T> Vec4<T>
+    where T: Number,
+{
+    fn from_lanes([x, y, z, w]: [T; LENGTH]) -> Self {
+        Self {
+            x, y, z, w
+        }
+    }
+}
+
+// Lane-wise, so `Vec4<f32>` gets the SIMD path of `Lanes`
+impl<T> Add<T> for Vec4<T>
+    where T: Number,
+{
+    type Output = Self;
+
+    fn add(self, rhs: T) -> Self::Output {
+        Self::from_lanes(T::add4(self.into(), [rhs; LENGTH]))
+    }
+}
+
+impl<T> Add for Vec4<T>
+    where T: Number,
+{
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::from_lanes(T::add4(self.into(), rhs.into()))
+    }
+}
+
+impl<T> Sub<T> for Vec4<T>
+    where T: Number,
+{
+    type Output = Self;
+
+    fn sub(self, rhs: T) -> Self::Output {
+        Self::from_lanes(T::sub4(self.into(), [rhs; LENGTH]))
+    }
+}
+
+impl<T> Sub for Vec4<T>
+    where T: Number,
+{
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::from_lanes(T::sub4(self.into(), rhs.into()))
+    }
+}
+
+impl<T> Mul<T> for Vec4<T>
+    where T: Number,
+{
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::from_lanes(T::mul4(self.into(), [rhs; LENGTH]))
+    }
+}
+
+impl<T> Div<T> for Vec4<T>
+    where T: Number,
+{
+    type Output = Self;
+
+    fn div(self, rhs: T) -> Self::Output {
+        Self::from_lanes(T::div4(self.into(), [rhs; LENGTH]))
+    }
+}
+
 impl<T> Display for Vec4<T>
     where T: Number,
 {
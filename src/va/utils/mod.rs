@@ -5,4 +5,6 @@ pub mod handle_container;
 pub mod idvector;
 pub mod iter;
 pub mod number;
-pub mod primitive_number;
\ No newline at end of file
+pub mod primitive_number;
+pub mod rect_packer;
+pub mod shelf_packer;
\ No newline at end of file
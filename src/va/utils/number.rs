@@ -2,6 +2,8 @@ use std::fmt::{Debug, Display};
 use std::iter::Sum;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Rem, RemAssign, Sub, SubAssign};
 
+use crate::utils::math::simd::Lanes;
+
 pub trait Number:
     Add
     + AddAssign
@@ -11,6 +13,7 @@ pub trait Number:
     + Display
     + Div
     + DivAssign
+    + Lanes
     + Mul
     + MulAssign
     + PartialEq
@@ -94,13 +97,18 @@ impl_integer_methods!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, i
 
 pub trait Float {
     fn new(value: f64) -> Self;
+    fn acos(self) -> Self;
+    fn atan2(self, x: Self) -> Self;
     fn ceil(self) -> Self;
     fn clamp(self, min: Self, max: Self) -> Self;
+    fn cos(self) -> Self;
     fn floor(self) -> Self;
     fn powi(self, n: i32) -> Self;
     fn powf(self, n: Self) -> Self;
     fn round(self) -> Self;
+    fn sin(self) -> Self;
     fn sqrt(self) -> Self;
+    fn tan(self) -> Self;
 }
 
 macro_rules! impl_float_methods {
@@ -111,6 +119,14 @@ macro_rules! impl_float_methods {
                     value as $n
                 }
 
+                fn acos(self) -> Self {
+                    self.acos()
+                }
+
+                fn atan2(self, x: Self) -> Self {
+                    self.atan2(x)
+                }
+
                 fn ceil(self) -> Self {
                     self.ceil()
                 }
@@ -119,6 +135,10 @@ macro_rules! impl_float_methods {
                     self.clamp(min, max)
                 }
 
+                fn cos(self) -> Self {
+                    self.cos()
+                }
+
                 fn floor(self) -> Self {
                     self.floor()
                 }
@@ -135,9 +155,17 @@ macro_rules! impl_float_methods {
                     self.round()
                 }
 
+                fn sin(self) -> Self {
+                    self.sin()
+                }
+
                 fn sqrt(self) -> Self {
                     self.sqrt()
                 }
+
+                fn tan(self) -> Self {
+                    self.tan()
+                }
             }
         )*
     };
@@ -0,0 +1,191 @@
+use crate::utils::math::geometry::rect::Rect;
+use crate::utils::math::vector::vector2::Vec2;
+
+#[derive(Clone, Copy, Debug)]
+struct SkylineNode {
+    x: usize,
+    y: usize,
+    width: usize,
+}
+
+/// Skyline bottom-left rectangle packer.
+///
+/// Returned rects have exclusive `p2`, the same as `Buffer2d` slices.
+// http://pds25.egloos.com/pds/201504/21/98/RectangleBinPack.pdf
+#[derive(Debug)]
+pub struct RectPacker {
+    size: Vec2<usize>,
+    skyline: Vec<SkylineNode>,
+    used_size: Vec2<usize>,
+}
+
+impl RectPacker {
+    pub fn new(size: Vec2<usize>) -> Self {
+        Self {
+            size,
+            skyline: vec![SkylineNode { x: 0, y: 0, width: size.x }],
+            used_size: Vec2::ZERO,
+        }
+    }
+
+    pub fn size(&self) -> Vec2<usize> {
+        self.size
+    }
+
+    /// Size of the area which contains all packed rects.
+    pub fn used_size(&self) -> Vec2<usize> {
+        self.used_size
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new(self.size);
+    }
+
+    /// Returns `None` if there is no free space for the rect.
+    pub fn pack(&mut self, size: Vec2<usize>) -> Option<Rect<usize>> {
+        if size.x == 0 || size.y == 0 {
+            return Some(Rect::new(Vec2::ZERO, Vec2::ZERO));
+        }
+
+        let mut best: Option<(usize, usize, usize)> = None;
+
+        for i in 0..self.skyline.len() {
+            let y = match self.fit(i, size) {
+                Some(val) => val,
+                None => continue,
+            };
+
+            let width = self.skyline[i].width;
+            let is_better = match best {
+                Some((_, best_y, best_width)) => {
+                    y < best_y || (y == best_y && width < best_width)
+                }
+                None => true,
+            };
+
+            if is_better {
+                best = Some((i, y, width));
+            }
+        }
+
+        let (i, y, _) = best?;
+        let x = self.skyline[i].x;
+        self.insert(i, Vec2::new(x, y), size);
+
+        let rect = Rect::new(Vec2::new(x, y), Vec2::new(x, y) + size);
+        self.used_size = Vec2::new(
+            self.used_size.x.max(rect.p2.x),
+            self.used_size.y.max(rect.p2.y),
+        );
+
+        Some(rect)
+    }
+
+    fn fit(&self, i: usize, size: Vec2<usize>) -> Option<usize> {
+        let x = self.skyline[i].x;
+        if x + size.x > self.size.x {
+            return None;
+        }
+
+        let mut width_left = size.x;
+        let mut y = self.skyline[i].y;
+        let mut j = i;
+
+        while width_left > 0 {
+            y = y.max(self.skyline[j].y);
+            if y + size.y > self.size.y {
+                return None;
+            }
+
+            width_left -= width_left.min(self.skyline[j].width);
+            j += 1;
+        }
+
+        Some(y)
+    }
+
+    fn insert(&mut self, i: usize, position: Vec2<usize>, size: Vec2<usize>) {
+        self.skyline.insert(i, SkylineNode {
+            x: position.x,
+            y: position.y + size.y,
+            width: size.x,
+        });
+
+        // Shrink nodes shadowed by the new one
+        let right = position.x + size.x;
+        while i + 1 < self.skyline.len() {
+            let node = &mut self.skyline[i + 1];
+            if node.x >= right {
+                break;
+            }
+
+            let shrink = right - node.x;
+            if shrink < node.width {
+                node.x += shrink;
+                node.width -= shrink;
+                break;
+            }
+
+            self.skyline.remove(i + 1);
+        }
+
+        // Merge neighbours with the same height
+        let mut j = 0;
+        while j + 1 < self.skyline.len() {
+            if self.skyline[j].y == self.skyline[j + 1].y {
+                self.skyline[j].width += self.skyline[j + 1].width;
+                self.skyline.remove(j + 1);
+            }
+            else {
+                j += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_overlapping(a: Rect<usize>, b: Rect<usize>) -> bool {
+        a.p1.x < b.p2.x && b.p1.x < a.p2.x && a.p1.y < b.p2.y && b.p1.y < a.p2.y
+    }
+
+    #[test]
+    fn rect_packer_pack() {
+        let mut packer = RectPacker::new(Vec2::new(16, 16));
+
+        let mut rects = Vec::new();
+        for size in [(8, 8), (8, 4), (4, 4), (4, 4), (16, 2), (3, 6)] {
+            let rect = packer.pack(Vec2::from(size)).unwrap();
+            assert_eq!(rect.p2 - rect.p1, Vec2::from(size));
+            assert!(rect.p2.x <= 16 && rect.p2.y <= 16);
+
+            for &other in &rects {
+                assert!(!is_overlapping(rect, other));
+            }
+
+            rects.push(rect);
+        }
+
+        assert_eq!(rects[0], Rect::new(Vec2::new(0, 0), Vec2::new(8, 8)));
+        assert_eq!(rects[1], Rect::new(Vec2::new(8, 0), Vec2::new(16, 4)));
+
+        assert!(packer.used_size().y <= 16);
+        assert!(packer.pack(Vec2::new(17, 1)).is_none());
+    }
+
+    #[test]
+    fn rect_packer_full() {
+        let mut packer = RectPacker::new(Vec2::new(4, 4));
+        for _ in 0..4 {
+            assert!(packer.pack(Vec2::new(2, 2)).is_some());
+        }
+
+        assert!(packer.pack(Vec2::new(1, 1)).is_none());
+        assert_eq!(packer.used_size(), Vec2::new(4, 4));
+
+        packer.clear();
+        assert!(packer.pack(Vec2::new(4, 4)).is_some());
+    }
+}
@@ -0,0 +1,205 @@
+use crate::utils::math::geometry::rect::Rect;
+use crate::utils::math::vector::vector2::Vec2;
+
+#[derive(Debug)]
+struct Shelf {
+    y: usize,
+    height: usize,
+
+    /// Sorted free spans `(x, width)`
+    free: Vec<(usize, usize)>,
+}
+
+impl Shelf {
+    fn new(y: usize, height: usize, width: usize) -> Self {
+        Self {
+            y,
+            height,
+            free: vec![(0, width)],
+        }
+    }
+
+    fn is_empty(&self, width: usize) -> bool {
+        self.free.len() == 1 && self.free[0] == (0, width)
+    }
+
+    fn allocate(&mut self, width: usize) -> Option<usize> {
+        let i = self.free.iter().position(|&(_, free_width)| free_width >= width)?;
+        let (x, free_width) = self.free[i];
+
+        if free_width == width {
+            self.free.remove(i);
+        }
+        else {
+            self.free[i] = (x + width, free_width - width);
+        }
+
+        Some(x)
+    }
+
+    fn deallocate(&mut self, x: usize, width: usize) {
+        let i = self.free.partition_point(|&(free_x, _)| free_x < x);
+        self.free.insert(i, (x, width));
+
+        // Merge with the next span, then with the previous one
+        if i + 1 < self.free.len() && x + width == self.free[i + 1].0 {
+            self.free[i].1 += self.free[i + 1].1;
+            self.free.remove(i + 1);
+        }
+
+        if i > 0 && self.free[i - 1].0 + self.free[i - 1].1 == x {
+            self.free[i - 1].1 += self.free[i].1;
+            self.free.remove(i);
+        }
+    }
+}
+
+/// Shelf rectangle packer which supports freeing of packed rects.
+///
+/// Returned rects have exclusive `p2`, the same as `Buffer2d` slices.
+#[derive(Debug)]
+pub struct ShelfPacker {
+    size: Vec2<usize>,
+    shelves: Vec<Shelf>,
+}
+
+impl ShelfPacker {
+    pub fn new(size: Vec2<usize>) -> Self {
+        Self {
+            size,
+            shelves: Vec::new(),
+        }
+    }
+
+    pub fn size(&self) -> Vec2<usize> {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shelves.iter().all(|shelf| shelf.is_empty(self.size.x))
+    }
+
+    pub fn clear(&mut self) {
+        self.shelves.clear();
+    }
+
+    /// Returns `None` if there is no free space for the rect.
+    pub fn pack(&mut self, size: Vec2<usize>) -> Option<Rect<usize>> {
+        if size.x == 0 || size.y == 0 {
+            return Some(Rect::new(Vec2::ZERO, Vec2::ZERO));
+        }
+
+        if size.x > self.size.x {
+            return None;
+        }
+
+        // The lowest suitable shelf wastes less space
+        let mut best: Option<usize> = None;
+        for (i, shelf) in self.shelves.iter().enumerate() {
+            if shelf.height < size.y || !shelf.free.iter().any(|&(_, width)| width >= size.x) {
+                continue;
+            }
+
+            // Don't waste a lot of space with high shelves, unless they are empty
+            if shelf.height > size.y * 2 && !shelf.is_empty(self.size.x) {
+                continue;
+            }
+
+            if best.is_none_or(|best| shelf.height < self.shelves[best].height) {
+                best = Some(i);
+            }
+        }
+
+        let i = match best {
+            Some(val) => val,
+            None => {
+                let y = self.shelves.last().map_or(0, |shelf| shelf.y + shelf.height);
+                if y + size.y > self.size.y {
+                    return None;
+                }
+
+                self.shelves.push(Shelf::new(y, size.y, self.size.x));
+                self.shelves.len() - 1
+            }
+        };
+
+        let shelf = &mut self.shelves[i];
+        let x = shelf.allocate(size.x)?;
+        let position = Vec2::new(x, shelf.y);
+
+        Some(Rect::new(position, position + size))
+    }
+
+    /// Frees the rect returned by [`ShelfPacker::pack`].
+    pub fn unpack(&mut self, rect: Rect<usize>) {
+        let width = rect.p2.x - rect.p1.x;
+        if width == 0 || rect.p2.y == rect.p1.y {
+            return;
+        }
+
+        let i = match self.shelves.iter().position(|shelf| shelf.y == rect.p1.y) {
+            Some(val) => val,
+            None => return,
+        };
+
+        self.shelves[i].deallocate(rect.p1.x, width);
+
+        // Merge empty neighbours, so higher rects can use the space
+        let width = self.size.x;
+        let mut j = 0;
+        while j + 1 < self.shelves.len() {
+            if self.shelves[j].is_empty(width) && self.shelves[j + 1].is_empty(width) {
+                self.shelves[j].height += self.shelves[j + 1].height;
+                self.shelves.remove(j + 1);
+            }
+            else {
+                j += 1;
+            }
+        }
+
+        if self.shelves.last().is_some_and(|shelf| shelf.is_empty(width)) {
+            self.shelves.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shelf_packer_pack() {
+        let mut packer = ShelfPacker::new(Vec2::new(8, 8));
+
+        let a = packer.pack(Vec2::new(4, 4)).unwrap();
+        let b = packer.pack(Vec2::new(4, 3)).unwrap();
+        let c = packer.pack(Vec2::new(8, 4)).unwrap();
+
+        assert_eq!(a, Rect::new(Vec2::new(0, 0), Vec2::new(4, 4)));
+        assert_eq!(b, Rect::new(Vec2::new(4, 0), Vec2::new(8, 3)));
+        assert_eq!(c, Rect::new(Vec2::new(0, 4), Vec2::new(8, 8)));
+
+        assert!(packer.pack(Vec2::new(1, 1)).is_none());
+        assert!(packer.pack(Vec2::new(9, 1)).is_none());
+    }
+
+    #[test]
+    fn shelf_packer_unpack() {
+        let mut packer = ShelfPacker::new(Vec2::new(8, 8));
+
+        let a = packer.pack(Vec2::new(4, 4)).unwrap();
+        let b = packer.pack(Vec2::new(4, 4)).unwrap();
+        let c = packer.pack(Vec2::new(8, 4)).unwrap();
+        assert!(packer.pack(Vec2::new(4, 4)).is_none());
+
+        packer.unpack(b);
+        assert_eq!(packer.pack(Vec2::new(4, 4)), Some(b));
+
+        // Empty shelves are merged and can hold a higher rect
+        packer.unpack(a);
+        packer.unpack(b);
+        packer.unpack(c);
+        assert!(packer.is_empty());
+        assert_eq!(packer.pack(Vec2::new(8, 8)), Some(Rect::new(Vec2::ZERO, Vec2::new(8, 8))));
+    }
+}
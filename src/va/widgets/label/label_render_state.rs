@@ -36,6 +36,15 @@ pub struct LabelRenderStateVertex {
 }
 vulkano::impl_vertex!(LabelRenderStateVertex, position, tex_coords);
 
+impl LabelRenderStateVertex {
+    pub fn new(position: Vec2<f32>, tex_coords: Vec2<f32>) -> Self {
+        Self {
+            position: [position.x, position.y],
+            tex_coords: [tex_coords.x, tex_coords.y],
+        }
+    }
+}
+
 impl LabelRenderState {
     pub fn new(manager: &Rc<Manager>, device: Arc<Device>, render_pass: Arc<RenderPass>, image_view: Arc<ImageView<ImmutableImage>>) 
         -> anyhow::Result<Rc<Self>>
@@ -99,11 +108,11 @@ impl LabelRenderState {
     }
 }
 
-impl RenderState<Vec2<f32>> for LabelRenderState {
+impl RenderState<LabelRenderStateVertex> for LabelRenderState {
     fn command_buffer(
         &self,
         graphics: &Rc<Graphics>,
-        buffer: &Arc<CpuAccessibleBuffer<[Vec2<f32>]>>,
+        buffer: &Arc<CpuAccessibleBuffer<[LabelRenderStateVertex]>>,
         framebuffer: Arc<Framebuffer>,
         viewport: Viewport,
     ) -> anyhow::Result<PrimaryAutoCommandBuffer> 
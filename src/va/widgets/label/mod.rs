@@ -6,46 +6,99 @@ use std::rc::Rc;
 use std::sync::Arc;
 
 use vulkano::device::Device;
+use vulkano::image::ImmutableImage;
 use vulkano::image::view::ImageView;
 use vulkano::render_pass::RenderPass;
 
 use crate::graphics::font::Font;
 use crate::graphics::mesh::Mesh;
+use crate::graphics::text_layout::{TextLayout, TextLayoutBuilder};
 use crate::layer::Layer;
 use crate::manager::Manager;
 use crate::object::Object;
 use crate::global::Va;
+use crate::utils::math::geometry::rect::Rect;
 use crate::utils::math::vector::vector2::Vec2;
 
-use self::label_render_state::LabelRenderState;
+use self::label_render_state::{LabelRenderState, LabelRenderStateVertex};
 
 pub struct Label {
     text: String,
     font: Rc<Font>,
+    layout: TextLayout,
 
-    mesh: RefCell<Option<Rc<Mesh<Vec2<f32>>>>>,
+    mesh: RefCell<Option<Rc<Mesh<LabelRenderStateVertex>>>>,
     render_state: Rc<LabelRenderState>,
 }
 
 impl Label {
-    pub fn new(manager: &Rc<Manager>, device: Arc<Device>, render_pass: Arc<RenderPass>, font: Rc<Font>, text: &str) 
-        -> anyhow::Result<Self> 
+    pub fn new(
+        manager: &Rc<Manager>,
+        device: Arc<Device>,
+        render_pass: Arc<RenderPass>,
+        font: Rc<Font>,
+        image_view: Arc<ImageView<ImmutableImage>>,
+        text: &str,
+        layout_builder: &TextLayoutBuilder,
+    ) -> anyhow::Result<Self> 
     {
+        let layout = layout_builder.build(&font, text);
+        let mesh = Label::create_mesh(&layout);
+
         let label = Self { 
             text: text.to_string(),
             font: Rc::clone(&font),
+            layout,
 
-            mesh: Default::default(),
+            mesh: RefCell::new(Some(mesh)),
             render_state: LabelRenderState::new(
                 manager, 
                 device, 
                 render_pass, 
-                ImageView::new_default(Arc::clone(&font.image))?,
+                image_view,
             )?,
         };
 
         Ok(label)
     }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn font(&self) -> &Rc<Font> {
+        &self.font
+    }
+
+    /// Measured bounds of the text in pixels
+    pub fn rect(&self) -> Rect<f32> {
+        self.layout.rect()
+    }
+
+    /// Two triangles per glyph, positions are in pixels
+    fn create_mesh(layout: &TextLayout) -> Rc<Mesh<LabelRenderStateVertex>> {
+        let mut vertices = Vec::with_capacity(layout.glyphs().len() * 6);
+
+        for glyph in layout.glyphs() {
+            let rect = glyph.rect();
+            let uv_rect = glyph.char_info().uv_rect();
+
+            let top_left = LabelRenderStateVertex::new(rect.p1, uv_rect.p1);
+            let top_right = LabelRenderStateVertex::new(
+                Vec2::new(rect.p2.x, rect.p1.y),
+                Vec2::new(uv_rect.p2.x, uv_rect.p1.y),
+            );
+            let bottom_left = LabelRenderStateVertex::new(
+                Vec2::new(rect.p1.x, rect.p2.y),
+                Vec2::new(uv_rect.p1.x, uv_rect.p2.y),
+            );
+            let bottom_right = LabelRenderStateVertex::new(rect.p2, uv_rect.p2);
+
+            vertices.extend([top_left, top_right, bottom_left, bottom_left, top_right, bottom_right]);
+        }
+
+        Mesh::new(vertices)
+    }
 }
 
 impl Object for Label {